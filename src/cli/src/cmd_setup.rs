@@ -1,5 +1,6 @@
 use clap::{arg, Arg, Command};
 use liboxen::constants::{DEFAULT_BRANCH_NAME, DEFAULT_REMOTE_NAME};
+use liboxen::model::schema::FieldMetadata;
 
 pub const ADD: &str = "add";
 pub const BRANCH: &str = "branch";
@@ -12,6 +13,8 @@ pub const CREATE_REMOTE: &str = "create-remote";
 pub const DF: &str = "df";
 pub const DIFF: &str = "diff";
 pub const DOWNLOAD: &str = "download";
+pub const DU: &str = "du";
+pub const INDEX: &str = "index";
 pub const INIT: &str = "init";
 pub const KVDB_INSPECT: &str = "kvdb-inspect";
 pub const LOG: &str = "log";
@@ -24,6 +27,7 @@ pub const REMOTE: &str = "remote";
 pub const RESTORE: &str = "restore";
 pub const RM: &str = "rm";
 pub const SCHEMAS: &str = "schemas";
+pub const SPLIT: &str = "split";
 pub const STATUS: &str = "status";
 
 pub fn init() -> Command {
@@ -183,7 +187,7 @@ pub fn ls() -> Command {
 
 pub fn df() -> Command {
     Command::new(DF)
-        .about("View and transform data frames. Supported types: csv, tsv, ndjson, jsonl, parquet.")
+        .about("View and transform data frames. Supported types: csv, tsv, json, ndjson, jsonl, parquet, arrow, arrows (ipc stream), avro, and gzip or zstd compressed versions of all but json ex) data.csv.gz.")
         .arg(arg!(<DF_SPEC> ... "The DataFrame you want to process. If in the schema subcommand the schema ref."))
         .arg_required_else_help(true)
         .arg(
@@ -211,14 +215,14 @@ pub fn df() -> Command {
             Arg::new("filter")
                 .long("filter")
                 .short('f')
-                .help("An filter the row data based on an expression. Supported Ops (=, !=, >, <, <= , >=) Supported dtypes (str,int,float)")
+                .help("Filter the row data based on an expression. Supported Ops (==, !=, >, <, <=, >=, in [..], contains, =~, is null, is not null) combined with &&, ||, not and parentheses. Values are compared using the column dtype (str,int,float,bool,date,datetime)")
                 .action(clap::ArgAction::Set),
         )
         .arg(
             Arg::new("aggregate")
                 .long("aggregate")
                 .short('a')
-                .help("Aggregate up values based on field.")
+                .help("Aggregate up values based on fields. Args can be columns or expressions, name outputs with 'as'. Ex) \"('label') -> (count('file') as n, mean(width * height) as area, quantile('score', 0.9))\" or \"() -> (value_counts('label'))\"")
                 .action(clap::ArgAction::Set),
         )
         .arg(
@@ -280,6 +284,36 @@ pub fn df() -> Command {
                 .help("Add a column with a default value to the data table. If used with --add-row, row is added first, then column. Format 'name:val:dtype'")
                 .action(clap::ArgAction::Set),
         )
+        .arg(
+            Arg::new("cast")
+                .long("cast")
+                .help("Convert columns to another data type. Format 'name:dtype,name:dtype' Ex) 'width:i64,score:f32'")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("with-column")
+                .long("with-column")
+                .help("Add or replace a column computed from the others, can be repeated. Supports + - * / %, concat, lower, upper, cast and fill_null. Ex) \"area = width * height\" or \"path = concat('images/', file)\"")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("pivot")
+                .long("pivot")
+                .help("Turn the distinct values of a column into their own columns, keeping the first value for each index. Format 'index:columns:values' Ex) 'file:label:score'")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("melt")
+                .long("melt")
+                .help("Unpivot every column except the id columns into 'variable' and 'value' rows. Format 'id_col,id_col'")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("explode")
+                .long("explode")
+                .help("Give each value of a list column its own row. Format 'list_col,list_col'")
+                .action(clap::ArgAction::Set),
+        )
         .arg(
             Arg::new("add-row")
                 .long("add-row")
@@ -312,6 +346,48 @@ pub fn df() -> Command {
                 .help("Unique the output by a set of column names. Takes a comma separated set of column names ie: \"text,label\".")
                 .action(clap::ArgAction::Set),
         )
+        .arg(
+            Arg::new("sql")
+                .long("sql")
+                .help("Run a SQL query on the data frame, which is the table named 'df'. Ex) \"SELECT label, count(*) FROM df GROUP BY label\"")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("table")
+                .long("table")
+                .help("Register another file as a named table to reference from --sql. Format 'name=path'")
+                .action(clap::ArgAction::Append),
+        )
+        .arg(
+            Arg::new("join")
+                .long("join")
+                .help("Join with another tabular file. Use with --on and --how.")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("on")
+                .long("on")
+                .help("A comma separated set of columns to join on, they must exist in both files. Ex file,label")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("how")
+                .long("how")
+                .help("How to join the files. Valid values are 'inner', 'left', 'outer'. Default is 'inner'")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("revision")
+                .long("revision")
+                .help("Read the version of the file from this commit or branch without checking it out")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("join-revision")
+                .long("join-revision")
+                .help("Join with the version of the --join file from this commit or branch")
+                .action(clap::ArgAction::Set),
+        )
         .arg(
             Arg::new("randomize")
                 .long("randomize")
@@ -336,17 +412,107 @@ pub fn df() -> Command {
                 .help("Print the full list of columns and data types within the schema.")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("stats")
+                .long("stats")
+                .help("Print the row count and the nulls, min, max, distinct and most common values of each column.")
+                .action(clap::ArgAction::SetTrue),
+        )
+}
+
+pub fn split() -> Command {
+    Command::new(SPLIT)
+        .about("Split the rows of a tabular file into reproducible train/val/test files. Rows are assigned by hashing their key, and rows already in an existing split file stay in it.")
+        .arg(arg!(<PATH> ... "The tabular file to split"))
+        .arg(
+            Arg::new("ratios")
+                .long("ratios")
+                .required(true)
+                .help("Fraction of the rows in each split, must add up to 1. Ex) '0.8,0.1,0.1'")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("names")
+                .long("names")
+                .help("Names of the splits. Defaults to 'train,test' or 'train,val,test'")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("stratify")
+                .long("stratify")
+                .help("Keep the distribution of values in this column the same in every split")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("key")
+                .long("key")
+                .help("Columns that identify a row, rows with the same key are always in the same split. Defaults to every column. Format 'col,col'")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("seed")
+                .long("seed")
+                .default_value("0")
+                .help("Seed for hashing the keys")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("output")
+                .long("output")
+                .short('o')
+                .help("Directory to write the splits to. Defaults to the directory of the file, as 'name_train.ext'")
+                .action(clap::ArgAction::Set),
+        )
+        .arg(
+            Arg::new("add")
+                .long("add")
+                .help("Stage the split files")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("commit")
+                .long("commit")
+                .short('m')
+                .help("Stage and commit the split files with this message")
+                .action(clap::ArgAction::Set),
+        )
+}
+
+pub fn index() -> Command {
+    Command::new(INDEX)
+        .about("Index a schema field so equality filters in `oxen df` look up rows instead of scanning files. Indices are rebuilt for every tabular file with the schema on each commit.")
+        .subcommand_required(true)
+        .subcommand(
+            Command::new("create")
+                .about("Index a field for every file with the schema at HEAD")
+                .arg(arg!(<SCHEMA> "Name or hash of the schema"))
+                .arg(arg!(<FIELD> "Name of the field to index")),
+        )
+        .subcommand(Command::new("list").about("List the indexed fields at HEAD"))
+        .subcommand(
+            Command::new("drop")
+                .about("Remove the index on a field")
+                .arg(arg!(<SCHEMA> "Name or hash of the schema"))
+                .arg(arg!(<FIELD> "Name of the indexed field")),
+        )
 }
 
 pub fn schemas() -> Command {
     Command::new(SCHEMAS)
         .about("Manage schemas that are created from committing tabular data")
         .subcommand(
-            Command::new("list").arg(
-                Arg::new("staged")
-                    .long("staged")
-                    .help("List the staged schemas"),
-            ),
+            Command::new("list")
+                .arg(
+                    Arg::new("staged")
+                        .long("staged")
+                        .help("List the staged schemas"),
+                )
+                .arg(
+                    Arg::new("revision")
+                        .long("revision")
+                        .help("List the schemas committed at this commit or branch")
+                        .action(clap::ArgAction::Set),
+                ),
         )
         .subcommand(
             Command::new("show")
@@ -355,6 +521,12 @@ pub fn schemas() -> Command {
                     Arg::new("staged")
                         .long("staged")
                         .help("Show the staged schema"),
+                )
+                .arg(
+                    Arg::new("revision")
+                        .long("revision")
+                        .help("Show the schema committed at this commit or branch")
+                        .action(clap::ArgAction::Set),
                 ),
         )
         .subcommand(
@@ -362,6 +534,102 @@ pub fn schemas() -> Command {
                 .arg(Arg::new("HASH").help("Hash of the schema you want to name."))
                 .arg(Arg::new("NAME").help("Name of the schema.")),
         )
+        .subcommand(
+            Command::new("field")
+                .about("Describe a field of a staged schema, values outside its constraints are flagged by `oxen df`")
+                .arg(arg!(<NAME_OR_HASH> "Name or the hash of the schema."))
+                .arg(arg!(<FIELD> "Name of the field."))
+                .arg(
+                    Arg::new("description")
+                        .long("description")
+                        .help("What the field holds")
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("unit")
+                        .long("unit")
+                        .help("Unit of the values, ex) 'px'")
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("values")
+                        .long("values")
+                        .help("Comma separated values the field may take")
+                        .value_delimiter(',')
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("min")
+                        .long("min")
+                        .help("Smallest value the field may take")
+                        .value_parser(clap::value_parser!(f64))
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("max")
+                        .long("max")
+                        .help("Largest value the field may take")
+                        .value_parser(clap::value_parser!(f64))
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("pii")
+                        .long("pii")
+                        .help("Mark the field as holding personally identifiable information")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("no-pii")
+                        .long("no-pii")
+                        .help("Mark the field as not holding personally identifiable information")
+                        .conflicts_with("pii")
+                        .action(clap::ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("clear")
+                        .long("clear")
+                        .help("Comma separated values to remove, ex) 'unit,min'")
+                        .value_delimiter(',')
+                        .value_parser(FieldMetadata::KEYS)
+                        .action(clap::ArgAction::Set),
+                ),
+        )
+        .subcommand(
+            Command::new("pin")
+                .about("Require the tabular files matching a path glob to have a schema, staged in .oxencontracts and checked at commit and push")
+                .arg(arg!(<PATH> "Path glob relative to the repository root, ex) 'annotations/**/*.csv'"))
+                .arg(arg!(<NAME_OR_HASH> "Name or the hash of the schema the files must have."))
+                .arg(
+                    Arg::new("not-null")
+                        .long("not-null")
+                        .help("Comma separated columns that may not contain null values")
+                        .value_delimiter(',')
+                        .action(clap::ArgAction::Set),
+                )
+                .arg(
+                    Arg::new("allow-extra")
+                        .long("allow-extra")
+                        .help("Allow columns that are not in the schema")
+                        .action(clap::ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("unpin")
+                .about("Remove the schema pinned to a path glob, staged in .oxencontracts")
+                .arg(arg!(<PATH> "Path glob the schema was pinned to")),
+        )
+        .subcommand(
+            Command::new("diff")
+                .about("Show the added, removed, renamed and retyped fields of the schemas that changed between two revisions")
+                .arg(arg!(<REV1> "The base commit or branch"))
+                .arg(arg!(<REV2> "The commit or branch to compare to the base"))
+                .arg(arg!([NAME_OR_HASH] "Only compare the files with this schema")),
+        )
+        .subcommand(
+            Command::new("log")
+                .about("List the commits that changed the schema of a tabular file")
+                .arg(arg!(<PATH> "Path of the tabular file")),
+        )
         .subcommand(df())
 }
 
@@ -594,9 +862,39 @@ pub fn pull() -> Command {
 
 pub fn diff() -> Command {
     Command::new(DIFF)
-        .about("Compare two files against each other or against versions. The first parameter can be one of three things 1) another file 2) a commit hash 3) a branch name. If the first parameter is a committish it will compare the second parameter path to that version of the file.")
+        .about("Compare two files against each other or against versions. The first parameter can be one of three things 1) another file 2) a commit hash 3) a branch name. If the first parameter is a committish it will compare the second parameter path to that version of the file. Two revisions given as base..head compare the versions of the path from each revision.")
         .arg(Arg::new("FILE_OR_COMMITTISH").required(true))
         .arg(Arg::new("PATH").required(false))
+        .arg(
+            Arg::new("stat")
+                .long("stat")
+                .help("Summarize the changes between two revisions given as base..head, grouped by directory and data type. If only one revision is given it is compared to HEAD.")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("json")
+                .long("json")
+                .help("Output the --stat summary as json")
+                .action(clap::ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("mask")
+                .long("mask")
+                .help("When comparing two versions of an image, write a mask of the changed pixels to this path")
+                .action(clap::ArgAction::Set),
+        )
+}
+
+pub fn du() -> Command {
+    Command::new(DU)
+        .about("Show the size of the repository at HEAD, with its largest directories and files")
+        .arg(arg!([PATH] "Only count the files in this directory. Defaults to the whole repository."))
+        .arg(
+            Arg::new("history")
+                .long("history")
+                .help("Chart the size after every commit, to find the commits that grew it the most")
+                .action(clap::ArgAction::SetTrue),
+        )
 }
 
 pub fn commit_cache() -> Command {
@@ -610,6 +908,12 @@ pub fn commit_cache() -> Command {
                 .help("Compute the cache for all the oxen repositories in this directory")
                 .action(clap::ArgAction::SetTrue),
         )
+        .arg(
+            Arg::new("status")
+                .long("status")
+                .help("Print the status of each cacher and the files they failed on without computing anything")
+                .action(clap::ArgAction::SetTrue),
+        )
         .arg(arg!([COMMITTISH] "The commit or branch id you want to compute the cache for. Defaults to main."))
 }
 
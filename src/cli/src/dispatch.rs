@@ -5,6 +5,7 @@ use liboxen::constants;
 use liboxen::error;
 use liboxen::error::OxenError;
 use liboxen::model::schema;
+use liboxen::model::schema::FieldMetadata;
 use liboxen::model::{staged_data::StagedDataOpts, LocalRepository};
 use liboxen::opts::AddOpts;
 use liboxen::opts::CloneOpts;
@@ -13,6 +14,7 @@ use liboxen::opts::LogOpts;
 use liboxen::opts::PaginateOpts;
 use liboxen::opts::RestoreOpts;
 use liboxen::opts::RmOpts;
+use liboxen::opts::SplitOpts;
 use liboxen::util;
 
 use colored::Colorize;
@@ -262,6 +264,63 @@ pub async fn diff(commit_id: Option<&str>, path: &str, remote: bool) -> Result<(
     Ok(())
 }
 
+pub fn diff_image(commit_id: Option<&str>, path: &str, mask: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    let path = Path::new(path);
+    let mask = Path::new(mask);
+
+    let result = command::diff::diff_image(&repository, commit_id, path, Some(mask))?;
+    println!("{result}");
+    if let Some(mask_path) = &result.mask_path {
+        println!("\nWrote difference mask to {mask_path:?}");
+    }
+    Ok(())
+}
+
+pub fn du(path: Option<&str>, history: bool) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    let path = match path {
+        Some(path) => command::du::relative_path(&repository, repo_dir.join(path))?,
+        None => PathBuf::new(),
+    };
+
+    if history {
+        let entries = command::du::du_history(&repository)?;
+        println!("{}", command::du::format_history(&entries, &path));
+    } else {
+        let stats = command::du::du(&repository)?;
+        println!("{}", command::du::format_usage(&stats, &path)?);
+    }
+    Ok(())
+}
+
+pub fn diff_summary(base_head: &str, is_json: bool) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    // Accept base..head, or a single revision to compare against HEAD
+    let (base, head) = match base_head.split_once("..") {
+        Some((base, head)) => (base.to_string(), head.to_string()),
+        None => {
+            let head = api::local::commits::head_commit(&repository)?;
+            (base_head.to_string(), head.id)
+        }
+    };
+
+    let summary = command::diff::summary(&repository, &base, &head)?;
+    if is_json {
+        match serde_json::to_string_pretty(&summary) {
+            Ok(json) => println!("{json}"),
+            Err(err) => return Err(OxenError::basic_str(format!("{err}"))),
+        }
+    } else {
+        println!("{summary}");
+    }
+    Ok(())
+}
+
 pub fn merge(branch: &str) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
@@ -427,11 +486,64 @@ pub async fn remote_ls(directory: Option<PathBuf>, opts: &PaginateOpts) -> Resul
     Ok(())
 }
 
-pub fn df<P: AsRef<Path>>(input: P, opts: DFOpts) -> Result<(), OxenError> {
-    command::df(input, opts)?;
+pub fn df<P: AsRef<Path>>(
+    input: P,
+    revision: Option<&str>,
+    mut opts: DFOpts,
+) -> Result<(), OxenError> {
+    let current_dir = env::current_dir().unwrap();
+    let repo_dir = match util::fs::get_repo_root(&current_dir) {
+        Some(repo_dir) => repo_dir,
+        None if revision.is_some() || opts.join_revision.is_some() => {
+            return Err(OxenError::local_repo_not_found());
+        }
+        None => return command::df(input, opts),
+    };
+    let repo = LocalRepository::from_dir(&repo_dir)?;
+
+    // Paths are relative to where the command was run, which can be below the repo root
+    let input = current_dir.join(input);
+    opts.output = opts.output.map(|output| current_dir.join(output));
+    opts.join = opts.join.map(|join| current_dir.join(join));
+    if opts.sql_tables.is_some() {
+        let tables = opts
+            .sql_table_paths()?
+            .into_iter()
+            .map(|(name, path)| format!("{name}={}", current_dir.join(path).to_string_lossy()))
+            .collect();
+        opts.sql_tables = Some(tables);
+    }
+
+    match revision {
+        Some(revision) => command::df::df_revision(&repo, input, revision, opts),
+        None => {
+            command::df::resolve_join_revision(&repo, &mut opts)?;
+            command::df::df_in_repo(&repo, input, opts)
+        }
+    }
+}
+
+/// Inside a repository, read csvs with the dialect saved when they were added
+fn resolve_csv_dialect(input: impl AsRef<Path>, opts: &mut DFOpts) -> Result<(), OxenError> {
+    let current_dir = env::current_dir().unwrap();
+    if let Some(repo_dir) = util::fs::get_repo_root(&current_dir) {
+        let repo = LocalRepository::from_dir(&repo_dir)?;
+        command::df::resolve_csv_dialect(&repo, current_dir.join(input), None, opts)?;
+    }
     Ok(())
 }
 
+/// The repository above where the command was run, and `input` relative to where it was run
+fn repo_and_input_path(input: impl AsRef<Path>) -> Result<(LocalRepository, PathBuf), OxenError> {
+    let current_dir = env::current_dir().unwrap();
+    let repo_dir = match util::fs::get_repo_root(&current_dir) {
+        Some(repo_dir) => repo_dir,
+        None => return Err(OxenError::local_repo_not_found()),
+    };
+    let repo = LocalRepository::from_dir(&repo_dir)?;
+    Ok((repo, current_dir.join(input)))
+}
+
 pub async fn remote_df<P: AsRef<Path>>(input: P, opts: DFOpts) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repo = LocalRepository::from_dir(&repo_dir)?;
@@ -443,12 +555,87 @@ pub async fn remote_df<P: AsRef<Path>>(input: P, opts: DFOpts) -> Result<(), Oxe
     Ok(())
 }
 
-pub fn df_schema<P: AsRef<Path>>(input: P, flatten: bool, opts: DFOpts) -> Result<(), OxenError> {
-    let result = command::df::schema(input, flatten, opts)?;
+pub fn split(input: impl AsRef<Path>, opts: &SplitOpts) -> Result<(), OxenError> {
+    if !opts.add && opts.commit_message.is_none() {
+        command::split(input, opts)?;
+        return Ok(());
+    }
+
+    let current_dir = env::current_dir().unwrap();
+    let repo_dir = util::fs::get_repo_root(&current_dir).expect(error::NO_REPO_FOUND);
+    let repo = LocalRepository::from_dir(&repo_dir)?;
+    // Paths are relative to where the command was run, which can be below the repo root
+    let mut opts = opts.to_owned();
+    opts.output = opts.output.map(|output| current_dir.join(output));
+    if let Some(commit) = command::split::split_in_repo(&repo, current_dir.join(input), &opts)? {
+        println!("Committed splits {}", commit.id);
+    }
+    Ok(())
+}
+
+pub fn index_create(schema_ref: &str, field: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repo = LocalRepository::from_dir(&repo_dir)?;
+    let (schema, field) = command::index::create(&repo, schema_ref, field)?;
+    println!("Indexed {} on schema {}", field.name, schema.hash);
+    Ok(())
+}
+
+pub fn index_list() -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repo = LocalRepository::from_dir(&repo_dir)?;
+    let indices = command::index::list(&repo)?;
+    if indices.is_empty() {
+        eprintln!("No indices found");
+    }
+    for (schema, field) in indices {
+        let name = schema.name.unwrap_or_default();
+        println!("{}\t{}\t{}", name, schema.hash, field.name);
+    }
+    Ok(())
+}
+
+pub fn index_drop(schema_ref: &str, field: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repo = LocalRepository::from_dir(&repo_dir)?;
+    command::index::drop(&repo, schema_ref, field)?;
+    println!("Dropped index on {field}");
+    Ok(())
+}
+
+pub fn df_schema<P: AsRef<Path>>(
+    input: P,
+    revision: Option<&str>,
+    flatten: bool,
+    mut opts: DFOpts,
+) -> Result<(), OxenError> {
+    let result = if let Some(revision) = revision {
+        let (repo, input) = repo_and_input_path(input)?;
+        command::df::schema_revision(&repo, input, revision, flatten, opts)?
+    } else {
+        resolve_csv_dialect(&input, &mut opts)?;
+        command::df::schema(input, flatten, opts)?
+    };
     println!("{result}");
     Ok(())
 }
 
+pub fn df_stats<P: AsRef<Path>>(
+    input: P,
+    revision: Option<&str>,
+    mut opts: DFOpts,
+) -> Result<(), OxenError> {
+    let stats = if let Some(revision) = revision {
+        let (repo, input) = repo_and_input_path(input)?;
+        command::df::stats_revision(&repo, input, revision, opts)?
+    } else {
+        resolve_csv_dialect(&input, &mut opts)?;
+        command::df::stats(input, opts)?
+    };
+    println!("{stats}");
+    Ok(())
+}
+
 pub fn schema_show(val: &str, staged: bool) -> Result<Option<schema::Schema>, OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repo = LocalRepository::from_dir(&repo_dir)?;
@@ -461,12 +648,13 @@ pub fn schema_show(val: &str, staged: bool) -> Result<Option<schema::Schema>, Ox
 
     if let Some(schema) = schema {
         if let Some(name) = &schema.name {
-            println!("{name}\n{schema}");
+            println!("{name}\n{}", schema.verbose_str());
             Ok(Some(schema))
         } else {
             println!(
                 "Schema has no name, to name run:\n\n  oxen schemas name {} \"my_schema\"\n\n{}\n",
-                schema.hash, schema
+                schema.hash,
+                schema.verbose_str()
             );
             Ok(None)
         }
@@ -475,6 +663,24 @@ pub fn schema_show(val: &str, staged: bool) -> Result<Option<schema::Schema>, Ox
     }
 }
 
+pub fn schema_show_revision(val: &str, revision: &str) -> Result<schema::Schema, OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repo = LocalRepository::from_dir(&repo_dir)?;
+    let commit = api::local::commits::get_by_id_or_branch(&repo, revision)?
+        .ok_or_else(|| OxenError::committish_not_found(revision.into()))?;
+
+    match command::schemas::get(&repo, Some(&commit.id), val)? {
+        Some(schema) => {
+            if let Some(name) = &schema.name {
+                println!("{name}");
+            }
+            println!("{}", schema.verbose_str());
+            Ok(schema)
+        }
+        None => Err(OxenError::schema_does_not_exist(val)),
+    }
+}
+
 pub fn schema_name(schema_ref: &str, val: &str) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
@@ -487,6 +693,86 @@ pub fn schema_name(schema_ref: &str, val: &str) -> Result<(), OxenError> {
     Ok(())
 }
 
+pub fn schema_field(
+    schema_ref: &str,
+    field: &str,
+    metadata: &FieldMetadata,
+    clear: &[String],
+) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    let schema =
+        command::schemas::set_field_metadata(&repository, schema_ref, field, metadata, clear)?;
+    println!("{}", schema.verbose_str());
+    Ok(())
+}
+
+pub fn schema_pin(
+    path: &str,
+    schema_ref: &str,
+    not_null: &[String],
+    allow_extra: bool,
+) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    let contract = command::schemas::pin(&repository, path, schema_ref, not_null, allow_extra)?;
+    println!(
+        "Pinned schema {schema_ref} with {} fields to {}, commit {} to enforce it",
+        contract.fields.len(),
+        contract.path,
+        constants::SCHEMA_CONTRACTS_FILE
+    );
+    Ok(())
+}
+
+pub fn schema_unpin(path: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    if command::schemas::unpin(&repository, path)? {
+        println!(
+            "Unpinned schema from {path}, commit {} to stop enforcing it",
+            constants::SCHEMA_CONTRACTS_FILE
+        );
+    } else {
+        println!("No schema is pinned to {path}");
+    }
+    Ok(())
+}
+
+pub fn schema_diff(base: &str, head: &str, schema_ref: Option<&str>) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    let diffs = command::schemas::diff(&repository, base, head, schema_ref)?;
+    if diffs.is_empty() {
+        println!("No schema changes between {base} and {head}");
+    }
+    for file_diff in diffs {
+        println!("{}", file_diff.path.display());
+        println!("{}\n", file_diff.diff);
+    }
+    Ok(())
+}
+
+pub fn schema_log(path: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+
+    let path = repo_dir.join(path);
+    for entry in command::schemas::log(&repository, &path)? {
+        println!("commit {} {}", entry.commit.id, entry.commit.message);
+        match &entry.schema.name {
+            Some(name) => println!("schema {} ({name})", entry.schema.hash),
+            None => println!("schema {}", entry.schema.hash),
+        }
+        println!("{}\n", entry.diff);
+    }
+    Ok(())
+}
+
 pub fn schema_list(staged: bool) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
@@ -506,6 +792,14 @@ pub fn schema_list(staged: bool) -> Result<(), OxenError> {
     Ok(())
 }
 
+pub fn schema_list_revision(revision: &str) -> Result<(), OxenError> {
+    let repo_dir = env::current_dir().unwrap();
+    let repository = LocalRepository::from_dir(&repo_dir)?;
+    let commit = api::local::commits::get_by_id_or_branch(&repository, revision)?
+        .ok_or_else(|| OxenError::committish_not_found(revision.into()))?;
+    schema_list_commit_id(&commit.id)
+}
+
 pub fn schema_list_commit_id(commit_id: &str) -> Result<(), OxenError> {
     let repo_dir = env::current_dir().unwrap();
     let repository = LocalRepository::from_dir(&repo_dir)?;
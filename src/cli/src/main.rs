@@ -26,6 +26,8 @@ async fn main() {
         .subcommand(cmd_setup::df())
         .subcommand(cmd_setup::diff())
         .subcommand(cmd_setup::download())
+        .subcommand(cmd_setup::du())
+        .subcommand(cmd_setup::index())
         .subcommand(cmd_setup::init())
         .subcommand(cmd_setup::inspect_kv_db())
         .subcommand(cmd_setup::log())
@@ -37,6 +39,7 @@ async fn main() {
         .subcommand(cmd_setup::restore())
         .subcommand(cmd_setup::rm())
         .subcommand(cmd_setup::schemas())
+        .subcommand(cmd_setup::split())
         .subcommand(cmd_setup::status());
 
     let matches = command.get_matches();
@@ -56,6 +59,8 @@ async fn main() {
         }
         Some((cmd_setup::DF, sub_matches)) => parse_and_run::df(sub_matches),
         Some((cmd_setup::DIFF, sub_matches)) => parse_and_run::diff(sub_matches).await,
+        Some((cmd_setup::DU, sub_matches)) => parse_and_run::du(sub_matches),
+        Some((cmd_setup::INDEX, sub_matches)) => parse_and_run::index(sub_matches),
         Some((cmd_setup::INIT, sub_matches)) => parse_and_run::init(sub_matches).await,
         Some((cmd_setup::KVDB_INSPECT, sub_matches)) => parse_and_run::kvdb_inspect(sub_matches),
         Some((cmd_setup::LOG, sub_matches)) => parse_and_run::log(sub_matches).await,
@@ -67,6 +72,7 @@ async fn main() {
         Some((cmd_setup::RESTORE, sub_matches)) => parse_and_run::restore(sub_matches).await,
         Some((cmd_setup::RM, sub_matches)) => parse_and_run::rm(sub_matches).await,
         Some((cmd_setup::SCHEMAS, sub_matches)) => parse_and_run::schemas(sub_matches),
+        Some((cmd_setup::SPLIT, sub_matches)) => parse_and_run::split(sub_matches),
         Some((cmd_setup::STATUS, sub_matches)) => parse_and_run::status(sub_matches).await,
         // TODO: Get these in the help command instead of just falling back
         Some((ext, _sub_matches)) => {
@@ -7,10 +7,11 @@
 use crate::cmd_setup::{ADD, COMMIT, DF, DIFF, DOWNLOAD, LOG, LS, RESTORE, RM, STATUS};
 use crate::dispatch;
 use clap::ArgMatches;
+use liboxen::model::schema::FieldMetadata;
 use liboxen::model::staged_data::StagedDataOpts;
 use liboxen::model::ContentType;
 use liboxen::model::LocalRepository;
-use liboxen::opts::{AddOpts, CloneOpts, LogOpts, PaginateOpts, RmOpts};
+use liboxen::opts::{AddOpts, CloneOpts, LogOpts, PaginateOpts, RmOpts, SplitOpts};
 use liboxen::util;
 use liboxen::{command, opts::RestoreOpts};
 use std::path::{Path, PathBuf};
@@ -305,6 +306,14 @@ fn parse_df_sub_matches(sub_matches: &ArgMatches) -> liboxen::opts::DFOpts {
             None
         };
 
+    let sql_tables: Option<Vec<String>> = sub_matches
+        .get_many::<String>("table")
+        .map(|tables| tables.map(String::from).collect());
+
+    let with_columns: Option<Vec<String>> = sub_matches
+        .get_many::<String>("with-column")
+        .map(|exprs| exprs.map(String::from).collect());
+
     // CSV is easier from the CLI, but JSON is easier from API, so default to CSV here.
     let mut content_type = "csv";
     let maybe_content_type = sub_matches.get_one::<String>("content-type");
@@ -317,6 +326,7 @@ fn parse_df_sub_matches(sub_matches: &ArgMatches) -> liboxen::opts::DFOpts {
             .get_one::<String>("output")
             .map(std::path::PathBuf::from),
         delimiter: sub_matches.get_one::<String>("delimiter").map(String::from),
+        dialect: None,
         slice: sub_matches.get_one::<String>("slice").map(String::from),
         page_size: sub_matches
             .get_one::<String>("page-size")
@@ -338,11 +348,26 @@ fn parse_df_sub_matches(sub_matches: &ArgMatches) -> liboxen::opts::DFOpts {
         vstack,
         add_col: sub_matches.get_one::<String>("add-col").map(String::from),
         add_row: sub_matches.get_one::<String>("add-row").map(String::from),
+        cast: sub_matches.get_one::<String>("cast").map(String::from),
+        with_columns,
+        pivot: sub_matches.get_one::<String>("pivot").map(String::from),
+        melt: sub_matches.get_one::<String>("melt").map(String::from),
+        explode: sub_matches.get_one::<String>("explode").map(String::from),
         delete_row: sub_matches
             .get_one::<String>("delete-row")
             .map(String::from),
         sort_by: sub_matches.get_one::<String>("sort").map(String::from),
         unique: sub_matches.get_one::<String>("unique").map(String::from),
+        sql: sub_matches.get_one::<String>("sql").map(String::from),
+        sql_tables,
+        join: sub_matches
+            .get_one::<String>("join")
+            .map(std::path::PathBuf::from),
+        join_on: sub_matches.get_one::<String>("on").map(String::from),
+        join_how: sub_matches.get_one::<String>("how").map(String::from),
+        join_revision: sub_matches
+            .get_one::<String>("join-revision")
+            .map(String::from),
         content_type: ContentType::from_str(content_type).unwrap(),
         should_randomize: sub_matches.get_flag("randomize"),
         should_reverse: sub_matches.get_flag("reverse"),
@@ -364,15 +389,25 @@ async fn remote_df(sub_matches: &ArgMatches) {
 pub fn df(sub_matches: &ArgMatches) {
     let opts = parse_df_sub_matches(sub_matches);
     let path = sub_matches.get_one::<String>("DF_SPEC").expect("required");
+    let revision = sub_matches
+        .get_one::<String>("revision")
+        .map(String::as_str);
     if sub_matches.get_flag("schema") || sub_matches.get_flag("schema_flat") {
-        match dispatch::df_schema(path, sub_matches.get_flag("schema_flat"), opts) {
+        match dispatch::df_schema(path, revision, sub_matches.get_flag("schema_flat"), opts) {
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("{err}")
+            }
+        }
+    } else if sub_matches.get_flag("stats") {
+        match dispatch::df_stats(path, revision, opts) {
             Ok(_) => {}
             Err(err) => {
                 eprintln!("{err}")
             }
         }
     } else {
-        match dispatch::df(path, opts) {
+        match dispatch::df(path, revision, opts) {
             Ok(_) => {}
             Err(err) => {
                 eprintln!("{err}")
@@ -381,21 +416,97 @@ pub fn df(sub_matches: &ArgMatches) {
     }
 }
 
+pub fn split(sub_matches: &ArgMatches) {
+    let path = sub_matches.get_one::<String>("PATH").expect("required");
+    let ratios = sub_matches.get_one::<String>("ratios").expect("required");
+    let ratios = match SplitOpts::parse_ratios(ratios) {
+        Ok(ratios) => ratios,
+        Err(err) => {
+            eprintln!("{err}");
+            return;
+        }
+    };
+
+    let split_list = |name: &str| {
+        sub_matches
+            .get_one::<String>(name)
+            .map(|list| list.split(',').map(|s| s.trim().to_string()).collect())
+    };
+    let opts = SplitOpts {
+        ratios,
+        names: split_list("names"),
+        stratify: sub_matches.get_one::<String>("stratify").map(String::from),
+        key: split_list("key"),
+        seed: sub_matches
+            .get_one::<String>("seed")
+            .map(|x| x.parse::<u64>().expect("seed must be valid int"))
+            .unwrap_or(0),
+        output: sub_matches.get_one::<String>("output").map(PathBuf::from),
+        add: sub_matches.get_flag("add"),
+        commit_message: sub_matches.get_one::<String>("commit").map(String::from),
+    };
+
+    match dispatch::split(path, &opts) {
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("{err}")
+        }
+    }
+}
+
+pub fn index(sub_matches: &ArgMatches) {
+    let result = match sub_matches.subcommand() {
+        Some(("create", sub_matches)) => {
+            let schema = sub_matches.get_one::<String>("SCHEMA").expect("required");
+            let field = sub_matches.get_one::<String>("FIELD").expect("required");
+            dispatch::index_create(schema, field)
+        }
+        Some(("list", _)) => dispatch::index_list(),
+        Some(("drop", sub_matches)) => {
+            let schema = sub_matches.get_one::<String>("SCHEMA").expect("required");
+            let field = sub_matches.get_one::<String>("FIELD").expect("required");
+            dispatch::index_drop(schema, field)
+        }
+        Some((cmd, _)) => {
+            eprintln!("Unknown subcommand {cmd}");
+            return;
+        }
+        None => return,
+    };
+
+    match result {
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("{err}")
+        }
+    }
+}
+
 pub fn schemas(sub_matches: &ArgMatches) {
     if let Some(subcommand) = sub_matches.subcommand() {
         match subcommand {
-            ("list", sub_matches) => match dispatch::schema_list(sub_matches.get_flag("staged")) {
-                Ok(_) => {}
-                Err(err) => {
-                    eprintln!("{err}")
+            ("list", sub_matches) => {
+                let result = match sub_matches.get_one::<String>("revision") {
+                    Some(revision) => dispatch::schema_list_revision(revision),
+                    None => dispatch::schema_list(sub_matches.get_flag("staged")),
+                };
+                match result {
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{err}")
+                    }
                 }
-            },
+            }
             ("show", sub_matches) => {
                 let val = sub_matches
                     .get_one::<String>("NAME_OR_HASH")
                     .expect("required");
 
-                match dispatch::schema_show(val, sub_matches.get_flag("staged")) {
+                let result = match sub_matches.get_one::<String>("revision") {
+                    Some(revision) => dispatch::schema_show_revision(val, revision).map(|_| ()),
+                    None => dispatch::schema_show(val, sub_matches.get_flag("staged")).map(|_| ()),
+                };
+                match result {
                     Ok(_) => {}
                     Err(err) => {
                         eprintln!("{err}")
@@ -412,6 +523,85 @@ pub fn schemas(sub_matches: &ArgMatches) {
                     }
                 }
             }
+            ("field", sub_matches) => {
+                let schema_ref = sub_matches
+                    .get_one::<String>("NAME_OR_HASH")
+                    .expect("required");
+                let field = sub_matches.get_one::<String>("FIELD").expect("required");
+                let metadata = FieldMetadata {
+                    description: sub_matches.get_one::<String>("description").cloned(),
+                    unit: sub_matches.get_one::<String>("unit").cloned(),
+                    allowed_values: sub_matches
+                        .get_many::<String>("values")
+                        .map(|vals| vals.cloned().collect()),
+                    min: sub_matches.get_one::<f64>("min").copied(),
+                    max: sub_matches.get_one::<f64>("max").copied(),
+                    pii: if sub_matches.get_flag("pii") {
+                        Some(true)
+                    } else if sub_matches.get_flag("no-pii") {
+                        Some(false)
+                    } else {
+                        None
+                    },
+                };
+                let clear: Vec<String> = match sub_matches.get_many::<String>("clear") {
+                    Some(vals) => vals.cloned().collect(),
+                    None => vec![],
+                };
+                match dispatch::schema_field(schema_ref, field, &metadata, &clear) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{err}")
+                    }
+                }
+            }
+            ("pin", sub_matches) => {
+                let path = sub_matches.get_one::<String>("PATH").expect("required");
+                let schema_ref = sub_matches
+                    .get_one::<String>("NAME_OR_HASH")
+                    .expect("required");
+                let not_null: Vec<String> = match sub_matches.get_many::<String>("not-null") {
+                    Some(vals) => vals.cloned().collect(),
+                    None => vec![],
+                };
+                let allow_extra = sub_matches.get_flag("allow-extra");
+                match dispatch::schema_pin(path, schema_ref, &not_null, allow_extra) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{err}")
+                    }
+                }
+            }
+            ("unpin", sub_matches) => {
+                let path = sub_matches.get_one::<String>("PATH").expect("required");
+                match dispatch::schema_unpin(path) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{err}")
+                    }
+                }
+            }
+            ("diff", sub_matches) => {
+                let base = sub_matches.get_one::<String>("REV1").expect("required");
+                let head = sub_matches.get_one::<String>("REV2").expect("required");
+                let schema_ref = sub_matches.get_one::<String>("NAME_OR_HASH");
+                match dispatch::schema_diff(base, head, schema_ref.map(|s| s.as_str())) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{err}")
+                    }
+                }
+            }
+            ("log", sub_matches) => {
+                let path = sub_matches.get_one::<String>("PATH").expect("required");
+                match dispatch::schema_log(path) {
+                    Ok(_) => {}
+                    Err(err) => {
+                        eprintln!("{err}")
+                    }
+                }
+            }
+            (DF, sub_matches) => df(sub_matches),
             (cmd, _) => {
                 eprintln!("Unknown subcommand {cmd}")
             }
@@ -660,6 +850,17 @@ pub async fn pull(sub_matches: &ArgMatches) {
     }
 }
 
+pub fn du(sub_matches: &ArgMatches) {
+    let path = sub_matches.get_one::<String>("PATH").map(String::as_str);
+    let history = sub_matches.get_flag("history");
+    match dispatch::du(path, history) {
+        Ok(_) => {}
+        Err(err) => {
+            eprintln!("{err}")
+        }
+    }
+}
+
 pub async fn remote_diff(sub_matches: &ArgMatches) {
     let is_remote = true;
     p_diff(sub_matches, is_remote).await
@@ -675,6 +876,42 @@ async fn p_diff(sub_matches: &ArgMatches, is_remote: bool) {
     let file_or_commit_id = sub_matches
         .get_one::<String>("FILE_OR_COMMITTISH")
         .expect("required");
+
+    if sub_matches.get_flag("stat") {
+        if is_remote {
+            eprintln!("`oxen remote diff --stat` is not supported yet");
+            return;
+        }
+
+        let is_json = sub_matches.get_flag("json");
+        match dispatch::diff_summary(file_or_commit_id, is_json) {
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("{err}")
+            }
+        }
+        return;
+    }
+
+    if let Some(mask) = sub_matches.get_one::<String>("mask") {
+        if is_remote {
+            eprintln!("`oxen remote diff --mask` is not supported yet");
+            return;
+        }
+
+        let (resource, path) = match sub_matches.get_one::<String>("PATH") {
+            Some(path) => (Some(file_or_commit_id.as_str()), path),
+            None => (None, file_or_commit_id),
+        };
+        match dispatch::diff_image(resource, path, mask) {
+            Ok(_) => {}
+            Err(err) => {
+                eprintln!("{err}")
+            }
+        }
+        return;
+    }
+
     let path = sub_matches.get_one::<String>("PATH");
     if let Some(path) = path {
         match dispatch::diff(Some(file_or_commit_id), path, is_remote).await {
@@ -757,13 +994,21 @@ pub async fn compute_commit_cache(sub_matches: &ArgMatches) {
             .get_one::<String>("COMMITTISH")
             .map(String::from);
 
+        let status_only = sub_matches.get_flag("status");
         match LocalRepository::new(path) {
-            Ok(repo) => match command::commit_cache::compute_cache(&repo, committish).await {
-                Ok(_) => {}
-                Err(err) => {
-                    println!("Err: {err}")
+            Ok(repo) => {
+                let result = if status_only {
+                    command::commit_cache::print_cache_status(&repo, committish).await
+                } else {
+                    command::commit_cache::compute_cache(&repo, committish).await
+                };
+                match result {
+                    Ok(_) => {}
+                    Err(err) => {
+                        println!("Err: {err}")
+                    }
                 }
-            },
+            }
             Err(err) => {
                 println!("Err: {err}")
             }
@@ -4,10 +4,14 @@
 //!
 
 use crate::api;
-use crate::core::index::{CommitEntryReader, CommitReader, CommitWriter, RefReader, Stager};
+use crate::constants::{HASH_FILE, HISTORY_DIR};
+use crate::core::index::{
+    CommitEntryReader, CommitReader, CommitWriter, RefReader, RefWriter, Stager,
+};
 use crate::error::OxenError;
 use crate::model::{Commit, CommitEntry, LocalRepository, StagedData};
 use crate::opts::LogOpts;
+use crate::util;
 
 use std::path::Path;
 
@@ -160,6 +164,65 @@ pub fn create_commit_object(
     Ok(())
 }
 
+/// Undo a pushed commit that the remote rejected. Branches that point at the commit are moved
+/// back to its first parent, or deleted if it has none, and its history and the version files
+/// it added are removed.
+pub fn remove_rejected(repo: &LocalRepository, commit: &Commit) -> Result<(), OxenError> {
+    log::debug!(
+        "Removing rejected commit {} from {:?}",
+        commit.id,
+        repo.path
+    );
+    let ref_writer = RefWriter::new(repo)?;
+    for branch in ref_writer.list_branches()? {
+        if branch.commit_id != commit.id {
+            continue;
+        }
+        match commit.parent_ids.first() {
+            Some(parent_id) => ref_writer.set_branch_commit_id(&branch.name, parent_id)?,
+            None => ref_writer.delete_branch(&branch.name)?,
+        }
+    }
+
+    // Version files are named by the commit that added them, so other commits never share them
+    for entry in CommitEntryReader::new(repo, commit)?.list_entries()? {
+        if entry.commit_id != commit.id {
+            continue;
+        }
+        let version_path = util::fs::version_path(repo, &entry);
+        if version_path.exists() {
+            util::fs::remove_file(&version_path)?;
+        }
+        remove_version_dir_if_unused(&version_path)?;
+    }
+
+    let commit_writer = CommitWriter::new(repo)?;
+    commit_writer.commits_db.delete(&commit.id)?;
+
+    let history_dir = util::fs::oxen_hidden_dir(&repo.path)
+        .join(HISTORY_DIR)
+        .join(&commit.id);
+    if history_dir.exists() {
+        util::fs::remove_dir_all(history_dir)?;
+    }
+    Ok(())
+}
+
+// The version dir of a hash is unused once only the HASH file written on upload is left
+fn remove_version_dir_if_unused(version_path: &Path) -> Result<(), OxenError> {
+    let version_dir = match version_path.parent() {
+        Some(version_dir) if version_dir.exists() => version_dir,
+        _ => return Ok(()),
+    };
+    let is_unused = std::fs::read_dir(version_dir)?
+        .filter_map(|entry| entry.ok())
+        .all(|entry| entry.file_name() == HASH_FILE);
+    if is_unused {
+        util::fs::remove_dir_all(version_dir)?;
+    }
+    Ok(())
+}
+
 /// List commits on the current branch
 pub fn list(repo: &LocalRepository) -> Result<Vec<Commit>, OxenError> {
     let committer = CommitReader::new(repo)?;
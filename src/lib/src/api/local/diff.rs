@@ -1,17 +1,22 @@
+use crate::api;
 use crate::core::df::tabular;
 use crate::core::index::CommitDirEntryReader;
 use crate::error::OxenError;
 use crate::model::entry::diff_entry::DiffEntryStatus;
-use crate::model::{Commit, CommitEntry, DataFrameDiff, DiffEntry, LocalRepository, Schema};
+use crate::model::{
+    Commit, CommitEntry, DataFrameDiff, DiffEntry, DiffSummary, DiffSummaryGroup, ImageDiff,
+    ImageInfo, JsonDiff, LocalRepository, Schema,
+};
 use crate::opts::DFOpts;
 use crate::{constants, util};
 
 use colored::Colorize;
 use difference::{Changeset, Difference};
+use image::{DynamicImage, GenericImageView, GrayImage, Luma};
 use polars::export::ahash::HashMap;
 use polars::prelude::DataFrame;
 use polars::prelude::IntoLazy;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
 use std::path::{Path, PathBuf};
 
 use crate::core::index::CommitEntryReader;
@@ -66,6 +71,12 @@ pub fn diff_files(
     if util::fs::is_tabular(original) && util::fs::is_tabular(compare) {
         let tabular_diff = diff_tabular(original, compare)?;
         return Ok(tabular_diff.to_string());
+    } else if util::fs::is_image(original) && util::fs::is_image(compare) {
+        let image_diff = diff_image(original, compare, None::<&Path>)?;
+        return Ok(image_diff.to_string());
+    } else if util::fs::is_json(original) && util::fs::is_json(compare) {
+        let json_diff = diff_json(original, compare)?;
+        return Ok(json_diff.to_string());
     } else if util::fs::is_utf8(original) && util::fs::is_utf8(compare) {
         return diff_utf8(original, compare);
     }
@@ -108,9 +119,160 @@ pub fn diff_utf8(
     Ok(outputs.join(""))
 }
 
+/// Structural diff of two json files, reports the paths that were added, removed or changed
+/// so that reordering keys or reformatting the file does not show up as a change.
+pub fn diff_json(
+    original_path: impl AsRef<Path>,
+    compare_path: impl AsRef<Path>,
+) -> Result<JsonDiff, OxenError> {
+    let original_path = original_path.as_ref();
+    let compare_path = compare_path.as_ref();
+    // Make sure files exist
+    if !original_path.exists() {
+        return Err(OxenError::entry_does_not_exist(original_path));
+    }
+
+    if !compare_path.exists() {
+        return Err(OxenError::entry_does_not_exist(compare_path));
+    }
+
+    let original_data = util::fs::read_from_path(original_path)?;
+    let compare_data = util::fs::read_from_path(compare_path)?;
+    let original: serde_json::Value = serde_json::from_str(&original_data)?;
+    let compare: serde_json::Value = serde_json::from_str(&compare_data)?;
+    Ok(JsonDiff::from_values(&original, &compare))
+}
+
+/// Compare two versions of an image. If `mask_path` is provided and any pixels differ, a black
+/// and white image is written there with the pixels that differ in white.
+pub fn diff_image(
+    original_path: impl AsRef<Path>,
+    compare_path: impl AsRef<Path>,
+    mask_path: Option<impl AsRef<Path>>,
+) -> Result<ImageDiff, OxenError> {
+    let original_path = original_path.as_ref();
+    let compare_path = compare_path.as_ref();
+    // Make sure files exist
+    if !original_path.exists() {
+        return Err(OxenError::entry_does_not_exist(original_path));
+    }
+
+    if !compare_path.exists() {
+        return Err(OxenError::entry_does_not_exist(compare_path));
+    }
+
+    let (original_info, original) = read_image(original_path)?;
+    let (compare_info, compare) = read_image(compare_path)?;
+
+    // Pixel comparisons need the same dimensions, so scale head to match base if re-cropped
+    let resized_for_comparison = original.dimensions() != compare.dimensions();
+    let resized = if resized_for_comparison {
+        compare.resize_exact(
+            original.width(),
+            original.height(),
+            image::imageops::FilterType::Triangle,
+        )
+    } else {
+        compare.clone()
+    };
+
+    let original_rgba = original.to_rgba8();
+    let compare_rgba = resized.to_rgba8();
+    let mut mask = GrayImage::new(original.width(), original.height());
+    let mut num_changed: u64 = 0;
+    let mut total_abs_diff: u64 = 0;
+    for (x, y, pixel) in original_rgba.enumerate_pixels() {
+        let other = compare_rgba.get_pixel(x, y);
+        let abs_diff: u64 = pixel
+            .0
+            .iter()
+            .zip(other.0.iter())
+            .map(|(a, b)| (*a as i16 - *b as i16).unsigned_abs() as u64)
+            .sum();
+        if abs_diff > 0 {
+            num_changed += 1;
+            mask.put_pixel(x, y, Luma([255]));
+        }
+        total_abs_diff += abs_diff;
+    }
+
+    let num_pixels = (original.width() as u64 * original.height() as u64).max(1);
+    let pixel_diff_ratio = num_changed as f64 / num_pixels as f64;
+    // 4 channels per pixel, each with a max difference of 255
+    let mean_abs_diff = total_abs_diff as f64 / (num_pixels as f64 * 4.0 * 255.0);
+
+    let mask_path = match mask_path {
+        Some(mask_path) if num_changed > 0 => {
+            mask.save(mask_path.as_ref())?;
+            Some(mask_path.as_ref().to_path_buf())
+        }
+        _ => None,
+    };
+
+    let phash_distance = (perceptual_hash(&original) ^ perceptual_hash(&compare)).count_ones();
+
+    Ok(ImageDiff {
+        base: original_info,
+        head: compare_info,
+        resized_for_comparison,
+        pixel_diff_ratio,
+        mean_abs_diff,
+        phash_distance,
+        mask_path,
+    })
+}
+
+fn read_image(path: &Path) -> Result<(ImageInfo, DynamicImage), OxenError> {
+    let reader = image::io::Reader::open(path)?.with_guessed_format()?;
+    let format = match reader.format() {
+        Some(format) => format!("{format:?}").to_lowercase(),
+        None => String::from("unknown"),
+    };
+    let img = reader.decode()?;
+    let info = ImageInfo {
+        format,
+        color_mode: format!("{:?}", img.color()).to_lowercase(),
+        width: img.width(),
+        height: img.height(),
+    };
+    Ok((info, img))
+}
+
+/// 64 bit difference hash, each bit is whether a pixel is brighter than its right neighbor
+/// in a 9x8 grayscale thumbnail, so it is robust to scaling and re-encoding.
+fn perceptual_hash(img: &DynamicImage) -> u64 {
+    let thumbnail = img
+        .resize_exact(9, 8, image::imageops::FilterType::Triangle)
+        .to_luma8();
+    let mut hash: u64 = 0;
+    for y in 0..8 {
+        for x in 0..8 {
+            let left = thumbnail.get_pixel(x, y).0[0];
+            let right = thumbnail.get_pixel(x + 1, y).0[0];
+            hash = (hash << 1) | (left > right) as u64;
+        }
+    }
+    hash
+}
+
 pub fn diff_tabular(
     original_path: impl AsRef<Path>,
     compare_path: impl AsRef<Path>,
+) -> Result<DataFrameDiff, OxenError> {
+    diff_tabular_with_opts(
+        original_path,
+        compare_path,
+        &DFOpts::empty(),
+        &DFOpts::empty(),
+    )
+}
+
+/// Diff two tabular files, reading each with its own opts, ex) the csv dialect saved on its schema
+pub fn diff_tabular_with_opts(
+    original_path: impl AsRef<Path>,
+    compare_path: impl AsRef<Path>,
+    original_opts: &DFOpts,
+    compare_opts: &DFOpts,
 ) -> Result<DataFrameDiff, OxenError> {
     let original_path = original_path.as_ref();
     let compare_path = compare_path.as_ref();
@@ -124,8 +286,8 @@ pub fn diff_tabular(
     }
 
     // Read DFs and get schemas
-    let original_df = tabular::read_df(original_path, DFOpts::empty())?;
-    let compare_df = tabular::read_df(compare_path, DFOpts::empty())?;
+    let original_df = tabular::read_df(original_path, original_opts.to_owned())?;
+    let compare_df = tabular::read_df(compare_path, compare_opts.to_owned())?;
     let original_schema = Schema::from_polars(&original_df.schema());
     let compare_schema = Schema::from_polars(&compare_df.schema());
 
@@ -140,12 +302,7 @@ pub fn diff_tabular(
 
     // If schemas don't match, figure out which columns are different
     if original_schema.hash != compare_schema.hash {
-        compute_new_columns(
-            original_path,
-            compare_path,
-            &original_schema,
-            &compare_schema,
-        )
+        compute_new_columns(original_df, compare_df, &original_schema, &compare_schema)
     } else {
         log::debug!("Computing diff for {original_path:?} to {compare_path:?}");
         compute_new_rows(original_df, compare_df, &original_schema)
@@ -231,8 +388,8 @@ fn compute_new_rows(
 }
 
 fn compute_new_columns(
-    versioned_path: &Path,
-    current_path: &Path,
+    versioned_df: DataFrame,
+    current_df: DataFrame,
     versioned_schema: &Schema,
     current_schema: &Schema,
 ) -> Result<DataFrameDiff, OxenError> {
@@ -241,7 +398,7 @@ fn compute_new_columns(
 
     let added_cols = if !added_fields.is_empty() {
         let opts = DFOpts::from_columns(added_fields);
-        let df_added = tabular::read_df(current_path, opts)?;
+        let df_added = tabular::transform(current_df, opts)?;
         log::debug!("Got added col df: {}", df_added);
         if df_added.width() > 0 {
             Some(df_added)
@@ -254,7 +411,7 @@ fn compute_new_columns(
 
     let removed_cols = if !removed_fields.is_empty() {
         let opts = DFOpts::from_columns(removed_fields);
-        let df_removed = tabular::read_df(versioned_path, opts)?;
+        let df_removed = tabular::transform(versioned_df, opts)?;
         log::debug!("Got removed col df: {}", df_removed);
         if df_removed.width() > 0 {
             Some(df_removed)
@@ -309,6 +466,118 @@ pub fn list_diff_entries(
     Ok(diff_entries)
 }
 
+/// Summarize the changes between two commits, grouping the added, modified and removed
+/// files by their parent directory and data type
+pub fn summarize_diff(
+    repo: &LocalRepository,
+    base_commit: &Commit,
+    head_commit: &Commit,
+) -> Result<DiffSummary, OxenError> {
+    log::debug!(
+        "summarize_diff base_commit: '{}', head_commit: '{}'",
+        base_commit.id,
+        head_commit.id
+    );
+    let head_entries = read_entries_from_commit(repo, head_commit)?;
+    let base_entries = read_entries_from_commit(repo, base_commit)?;
+
+    // BTreeMap so that the groups come back sorted by directory then data type
+    let mut groups: BTreeMap<(String, String), DiffSummaryGroup> = BTreeMap::new();
+    for head_entry in head_entries.iter() {
+        match base_entries.get(head_entry) {
+            None => {
+                add_to_summary(
+                    repo,
+                    &mut groups,
+                    None,
+                    Some(head_entry),
+                    DiffEntryStatus::Added,
+                );
+            }
+            Some(base_entry) if base_entry.hash != head_entry.hash => {
+                add_to_summary(
+                    repo,
+                    &mut groups,
+                    Some(base_entry),
+                    Some(head_entry),
+                    DiffEntryStatus::Modified,
+                );
+            }
+            Some(_) => {}
+        }
+    }
+
+    for base_entry in base_entries.iter() {
+        if !head_entries.contains(base_entry) {
+            add_to_summary(
+                repo,
+                &mut groups,
+                Some(base_entry),
+                None,
+                DiffEntryStatus::Removed,
+            );
+        }
+    }
+
+    Ok(DiffSummary::from_groups(groups.into_values().collect()))
+}
+
+fn add_to_summary(
+    repo: &LocalRepository,
+    groups: &mut BTreeMap<(String, String), DiffSummaryGroup>,
+    base_entry: Option<&CommitEntry>,
+    head_entry: Option<&CommitEntry>,
+    status: DiffEntryStatus,
+) {
+    // One of the two is always set, prefer head so we describe the newest version
+    let current_entry = head_entry.or(base_entry).unwrap();
+    let version_path = util::fs::version_path(repo, current_entry);
+    let data_type = util::fs::file_datatype(&version_path);
+    let directory = match current_entry.path.parent() {
+        Some(parent) if parent != Path::new("") => parent.to_string_lossy().to_string(),
+        _ => String::from("."),
+    };
+
+    let base_bytes = base_entry.map(|e| e.num_bytes as i64).unwrap_or(0);
+    let head_bytes = head_entry.map(|e| e.num_bytes as i64).unwrap_or(0);
+
+    let rows_delta = if util::fs::is_tabular(&current_entry.path) {
+        let base_rows = base_entry.map(|e| count_rows(repo, e));
+        let head_rows = head_entry.map(|e| count_rows(repo, e));
+        match (base_rows, head_rows) {
+            (Some(Some(base)), Some(Some(head))) => Some(head - base),
+            (None, Some(Some(head))) => Some(head),
+            (Some(Some(base)), None) => Some(-base),
+            _ => None,
+        }
+    } else {
+        None
+    };
+
+    groups
+        .entry((directory.to_owned(), data_type.to_owned()))
+        .or_insert_with(|| DiffSummaryGroup::new(&directory, &data_type))
+        .add(&status, head_bytes - base_bytes, rows_delta);
+}
+
+/// Count the rows of a version with a lazy scan, so the file is never fully loaded into memory
+fn count_rows(repo: &LocalRepository, entry: &CommitEntry) -> Option<i64> {
+    let version_path = util::fs::version_path(repo, entry);
+    let counted = api::local::schemas::get_csv_dialect(repo, Some(&entry.commit_id), &entry.path)
+        .and_then(|dialect| {
+            let mut opts = DFOpts::empty();
+            opts.dialect = dialect;
+            tabular::count_rows(tabular::scan_df(&version_path, &opts)?)
+        });
+    match counted {
+        Ok(num_rows) => Some(num_rows as i64),
+        Err(err) => {
+            log::warn!("Could not count rows for {:?}: {}", entry.path, err);
+            None
+        }
+    }
+}
+
 // Find the entries that are in HEAD but not in BASE
 fn collect_added_entries(
     repo: &LocalRepository,
@@ -464,6 +733,115 @@ train/cat_2.jpg,cat,30.5,44.0,333,396
         })
     }
 
+    #[test]
+    fn test_summarize_diff_groups_by_dir_and_data_type() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let bbox_file = repo
+                .path
+                .join("annotations")
+                .join("train")
+                .join("bounding_box.csv");
+
+            // get og commit
+            let base_commit = api::local::commits::head_commit(&repo)?;
+
+            // Remove three rows and add a new text file
+            let bbox_file = test::modify_txt_file(
+                bbox_file,
+                r"file,label,min_x,min_y,width,height
+train/dog_1.jpg,dog,101.5,32.0,385,330
+train/dog_2.jpg,dog,7.0,29.5,246,247
+train/cat_2.jpg,cat,30.5,44.0,333,396
+",
+            )?;
+            let hello_file = repo.path.join("Hello.txt");
+            test::write_txt_file_to_path(&hello_file, "Hello")?;
+
+            command::add(&repo, bbox_file)?;
+            command::add(&repo, &hello_file)?;
+            let head_commit = command::commit(&repo, "Removing rows and adding hello")?;
+
+            let summary = api::local::diff::summarize_diff(&repo, &base_commit, &head_commit)?;
+            assert_eq!(summary.num_added, 1);
+            assert_eq!(summary.num_modified, 1);
+            assert_eq!(summary.num_removed, 0);
+            assert_eq!(summary.rows_delta, Some(-3));
+            assert_eq!(summary.groups.len(), 2);
+
+            // Groups are sorted by directory
+            assert_eq!(summary.groups[0].directory, ".");
+            assert_eq!(summary.groups[0].data_type, "text");
+            assert_eq!(summary.groups[0].num_added, 1);
+            assert_eq!(summary.groups[0].rows_delta, None);
+
+            assert_eq!(summary.groups[1].directory, "annotations/train");
+            assert_eq!(summary.groups[1].data_type, "tabular");
+            assert_eq!(summary.groups[1].num_modified, 1);
+            assert_eq!(summary.groups[1].rows_delta, Some(-3));
+            assert!(summary.groups[1].bytes_delta < 0);
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_diff_json_ignores_key_order() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let original = dir.join("original.json");
+            let compare = dir.join("compare.json");
+            test::write_txt_file_to_path(
+                &original,
+                r#"{"label": "dog", "bbox": [1, 2, 3, 4], "meta": {"width": 100, "source": "web"}}"#,
+            )?;
+            test::write_txt_file_to_path(
+                &compare,
+                r#"{
+                    "meta": {"width": 120, "height": 80},
+                    "bbox": [1, 2, 3, 4, 5],
+                    "label": "dog"
+                }"#,
+            )?;
+
+            let diff = api::local::diff::diff_json(&original, &compare)?;
+            assert_eq!(diff.added.len(), 2);
+            assert_eq!(diff.added[0].path, "$.bbox[4]");
+            assert_eq!(diff.added[1].path, "$.meta.height");
+            assert_eq!(diff.removed.len(), 1);
+            assert_eq!(diff.removed[0].path, "$.meta.source");
+            assert_eq!(diff.changed.len(), 1);
+            assert_eq!(diff.changed[0].path, "$.meta.width");
+            assert_eq!(diff.changed[0].old_value, Some(serde_json::json!(100)));
+            assert_eq!(diff.changed[0].new_value, Some(serde_json::json!(120)));
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_diff_image_writes_mask() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let original = test::test_jpeg_file_with_name("cat_1.jpg");
+            let compare = test::test_jpeg_file_with_name("dog_1.jpg");
+            let mask = dir.join("mask.png");
+
+            let same = api::local::diff::diff_image(&original, &original, Some(&mask))?;
+            assert!(same.is_identical());
+            assert_eq!(same.phash_distance, 0);
+            assert!(same.mask_path.is_none());
+            assert!(!mask.exists());
+
+            let diff = api::local::diff::diff_image(&original, &compare, Some(&mask))?;
+            assert!(!diff.is_identical());
+            assert_eq!(diff.base.format, "jpeg");
+            assert!(diff.pixel_diff_ratio > 0.0);
+            assert!(diff.phash_distance > 0);
+            assert_eq!(diff.mask_path, Some(mask.to_owned()));
+            assert!(mask.exists());
+
+            Ok(())
+        })
+    }
+
     #[tokio::test]
     async fn test_list_diff_entries_remove_one_tabular() -> Result<(), OxenError> {
         test::run_training_data_repo_test_fully_committed_async(|repo| async move {
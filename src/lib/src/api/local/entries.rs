@@ -6,6 +6,7 @@ use crate::util;
 use crate::view::entry::ResourceVersion;
 use rayon::prelude::*;
 
+use crate::core::cache::cachers::image_meta_data;
 use crate::core::index::{CommitDirEntryReader, CommitEntryReader, CommitReader};
 use crate::model::{Commit, CommitEntry, DirEntry, LocalRepository};
 use crate::view::PaginatedDirEntries;
@@ -88,6 +89,7 @@ pub fn dir_entry_from_dir(
             version: revision.to_string(),
             path: String::from(path.to_string_lossy()),
         }),
+        image_meta_data: None,
     });
 }
 
@@ -105,6 +107,11 @@ pub fn dir_entry_from_commit_entry(
         .file_name()
         .ok_or(OxenError::file_has_no_name(&entry.path))?;
     let version_path = util::fs::version_path(repo, entry);
+    let image_meta_data = if util::fs::is_image(&entry.path) {
+        image_meta_data::get_meta_data(repo, entry)?
+    } else {
+        None
+    };
     return Ok(DirEntry {
         filename: String::from(base_name.to_string_lossy()),
         is_dir: false,
@@ -115,6 +122,7 @@ pub fn dir_entry_from_commit_entry(
             version: revision.to_string(),
             path: String::from(entry.path.to_string_lossy()),
         }),
+        image_meta_data,
     });
 }
 
@@ -1,10 +1,14 @@
 use crate::api;
 use crate::constants;
-use crate::core::index::{CommitEntryReader, CommitWriter, RefWriter};
+use crate::core::cache::cachers::{column_stats, repo_stats};
+use crate::core::cache::{commit_cacher, Cacher};
+use crate::core::index::{CommitEntryReader, CommitReader, CommitWriter, RefWriter};
 use crate::error::OxenError;
 use crate::model::DataTypeStat;
 use crate::model::RepoStats;
-use crate::model::{CommitStats, LocalRepository, RepositoryNew};
+use crate::model::{
+    Commit, CommitStats, LocalRepository, RepoSizeLogEntry, RepoSizeStats, RepositoryNew,
+};
 use crate::util;
 
 use jwalk::WalkDir;
@@ -96,6 +100,45 @@ pub fn get_repo_stats(repo: &LocalRepository) -> RepoStats {
     }
 }
 
+/// The size and composition of the repository at a commit. If the cacher has not run on the commit
+/// it is run now, with the column stats the row counts come from, so the stats are only computed once.
+pub fn get_size_stats(repo: &LocalRepository, commit: &Commit) -> Result<RepoSizeStats, OxenError> {
+    if let Some(stats) = repo_stats::get_stats(repo, commit)? {
+        return Ok(stats);
+    }
+
+    let cachers: [&dyn Cacher; 2] = [
+        &column_stats::ColumnStatsCacher,
+        &repo_stats::RepoStatsCacher,
+    ];
+    if let Err(err) = commit_cacher::run(repo, commit, &cachers) {
+        // The cache of the commit is locked while a job processes it
+        log::warn!("Could not cache the size stats of {}: {err}", commit.id);
+    }
+    match repo_stats::get_stats(repo, commit)? {
+        Some(stats) => Ok(stats),
+        None => repo_stats::compute_stats(repo, commit),
+    }
+}
+
+/// The size and composition of the repository after every commit in the history of `commit`, oldest first.
+/// Only the commits that were never listed before are computed.
+pub fn list_size_stats(
+    repo: &LocalRepository,
+    commit: &Commit,
+) -> Result<Vec<RepoSizeLogEntry>, OxenError> {
+    let reader = CommitReader::new(repo)?;
+    let mut commits = reader.history_from_commit_id(&commit.id)?;
+    commits.reverse();
+    commits
+        .into_iter()
+        .map(|commit| {
+            let stats = get_size_stats(repo, &commit)?;
+            Ok(RepoSizeLogEntry { commit, stats })
+        })
+        .collect()
+}
+
 pub fn list_namespaces(sync_dir: &Path) -> Result<Vec<String>, OxenError> {
     log::debug!(
         "api::local::entries::list_namespaces repositories for sync dir: {:?}",
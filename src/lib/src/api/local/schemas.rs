@@ -1,8 +1,18 @@
 use crate::api;
 
-use crate::core::index::SchemaReader;
+use crate::constants::SCHEMA_CONTRACTS_FILE;
+use crate::core::df::tabular;
+use crate::core::index::{schema_indexer, CommitEntryReader, CommitReader, SchemaReader, Stager};
 use crate::error::OxenError;
-use crate::model::{LocalRepository, Schema};
+use crate::model::schema::{ContractViolation, SchemaContracts, SchemaFileDiff, SchemaLogEntry};
+use crate::model::{
+    Commit, CommitEntry, CsvDialect, LocalRepository, Schema, StagedData, StagedEntryStatus,
+};
+use crate::opts::DFOpts;
+use crate::util;
+
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
 
 pub fn list(repo: &LocalRepository, commit_id: Option<&str>) -> Result<Vec<Schema>, OxenError> {
     log::debug!("api::local::schemas::list for path {:?}", repo.path);
@@ -20,3 +30,380 @@ pub fn list(repo: &LocalRepository, commit_id: Option<&str>) -> Result<Vec<Schem
         schema_reader.list_schemas()
     }
 }
+
+/// The schema of a tabular file as it is staged, or as of the version committed at `commit` (HEAD if None)
+pub fn get_schema_for_path(
+    repo: &LocalRepository,
+    commit: Option<&Commit>,
+    path: impl AsRef<Path>,
+) -> Result<Option<Schema>, OxenError> {
+    let relative_path = util::fs::path_relative_to_dir(path.as_ref(), &repo.path)?;
+    let commit = match commit {
+        Some(commit) => commit.to_owned(),
+        None => {
+            let stager = Stager::new(repo)?;
+            if let Some(schema) = stager.get_staged_schema_for_path(&relative_path)? {
+                return Ok(Some(schema));
+            }
+            match api::local::commits::head_commit(repo) {
+                Ok(commit) => commit,
+                Err(_) => return Ok(None),
+            }
+        }
+    };
+
+    // Schemas are only written in the commit that last changed the file
+    match CommitEntryReader::new(repo, &commit)?.get_entry(&relative_path)? {
+        Some(entry) => {
+            SchemaReader::new(repo, &entry.commit_id)?.get_schema_for_file(&relative_path)
+        }
+        None => Ok(None),
+    }
+}
+
+/// The schema of every tabular file in the commit, by path
+pub fn list_schemas_by_path(
+    repo: &LocalRepository,
+    commit: &Commit,
+) -> Result<HashMap<PathBuf, Schema>, OxenError> {
+    let mut schemas: HashMap<PathBuf, Schema> = HashMap::new();
+    for (_hash, (schema, entries)) in schema_indexer::tabular_entries_by_schema(repo, commit)? {
+        for entry in entries {
+            schemas.insert(entry.path, schema.to_owned());
+        }
+    }
+    Ok(schemas)
+}
+
+/// How the schemas of the tabular files changed from `base` to `head`. Files that only exist in
+/// one of the commits are compared to an empty schema. If `schema_ref` is set, only files with
+/// that schema name or hash in either commit are compared.
+pub fn diff(
+    repo: &LocalRepository,
+    base: &Commit,
+    head: &Commit,
+    schema_ref: Option<&str>,
+) -> Result<Vec<SchemaFileDiff>, OxenError> {
+    let base_schemas = list_schemas_by_path(repo, base)?;
+    let head_schemas = list_schemas_by_path(repo, head)?;
+
+    let is_match = |schema: Option<&Schema>| match (schema, schema_ref) {
+        (Some(schema), Some(schema_ref)) => {
+            schema.hash == schema_ref || schema.name.as_deref() == Some(schema_ref)
+        }
+        (_, None) => true,
+        (None, Some(_)) => false,
+    };
+
+    let mut paths: Vec<&PathBuf> = base_schemas.keys().chain(head_schemas.keys()).collect();
+    paths.sort();
+    paths.dedup();
+
+    let empty = Schema::from_fields(vec![]);
+    let mut diffs: Vec<SchemaFileDiff> = vec![];
+    for path in paths {
+        let base_schema = base_schemas.get(path);
+        let head_schema = head_schemas.get(path);
+        if !is_match(base_schema) && !is_match(head_schema) {
+            continue;
+        }
+        if let (Some(base_schema), Some(head_schema)) = (base_schema, head_schema) {
+            if base_schema.hash == head_schema.hash && base_schema.dialect == head_schema.dialect {
+                continue;
+            }
+        }
+
+        let diff = head_schema
+            .unwrap_or(&empty)
+            .diff(base_schema.unwrap_or(&empty));
+        diffs.push(SchemaFileDiff {
+            path: path.to_owned(),
+            base: base_schema.cloned(),
+            head: head_schema.cloned(),
+            diff,
+        });
+    }
+    Ok(diffs)
+}
+
+/// The commits in the history of `commit` that changed the schema of the file at `path`, newest first.
+/// Only the commits that changed the file are read, and the walk stops where the file did not exist yet.
+pub fn log(
+    repo: &LocalRepository,
+    commit: &Commit,
+    path: impl AsRef<Path>,
+) -> Result<Vec<SchemaLogEntry>, OxenError> {
+    let relative_path = util::fs::path_relative_to_dir(path.as_ref(), &repo.path)?;
+    let history = CommitReader::new(repo)?.history_from_commit_id(&commit.id)?;
+
+    // Newest first, the schema of each version of the file and the commit that wrote it
+    let mut versions: Vec<(Commit, Option<Schema>)> = vec![];
+    let mut i = 0;
+    while i < history.len() {
+        let entry = match CommitEntryReader::new(repo, &history[i])?.get_entry(&relative_path)? {
+            Some(entry) => entry,
+            None => break,
+        };
+        // Every commit back to the one that last changed the file has the same version
+        let changed_at = match history[i..].iter().position(|c| c.id == entry.commit_id) {
+            Some(offset) => i + offset,
+            None => break,
+        };
+        let schema =
+            SchemaReader::new(repo, &entry.commit_id)?.get_schema_for_file(&relative_path)?;
+        versions.push((history[changed_at].to_owned(), schema));
+        i = changed_at + 1;
+    }
+
+    let empty = Schema::from_fields(vec![]);
+    let mut previous: Option<Schema> = None;
+    let mut entries: Vec<SchemaLogEntry> = vec![];
+    for (commit, schema) in versions.into_iter().rev() {
+        if let Some(schema) = &schema {
+            let diff = schema.diff(previous.as_ref().unwrap_or(&empty));
+            if !diff.is_empty() {
+                entries.push(SchemaLogEntry {
+                    commit,
+                    schema: schema.to_owned(),
+                    diff,
+                });
+            }
+        }
+        previous = schema;
+    }
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Detect the dialect of a csv or tsv in the working directory, with any override from the repo config applied.
+/// Returns None if the file is not delimited text.
+pub fn detect_csv_dialect(
+    repo: &LocalRepository,
+    path: impl AsRef<Path>,
+) -> Result<Option<CsvDialect>, OxenError> {
+    let path = path.as_ref();
+    if !util::fs::is_delimited_text(path) {
+        return Ok(None);
+    }
+
+    let relative_path = util::fs::path_relative_to_dir(path, &repo.path)?;
+    let mut dialect = tabular::sniff_csv_dialect(repo.path.join(&relative_path))?;
+    if util::fs::has_ext(path, "tsv") {
+        dialect.delimiter = '\t';
+    }
+    Ok(Some(apply_csv_dialect_override(
+        repo,
+        &relative_path,
+        dialect,
+    )))
+}
+
+/// The dialect to read a csv or tsv at `path` with. Uses the dialect saved on the schema of the version
+/// of the file at `commit_id`, or the staged or HEAD schema if None, and detects it from the working file otherwise.
+pub fn get_csv_dialect(
+    repo: &LocalRepository,
+    commit_id: Option<&str>,
+    path: impl AsRef<Path>,
+) -> Result<Option<CsvDialect>, OxenError> {
+    let path = path.as_ref();
+    if !util::fs::is_delimited_text(path) {
+        return Ok(None);
+    }
+
+    let relative_path = util::fs::path_relative_to_dir(path, &repo.path)?;
+    // Looked up through the entry, schemas are only written in the commit that last changed the file
+    let full_path = repo.path.join(&relative_path);
+    let schema = match commit_id {
+        Some(commit_id) => match api::local::commits::get_by_id(repo, commit_id)? {
+            Some(commit) => get_schema_for_path(repo, Some(&commit), &full_path)?,
+            None => return Err(OxenError::committish_not_found(commit_id.into())),
+        },
+        None => get_schema_for_path(repo, None, &full_path)?,
+    };
+
+    match schema {
+        // Default dialects are not saved on the schema
+        Some(schema) => {
+            let dialect = schema.dialect.unwrap_or_default();
+            Ok(Some(apply_csv_dialect_override(
+                repo,
+                &relative_path,
+                dialect,
+            )))
+        }
+        None if repo.path.join(&relative_path).exists() => detect_csv_dialect(repo, &relative_path),
+        None => Ok(None),
+    }
+}
+
+fn apply_csv_dialect_override(
+    repo: &LocalRepository,
+    relative_path: &Path,
+    dialect: CsvDialect,
+) -> CsvDialect {
+    match repo.csv_dialect_override(relative_path) {
+        Some(dialect_override) => dialect_override.apply(dialect),
+        None => dialect,
+    }
+}
+
+/// The schema contracts in the .oxencontracts file of the working directory
+pub fn read_contracts(repo: &LocalRepository) -> Result<SchemaContracts, OxenError> {
+    let path = repo.path.join(SCHEMA_CONTRACTS_FILE);
+    if !path.exists() {
+        return Ok(SchemaContracts::default());
+    }
+    SchemaContracts::from_toml(&util::fs::read_from_path(&path)?)
+}
+
+/// Write the schema contracts to the .oxencontracts file of the working directory, it still has to be staged
+pub fn write_contracts(
+    repo: &LocalRepository,
+    contracts: &SchemaContracts,
+) -> Result<PathBuf, OxenError> {
+    let path = repo.path.join(SCHEMA_CONTRACTS_FILE);
+    util::fs::write_to_path(&path, &contracts.to_toml()?)?;
+    Ok(path)
+}
+
+/// The schema contracts versioned in `commit`
+pub fn get_contracts_for_commit(
+    repo: &LocalRepository,
+    commit: &Commit,
+) -> Result<SchemaContracts, OxenError> {
+    let entry_reader = CommitEntryReader::new(repo, commit)?;
+    match entry_reader.get_entry(Path::new(SCHEMA_CONTRACTS_FILE))? {
+        Some(entry) => {
+            let version_path = util::fs::version_path(repo, &entry);
+            SchemaContracts::from_toml(&util::fs::read_from_path(&version_path)?)
+        }
+        None => Ok(SchemaContracts::default()),
+    }
+}
+
+// The contracts the staged commit will have, and whether they changed since HEAD
+fn get_staged_contracts(
+    repo: &LocalRepository,
+    status: &StagedData,
+) -> Result<(SchemaContracts, bool), OxenError> {
+    match status.added_files.get(Path::new(SCHEMA_CONTRACTS_FILE)) {
+        Some(entry) if entry.status == StagedEntryStatus::Removed => {
+            Ok((SchemaContracts::default(), true))
+        }
+        Some(_) => Ok((read_contracts(repo)?, true)),
+        None => match api::local::commits::head_commit(repo) {
+            Ok(head) => Ok((get_contracts_for_commit(repo, &head)?, false)),
+            // Nothing has been committed yet
+            Err(_) => Ok((SchemaContracts::default(), false)),
+        },
+    }
+}
+
+/// Check the staged tabular files against the schema contracts the commit will have.
+/// If the contracts themselves are staged, every committed tabular file is checked too.
+/// Reads each file from the working directory so that non nullable fields can be checked.
+pub fn check_staged_contracts(
+    repo: &LocalRepository,
+    status: &StagedData,
+) -> Result<(), OxenError> {
+    let (contracts, contracts_changed) = get_staged_contracts(repo, status)?;
+    if contracts.is_empty() {
+        return Ok(());
+    }
+
+    let mut paths: BTreeSet<PathBuf> = status
+        .added_files
+        .iter()
+        .filter(|(_, entry)| entry.status != StagedEntryStatus::Removed)
+        .map(|(path, _)| path.to_owned())
+        .filter(|path| util::fs::is_tabular(path))
+        .collect();
+    // Files that did not change may not follow new contracts
+    if contracts_changed {
+        let head = api::local::commits::head_commit(repo)?;
+        for entry in CommitEntryReader::new(repo, &head)?.list_entries()? {
+            let is_removed = status
+                .added_files
+                .get(&entry.path)
+                .map(|staged| staged.status == StagedEntryStatus::Removed)
+                .unwrap_or(false);
+            if util::fs::is_tabular(&entry.path) && !is_removed {
+                paths.insert(entry.path);
+            }
+        }
+    }
+
+    let mut violations: Vec<ContractViolation> = vec![];
+    for path in paths {
+        let path_contracts = contracts.for_path(&path);
+        if path_contracts.is_empty() {
+            continue;
+        }
+
+        let mut opts = DFOpts::empty();
+        opts.dialect = match status.added_schemas.get(&path) {
+            Some(schema) if util::fs::is_delimited_text(&path) => Some(apply_csv_dialect_override(
+                repo,
+                &path,
+                schema.dialect.to_owned().unwrap_or_default(),
+            )),
+            _ => detect_csv_dialect(repo, &path)?,
+        };
+        let df = tabular::read_df(repo.path.join(&path), opts)?;
+        for contract in path_contracts {
+            violations.extend(contract.check_df(&path, &df));
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(OxenError::schema_contract_violation(&violations))
+    }
+}
+
+/// Check the tabular files a commit changed against the schema contracts versioned with it.
+/// If the commit changed the contracts, every tabular file in the commit is checked.
+/// Reads the version files, so the entries of the commit have to be synced.
+pub fn check_commit_contracts(repo: &LocalRepository, commit: &Commit) -> Result<(), OxenError> {
+    let contracts = get_contracts_for_commit(repo, commit)?;
+    if contracts.is_empty() {
+        return Ok(());
+    }
+    let contracts_changed = CommitEntryReader::new(repo, commit)?
+        .get_entry(Path::new(SCHEMA_CONTRACTS_FILE))?
+        .map(|entry| entry.commit_id == commit.id)
+        .unwrap_or(false);
+
+    let mut schema_entries: Vec<(Schema, CommitEntry)> = vec![];
+    for (schema, entries) in schema_indexer::tabular_entries_by_schema(repo, commit)?.into_values()
+    {
+        for entry in entries {
+            if contracts_changed || entry.commit_id == commit.id {
+                schema_entries.push((schema.to_owned(), entry));
+            }
+        }
+    }
+    schema_entries.sort_by(|(_, a), (_, b)| a.path.cmp(&b.path));
+
+    let mut violations: Vec<ContractViolation> = vec![];
+    for (schema, entry) in schema_entries {
+        let path_contracts = contracts.for_path(&entry.path);
+        if path_contracts.is_empty() {
+            continue;
+        }
+
+        let mut opts = DFOpts::empty();
+        opts.dialect = schema.dialect.to_owned();
+        let df = tabular::read_df(util::fs::version_path(repo, &entry), opts)?;
+        for contract in path_contracts {
+            violations.extend(contract.check_df(&entry.path, &df));
+        }
+    }
+
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(OxenError::schema_contract_violation(&violations))
+    }
+}
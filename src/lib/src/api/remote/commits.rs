@@ -1,13 +1,15 @@
 use crate::api::remote::client;
 use crate::constants::HISTORY_DIR;
-use crate::error::OxenError;
+use crate::error::{OxenError, StringError};
 use crate::model::commit::CommitWithBranchName;
 use crate::model::{Commit, LocalRepository, RemoteRepository};
 use crate::util::hasher::hash_buffer;
 use crate::{api, constants};
 use crate::{current_function, util};
 // use crate::util::ReadProgress;
-use crate::view::{CommitResponse, IsValidStatusMessage, ListCommitResponse, StatusMessage};
+use crate::view::{
+    CommitResponse, IsValidStatusMessage, ListCommitResponse, OxenResponse, StatusMessage,
+};
 
 use std::path::{Path, PathBuf};
 use std::str;
@@ -185,6 +187,9 @@ pub async fn post_push_complete(
 
     let client = client::new_for_url(&url)?;
     if let Ok(res) = client.post(&url).send().await {
+        if res.status() == reqwest::StatusCode::BAD_REQUEST {
+            return Err(rejected_commit_error(res).await);
+        }
         let body = client::parse_json_body(&url, res).await?;
         let response: Result<StatusMessage, serde_json::Error> = serde_json::from_str(&body);
         match response {
@@ -198,6 +203,15 @@ pub async fn post_push_complete(
     }
 }
 
+/// The remote rejects commits whose files violate the schema contracts versioned with them with a 400 and the report
+async fn rejected_commit_error(res: reqwest::Response) -> OxenError {
+    let report = match res.json::<OxenResponse>().await {
+        Ok(response) => response.desc_or_msg(),
+        Err(err) => format!("Commit rejected by remote: {err}"),
+    };
+    OxenError::SchemaContractViolation(Box::new(StringError::from(report)))
+}
+
 pub async fn post_commit_to_server(
     local_repo: &LocalRepository,
     remote_repo: &RemoteRepository,
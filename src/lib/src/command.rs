@@ -12,6 +12,8 @@ pub mod config;
 pub mod db_inspect;
 pub mod df;
 pub mod diff;
+pub mod du;
+pub mod index;
 pub mod init;
 pub mod merge;
 pub mod pull;
@@ -20,6 +22,7 @@ pub mod remote;
 pub mod restore;
 pub mod rm;
 pub mod schemas;
+pub mod split;
 pub mod status;
 
 pub use crate::command::add::add;
@@ -34,4 +37,5 @@ pub use crate::command::pull::{pull, pull_remote_branch};
 pub use crate::command::push::{push, push_remote_branch, push_remote_repo_branch_name};
 pub use crate::command::restore::restore;
 pub use crate::command::rm::rm;
+pub use crate::command::split::split;
 pub use crate::command::status::{status, status_from_dir};
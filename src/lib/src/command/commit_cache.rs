@@ -7,8 +7,9 @@ use std::path::Path;
 
 use crate::api;
 use crate::core::cache;
+use crate::core::cache::commit_cacher::{CacherStatus, CacherStatusType};
 use crate::error::OxenError;
-use crate::model::LocalRepository;
+use crate::model::{Commit, LocalRepository};
 use crate::opts::LogOpts;
 
 /// Run the computation cache on all repositories within a directory
@@ -37,7 +38,7 @@ pub async fn compute_cache_on_all_repos(path: &Path) -> Result<(), OxenError> {
     Ok(())
 }
 
-/// Run the computation cache on all repositories within a directory
+/// Run the computation cache on the commits in the history of `committish`, or of HEAD if None
 pub async fn compute_cache(
     repo: &LocalRepository,
     committish: Option<String>,
@@ -46,18 +47,70 @@ pub async fn compute_cache(
         "Compute cache for commit given [{committish:?}] on repo {:?}",
         repo.path
     );
-    let commits = if let Some(committish) = committish {
+    for commit in list_commits(repo, committish).await? {
+        println!("Compute cache for commit {:?}", commit);
+        cache::commit_cacher::run_all(repo, &commit)?;
+        print_statuses(repo, &commit)?;
+    }
+    Ok(())
+}
+
+/// Print the status of each cacher on the commits in the history of `committish` without computing anything
+pub async fn print_cache_status(
+    repo: &LocalRepository,
+    committish: Option<String>,
+) -> Result<(), OxenError> {
+    for commit in list_commits(repo, committish).await? {
+        println!("{} {}", commit.id, commit.message);
+        print_statuses(repo, &commit)?;
+    }
+    Ok(())
+}
+
+async fn list_commits(
+    repo: &LocalRepository,
+    committish: Option<String>,
+) -> Result<Vec<Commit>, OxenError> {
+    if let Some(committish) = committish {
         let opts = LogOpts {
             committish: Some(committish),
             remote: false,
         };
-        api::local::commits::list_with_opts(repo, &opts).await?
+        api::local::commits::list_with_opts(repo, &opts).await
     } else {
-        api::local::commits::list(repo)?
-    };
-    for commit in commits {
-        println!("Compute cache for commit {:?}", commit);
-        cache::commit_cacher::run_all(repo, &commit)?;
+        api::local::commits::list(repo)
+    }
+}
+
+/// The status of each cacher, with the number of files it computed and the files it failed on
+fn print_statuses(repo: &LocalRepository, commit: &Commit) -> Result<(), OxenError> {
+    let statuses = cache::commit_cacher::get_all_statuses(repo, commit)?;
+    if statuses.is_empty() {
+        println!("  not computed");
+    }
+    for cacher in statuses.iter().filter(|s| s.path.is_none()) {
+        let files: Vec<&CacherStatus> = statuses
+            .iter()
+            .filter(|s| s.path.is_some() && s.name == cacher.name)
+            .collect();
+        let failed: Vec<&&CacherStatus> = files
+            .iter()
+            .filter(|s| s.status == CacherStatusType::Failed)
+            .collect();
+        println!(
+            "  {} v{} {:?} {} files, {} failed {}",
+            cacher.name,
+            cacher.version,
+            cacher.status,
+            files.len(),
+            failed.len(),
+            cacher.status_message
+        );
+        for file in failed {
+            if let Some(path) = &file.path {
+                println!("    {}: {}", path.display(), file.status_message);
+            }
+        }
     }
     Ok(())
 }
@@ -3,22 +3,170 @@
 //! Interact with DataFrames
 //!
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
+use polars::prelude::LazyFrame;
+
+use crate::api;
+use crate::core::cache::cachers::column_stats;
 use crate::core::df::tabular;
+use crate::core::index::{schema_indexer, CommitEntryReader};
 use crate::error::OxenError;
+use crate::model::{Commit, LocalRepository, Schema, TabularStats};
 use crate::opts::DFOpts;
 use crate::util;
 
 /// Interact with DataFrames
 pub fn df<P: AsRef<Path>>(input: P, opts: DFOpts) -> Result<(), OxenError> {
-    let mut df = tabular::show_path(input, opts.clone())?;
+    let input = input.as_ref();
+    let df = tabular::scan_df(input, &opts)?;
+    transform_and_output(input, df, None, opts)
+}
+
+/// Interact with a DataFrame in a repository. If the file is unchanged since HEAD,
+/// equality filters on indexed fields only read the rows the index points to.
+pub fn df_in_repo(
+    repo: &LocalRepository,
+    input: impl AsRef<Path>,
+    mut opts: DFOpts,
+) -> Result<(), OxenError> {
+    let input = input.as_ref();
+    let full_path = repo.path.join(input);
+    resolve_csv_dialect(repo, &full_path, None, &mut opts)?;
+    let schema = api::local::schemas::get_schema_for_path(repo, None, &full_path)?;
+    if opts.filter_reads_source_rows() {
+        if let Ok(commit) = api::local::commits::head_commit(repo) {
+            let relative_path = util::fs::path_relative_to_dir(&full_path, &repo.path)?;
+            let entry = CommitEntryReader::new(repo, &commit)?.get_entry(&relative_path)?;
+            let unchanged = match entry {
+                Some(entry) => util::hasher::hash_file_contents(&full_path)? == entry.hash,
+                None => false,
+            };
+            if unchanged {
+                if let Some(df) =
+                    schema_indexer::scan_indexed(repo, &commit, &relative_path, &opts)?
+                {
+                    return transform_and_output(input, df, schema.as_ref(), opts);
+                }
+            }
+        }
+    }
+    let df = tabular::scan_df(input, &opts)?;
+    transform_and_output(input, df, schema.as_ref(), opts)
+}
 
-    if let Some(output) = opts.output {
+/// Warns about values outside the constraints declared on the fields of `schema`
+fn transform_and_output(
+    input: &Path,
+    df: LazyFrame,
+    schema: Option<&Schema>,
+    opts: DFOpts,
+) -> Result<(), OxenError> {
+    let df = tabular::transform_lazy(df, &opts)?;
+    let df = tabular::slice_lazy(df, &opts)?;
+    if let Some(schema) = schema.filter(|s| s.has_field_constraints()) {
+        // Counted in their own streaming pass, so the output below can still be streamed
+        for warning in schema.check_field_constraints(df.clone())? {
+            eprintln!("Warning: {warning}");
+        }
+    }
+    if let Some(output) = &opts.output {
         println!("Writing {output:?}");
-        tabular::write_df(&mut df, output)?;
+        if input == output {
+            // Cannot stream into the file we are still reading from
+            let mut df = tabular::collect_df(df)?;
+            tabular::write_df(&mut df, output)?;
+        } else {
+            tabular::sink_df(df, output)?;
+        }
+    } else {
+        tabular::show_df(df, &opts)?;
+    }
+
+    Ok(())
+}
+
+/// Interact with the version of a DataFrame committed at `revision` (a commit id or branch name),
+/// reading the version file directly without checking anything out
+pub fn df_revision(
+    repo: &LocalRepository,
+    input: impl AsRef<Path>,
+    revision: &str,
+    mut opts: DFOpts,
+) -> Result<(), OxenError> {
+    let input = input.as_ref();
+    let (commit, version_path) = version_path_at_revision(repo, input, revision)?;
+    resolve_csv_dialect(repo, input, Some(&commit.id), &mut opts)?;
+    // Join against the same revision unless told otherwise
+    if opts.join.is_some() && opts.join_revision.is_none() {
+        opts.join_revision = Some(revision.to_string());
+    }
+    resolve_join_revision(repo, &mut opts)?;
+    resolve_sql_tables(repo, &commit, &mut opts)?;
+    let relative_path = util::fs::path_relative_to_dir(input, &repo.path)?;
+    let schema = api::local::schemas::get_schema_for_path(repo, Some(&commit), &relative_path)?;
+    if let Some(df) = schema_indexer::scan_indexed(repo, &commit, &relative_path, &opts)? {
+        return transform_and_output(&version_path, df, schema.as_ref(), opts);
+    }
+    let df = tabular::scan_df(&version_path, &opts)?;
+    transform_and_output(&version_path, df, schema.as_ref(), opts)
+}
+
+/// Row count and per column stats of a DataFrame, after the transforms in `opts` are applied
+pub fn stats(input: impl AsRef<Path>, opts: DFOpts) -> Result<TabularStats, OxenError> {
+    let input = input.as_ref();
+    let df = tabular::read_df(input, opts)?;
+    Ok(TabularStats::from_df(input, &df))
+}
+
+/// Row count and per column stats of the version of a DataFrame committed at `revision`.
+/// Uses the stats cached on the commit unless there are transforms to apply.
+pub fn stats_revision(
+    repo: &LocalRepository,
+    input: impl AsRef<Path>,
+    revision: &str,
+    mut opts: DFOpts,
+) -> Result<TabularStats, OxenError> {
+    let input = input.as_ref();
+    let (commit, version_path) = version_path_at_revision(repo, input, revision)?;
+    let relative_path = util::fs::path_relative_to_dir(input, &repo.path)?;
+    if !opts.has_transform() {
+        if let Some(stats) = column_stats::get_stats(repo, &commit, &relative_path)? {
+            return Ok(stats);
+        }
+    }
+    resolve_csv_dialect(repo, input, Some(&commit.id), &mut opts)?;
+    let df = tabular::read_df(version_path, opts)?;
+    Ok(TabularStats::from_df(relative_path, &df))
+}
+
+/// Point `opts.join` at the version of the file committed at `opts.join_revision`,
+/// so that a file can be joined against an older version of another file
+pub fn resolve_join_revision(repo: &LocalRepository, opts: &mut DFOpts) -> Result<(), OxenError> {
+    if let (Some(join), Some(revision)) = (&opts.join, &opts.join_revision) {
+        let commit = api::local::commits::get_by_id_or_branch(repo, revision)?
+            .ok_or_else(|| OxenError::committish_not_found(revision.to_owned().into()))?;
+        let version_path = api::local::diff::get_version_file_from_commit(repo, &commit, join)?;
+        opts.join = Some(version_path);
     }
+    Ok(())
+}
 
+/// Point the tables referenced from `opts.sql` at the versions of their files committed at `commit`,
+/// so that a query at a revision never reads from the working tree
+pub fn resolve_sql_tables(
+    repo: &LocalRepository,
+    commit: &Commit,
+    opts: &mut DFOpts,
+) -> Result<(), OxenError> {
+    if opts.sql_tables.is_some() {
+        let mut tables: Vec<String> = vec![];
+        for (name, path) in opts.sql_table_paths()? {
+            let version_path = api::local::diff::get_version_file_from_commit(repo, commit, &path)?;
+            tables.push(format!("{name}={}", version_path.to_string_lossy()));
+        }
+        opts.sql_tables = Some(tables);
+    }
     Ok(())
 }
 
@@ -27,6 +175,45 @@ pub fn schema<P: AsRef<Path>>(input: P, flatten: bool, opts: DFOpts) -> Result<S
     tabular::schema_to_string(input, flatten, &opts)
 }
 
+/// Get a human readable schema for the version of a DataFrame committed at `revision`
+pub fn schema_revision(
+    repo: &LocalRepository,
+    input: impl AsRef<Path>,
+    revision: &str,
+    flatten: bool,
+    mut opts: DFOpts,
+) -> Result<String, OxenError> {
+    let input = input.as_ref();
+    let (commit, version_path) = version_path_at_revision(repo, input, revision)?;
+    resolve_csv_dialect(repo, input, Some(&commit.id), &mut opts)?;
+    schema(version_path, flatten, opts)
+}
+
+/// Read a csv in the repository with the dialect saved on its schema at `commit_id`, or staged if None,
+/// so it is parsed the same way it was when it was added
+pub fn resolve_csv_dialect(
+    repo: &LocalRepository,
+    input: impl AsRef<Path>,
+    commit_id: Option<&str>,
+    opts: &mut DFOpts,
+) -> Result<(), OxenError> {
+    if opts.dialect.is_none() {
+        opts.dialect = api::local::schemas::get_csv_dialect(repo, commit_id, input)?;
+    }
+    Ok(())
+}
+
+fn version_path_at_revision(
+    repo: &LocalRepository,
+    input: &Path,
+    revision: &str,
+) -> Result<(Commit, PathBuf), OxenError> {
+    let commit = api::local::commits::get_by_id_or_branch(repo, revision)?
+        .ok_or_else(|| OxenError::committish_not_found(revision.into()))?;
+    let version_path = api::local::diff::get_version_file_from_commit(repo, &commit, input)?;
+    Ok((commit, version_path))
+}
+
 /// Add a row to a dataframe
 pub fn add_row(path: &Path, data: &str) -> Result<(), OxenError> {
     if util::fs::is_tabular(path) {
@@ -3,18 +3,21 @@
 //! Compare files and directories between versions
 //!
 
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 use crate::api;
 use crate::core::index::MergeConflictReader;
 use crate::error::OxenError;
-use crate::model::LocalRepository;
+use crate::model::{Commit, DiffSummary, ImageDiff, LocalRepository};
+use crate::opts::DFOpts;
+use crate::util;
 
 /// Diff a file from a commit or compared to another file
 /// `resource` can be a None, commit id, branch name, or another path.
 ///    None: compare `path` to the last commit versioned of the file. If a merge conflict with compare to the merge conflict
 ///    commit id: compare `path` to the version of `path` from that commit
 ///    branch name: compare `path` to the version of `path` from that branch
+///    base..head: compare the version of `path` from base to the version from head
 ///    another path: compare `path` to the other `path` provided
 /// `path` is the path you want to compare the resource to
 pub fn diff(
@@ -22,21 +25,91 @@ pub fn diff(
     resource: Option<&str>,
     path: impl AsRef<Path>,
 ) -> Result<String, OxenError> {
+    let path = path.as_ref();
+    let (original, compare) = resolve_diff_paths(repo, resource, path)?;
+    if util::fs::is_delimited_text(path) && util::fs::is_tabular(&original.path) {
+        // Each version of `path` is read with the dialect saved on its own schema,
+        // another file given as the resource is sniffed
+        let original_opts = match resource {
+            Some(resource) if Path::new(resource) == original.path => DFOpts::empty(),
+            _ => original.df_opts(repo, path)?,
+        };
+        let compare_opts = compare.df_opts(repo, path)?;
+        let diff = api::local::diff::diff_tabular_with_opts(
+            original.path,
+            compare.path,
+            &original_opts,
+            &compare_opts,
+        )?;
+        return Ok(diff.to_string());
+    }
+    api::local::diff::diff_files(original.path, compare.path)
+}
+
+/// Diff two versions of an image, resolving `resource` and `path` the same way as `diff`.
+/// Optionally writes a mask of the changed pixels to `mask_path`.
+pub fn diff_image(
+    repo: &LocalRepository,
+    resource: Option<&str>,
+    path: impl AsRef<Path>,
+    mask_path: Option<&Path>,
+) -> Result<ImageDiff, OxenError> {
+    let (original, compare) = resolve_diff_paths(repo, resource, path)?;
+    api::local::diff::diff_image(original.path, compare.path, mask_path)
+}
+
+/// A file to diff and the commit its version was read from, None for a file in the working directory
+struct DiffVersion {
+    path: PathBuf,
+    commit: Option<Commit>,
+}
+
+impl DiffVersion {
+    fn working(path: impl AsRef<Path>) -> DiffVersion {
+        DiffVersion {
+            path: path.as_ref().to_path_buf(),
+            commit: None,
+        }
+    }
+
+    /// Read with the dialect of `repo_path` at the commit, or the staged one for the working file
+    fn df_opts(&self, repo: &LocalRepository, repo_path: &Path) -> Result<DFOpts, OxenError> {
+        let commit_id = self.commit.as_ref().map(|c| c.id.as_str());
+        let mut opts = DFOpts::empty();
+        opts.dialect = api::local::schemas::get_csv_dialect(repo, commit_id, repo_path)?;
+        Ok(opts)
+    }
+}
+
+/// Returns the (original, compare) pair of files to diff
+fn resolve_diff_paths(
+    repo: &LocalRepository,
+    resource: Option<&str>,
+    path: impl AsRef<Path>,
+) -> Result<(DiffVersion, DiffVersion), OxenError> {
+    let path = path.as_ref();
     if let Some(resource) = resource {
         // `resource` is Some(resource)
-        if let Some(compare_commit) = api::local::commits::get_by_id(repo, resource)? {
+        if let Some((base, head)) = resource.split_once("..") {
+            // `resource` is a range of two revisions, neither needs to be checked out
+            let original_commit = api::local::commits::get_by_id_or_branch(repo, base)?
+                .ok_or_else(|| OxenError::committish_not_found(base.into()))?;
+            let compare_commit = api::local::commits::get_by_id_or_branch(repo, head)?
+                .ok_or_else(|| OxenError::committish_not_found(head.into()))?;
+            version_paths(repo, &original_commit, &compare_commit, path)
+        } else if let Some(compare_commit) = api::local::commits::get_by_id(repo, resource)? {
             // `resource` is a commit id
             let original_commit = api::local::commits::head_commit(repo)?;
-            api::local::diff::diff(repo, &original_commit, &compare_commit, path)
+            version_paths(repo, &original_commit, &compare_commit, path)
         } else if let Some(branch) = api::local::branches::get_by_name(repo, resource)? {
             // `resource` is a branch name
             let compare_commit = api::local::commits::get_by_id(repo, &branch.commit_id)?.unwrap();
             let original_commit = api::local::commits::head_commit(repo)?;
 
-            api::local::diff::diff(repo, &original_commit, &compare_commit, path)
+            version_paths(repo, &original_commit, &compare_commit, path)
         } else if Path::new(resource).exists() {
             // `resource` is another path
-            api::local::diff::diff_files(resource, path)
+            Ok((DiffVersion::working(resource), DiffVersion::working(path)))
         } else {
             Err(OxenError::basic_str(format!(
                 "Could not find resource: {resource:?}"
@@ -49,13 +122,13 @@ pub fn diff(
         if merger.has_conflicts()? {
             match merger.get_conflict_commit() {
                 Ok(Some(commit)) => {
-                    let current_path = path.as_ref();
-                    let version_path = api::local::diff::get_version_file_from_commit(
-                        repo,
-                        &commit,
-                        current_path,
-                    )?;
-                    api::local::diff::diff_files(current_path, version_path)
+                    let version_path =
+                        api::local::diff::get_version_file_from_commit(repo, &commit, path)?;
+                    let compare = DiffVersion {
+                        path: version_path,
+                        commit: Some(commit),
+                    };
+                    Ok((DiffVersion::working(path), compare))
                 }
                 err => {
                     log::error!("{err:?}");
@@ -66,11 +139,40 @@ pub fn diff(
             }
         } else {
             // No merge conflicts, compare to last version committed of the file
-            let current_path = path.as_ref();
             let commit = api::local::commits::head_commit(repo)?;
-            let version_path =
-                api::local::diff::get_version_file_from_commit(repo, &commit, current_path)?;
-            api::local::diff::diff_files(version_path, current_path)
+            let version_path = api::local::diff::get_version_file_from_commit(repo, &commit, path)?;
+            let original = DiffVersion {
+                path: version_path,
+                commit: Some(commit),
+            };
+            Ok((original, DiffVersion::working(path)))
         }
     }
 }
+
+fn version_paths(
+    repo: &LocalRepository,
+    original: &Commit,
+    compare: &Commit,
+    path: &Path,
+) -> Result<(DiffVersion, DiffVersion), OxenError> {
+    let original = DiffVersion {
+        path: api::local::diff::get_version_file_from_commit(repo, original, path)?,
+        commit: Some(original.to_owned()),
+    };
+    let compare = DiffVersion {
+        path: api::local::diff::get_version_file_from_commit(repo, compare, path)?,
+        commit: Some(compare.to_owned()),
+    };
+    Ok((original, compare))
+}
+
+/// Summarize all the changes between two revisions, grouped by directory and data type
+/// `base` and `head` can be a commit id or a branch name
+pub fn summary(repo: &LocalRepository, base: &str, head: &str) -> Result<DiffSummary, OxenError> {
+    let base_commit = api::local::commits::get_by_id_or_branch(repo, base)?
+        .ok_or_else(|| OxenError::committish_not_found(base.into()))?;
+    let head_commit = api::local::commits::get_by_id_or_branch(repo, head)?
+        .ok_or_else(|| OxenError::committish_not_found(head.into()))?;
+    api::local::diff::summarize_diff(repo, &base_commit, &head_commit)
+}
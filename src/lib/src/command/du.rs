@@ -0,0 +1,130 @@
+//! # oxen du
+//!
+//! Size and composition of a repository, and how it grew over its commits
+//!
+
+use bytesize::ByteSize;
+use std::path::{Component, Path, PathBuf};
+
+use crate::api;
+use crate::error::OxenError;
+use crate::model::{LocalRepository, RepoSizeLogEntry, RepoSizeStats};
+use crate::util;
+
+/// How many of the largest files and directories to print
+const NUM_LARGEST: usize = 10;
+/// Width of the bars charting the size of each commit
+const BAR_WIDTH: usize = 40;
+
+/// The stats of the repository at HEAD
+pub fn du(repo: &LocalRepository) -> Result<RepoSizeStats, OxenError> {
+    let head = api::local::commits::head_commit(repo)?;
+    api::local::repositories::get_size_stats(repo, &head)
+}
+
+/// The stats after every commit in the history of HEAD, oldest first
+pub fn du_history(repo: &LocalRepository) -> Result<Vec<RepoSizeLogEntry>, OxenError> {
+    let head = api::local::commits::head_commit(repo)?;
+    api::local::repositories::list_size_stats(repo, &head)
+}
+
+/// Make `path` relative to the root of the repository, which is the empty path
+pub fn relative_path(repo: &LocalRepository, path: impl AsRef<Path>) -> Result<PathBuf, OxenError> {
+    let path = path.as_ref();
+    let path = if path.is_absolute() {
+        util::fs::path_relative_to_dir(path, &repo.path)?
+    } else {
+        path.to_path_buf()
+    };
+    Ok(path
+        .components()
+        .filter(|c| c != &Component::CurDir)
+        .collect())
+}
+
+/// The size of `path`, its largest directories and its largest files
+pub fn format_usage(stats: &RepoSizeStats, path: impl AsRef<Path>) -> Result<String, OxenError> {
+    let path = path.as_ref();
+    let dir = match stats.dir(path) {
+        Some(dir) => dir,
+        None => return Err(OxenError::path_does_not_exist(path.to_path_buf())),
+    };
+    let mut lines = vec![format!(
+        "{} in {} files",
+        ByteSize::b(dir.data_size),
+        dir.file_count
+    )];
+
+    let subdirs = stats.subdirs(path);
+    if !subdirs.is_empty() {
+        let mut table = comfy_table::Table::new();
+        table.set_header(vec!["directory", "size", "files"]);
+        for subdir in subdirs.into_iter().take(NUM_LARGEST) {
+            table.add_row(vec![
+                subdir.path.to_string_lossy().to_string(),
+                ByteSize::b(subdir.data_size).to_string(),
+                subdir.file_count.to_string(),
+            ]);
+        }
+        lines.push(table.to_string());
+    }
+
+    let files = stats.largest_files_in(path);
+    if !files.is_empty() {
+        let mut table = comfy_table::Table::new();
+        table.set_header(vec!["file", "type", "size", "rows"]);
+        for file in files.into_iter().take(NUM_LARGEST) {
+            table.add_row(vec![
+                file.path.to_string_lossy().to_string(),
+                file.data_type.to_owned(),
+                ByteSize::b(file.data_size).to_string(),
+                file.num_rows.map(|n| n.to_string()).unwrap_or_default(),
+            ]);
+        }
+        lines.push(table.to_string());
+    }
+    Ok(lines.join("\n"))
+}
+
+/// One row per commit with the size of `path`, how much it changed and a bar charting it
+pub fn format_history(entries: &[RepoSizeLogEntry], path: impl AsRef<Path>) -> String {
+    let path = path.as_ref();
+    let sizes: Vec<(u64, usize)> = entries
+        .iter()
+        .map(|entry| match entry.stats.dir(path) {
+            Some(dir) => (dir.data_size, dir.file_count),
+            None => (0, 0),
+        })
+        .collect();
+    let max_size = sizes
+        .iter()
+        .map(|(size, _)| *size)
+        .max()
+        .unwrap_or(0)
+        .max(1);
+
+    let mut table = comfy_table::Table::new();
+    table.set_header(vec![
+        "commit", "date", "message", "size", "files", "change", "",
+    ]);
+    let mut previous: u64 = 0;
+    for (entry, (size, file_count)) in entries.iter().zip(sizes) {
+        let change = if size >= previous {
+            format!("+{}", ByteSize::b(size - previous))
+        } else {
+            format!("-{}", ByteSize::b(previous - size))
+        };
+        let bar_len = (size as f64 / max_size as f64 * BAR_WIDTH as f64).round() as usize;
+        table.add_row(vec![
+            entry.commit.id.chars().take(8).collect::<String>(),
+            entry.commit.timestamp.date().to_string(),
+            entry.commit.message.to_owned(),
+            ByteSize::b(size).to_string(),
+            file_count.to_string(),
+            change,
+            "█".repeat(bar_len),
+        ]);
+        previous = size;
+    }
+    table.to_string()
+}
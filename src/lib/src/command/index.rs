@@ -0,0 +1,33 @@
+//! # oxen index
+//!
+//! Index the values of a schema field so equality filters can look up rows instead of scanning files
+//!
+
+use crate::api;
+use crate::core::index::schema_indexer;
+use crate::error::OxenError;
+use crate::model::schema::Field;
+use crate::model::{LocalRepository, Schema};
+
+/// Index a field for all the files with the schema `schema_ref` (a name or hash) at HEAD.
+/// Later commits keep the index up to date.
+pub fn create(
+    repo: &LocalRepository,
+    schema_ref: &str,
+    field: &str,
+) -> Result<(Schema, Field), OxenError> {
+    let commit = api::local::commits::head_commit(repo)?;
+    schema_indexer::create_field_index(repo, &commit, schema_ref, field)
+}
+
+/// List the indexed fields at HEAD
+pub fn list(repo: &LocalRepository) -> Result<Vec<(Schema, Field)>, OxenError> {
+    let commit = api::local::commits::head_commit(repo)?;
+    schema_indexer::list_field_indices(repo, &commit)
+}
+
+/// Remove the index on a field at HEAD
+pub fn drop(repo: &LocalRepository, schema_ref: &str, field: &str) -> Result<(), OxenError> {
+    let commit = api::local::commits::head_commit(repo)?;
+    schema_indexer::drop_field_index(repo, &commit, schema_ref, field)
+}
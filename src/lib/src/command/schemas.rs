@@ -4,9 +4,13 @@
 //!
 
 use crate::api;
+use crate::command;
 use crate::core::index::Stager;
 use crate::error::OxenError;
-use crate::model::{LocalRepository, Schema};
+use crate::model::schema::{FieldMetadata, SchemaContract, SchemaFileDiff, SchemaLogEntry};
+use crate::model::{Commit, LocalRepository, Schema};
+
+use std::path::Path;
 
 /// List the saved off schemas for a commit id
 pub fn list(repo: &LocalRepository, commit_id: Option<&str>) -> Result<Vec<Schema>, OxenError> {
@@ -56,3 +60,91 @@ pub fn set_name(repo: &LocalRepository, hash: &str, val: &str) -> Result<(), Oxe
     let stager = Stager::new(repo)?;
     stager.update_schema_names_for_hash(hash, val)
 }
+
+/// Set the metadata of a field on a staged schema, values not set on `metadata` are kept
+/// and the ones named in `clear` (see `FieldMetadata::KEYS`) are removed.
+/// The metadata is committed with the schema and carried over when the file changes.
+pub fn set_field_metadata(
+    repo: &LocalRepository,
+    schema_ref: &str,
+    field: &str,
+    metadata: &FieldMetadata,
+    clear: &[String],
+) -> Result<Schema, OxenError> {
+    let stager = Stager::new(repo)?;
+    stager.update_schema_field_metadata(schema_ref, field, metadata, clear)
+}
+
+/// Pin the schema `schema_ref` (a name or hash at HEAD) to the tabular files matching `path`,
+/// replacing any contract already pinned to `path`. Columns in `not_null` may not contain nulls.
+/// The contract is written to the .oxencontracts file and staged, it is enforced from the next commit.
+pub fn pin(
+    repo: &LocalRepository,
+    path: &str,
+    schema_ref: &str,
+    not_null: &[String],
+    allow_extra_fields: bool,
+) -> Result<SchemaContract, OxenError> {
+    let schema = match get_from_head(repo, schema_ref)? {
+        Some(schema) => schema,
+        None => return Err(OxenError::schema_does_not_exist(schema_ref)),
+    };
+
+    let mut contract = SchemaContract::from_schema(path, &schema);
+    contract.allow_extra_fields = allow_extra_fields;
+    for name in not_null {
+        match contract.fields.iter_mut().find(|f| f.name == *name) {
+            Some(field) => field.nullable = false,
+            None => return Err(OxenError::schema_does_not_have_field(name)),
+        }
+    }
+
+    let mut contracts = api::local::schemas::read_contracts(repo)?;
+    contracts.schema_contracts.retain(|c| c.path != path);
+    contracts.schema_contracts.push(contract.to_owned());
+    let contracts_path = api::local::schemas::write_contracts(repo, &contracts)?;
+    command::add(repo, contracts_path)?;
+    Ok(contract)
+}
+
+/// Remove the contract pinned to `path` and stage the .oxencontracts file, returns whether there was one
+pub fn unpin(repo: &LocalRepository, path: &str) -> Result<bool, OxenError> {
+    let mut contracts = api::local::schemas::read_contracts(repo)?;
+    let num_contracts = contracts.schema_contracts.len();
+    contracts.schema_contracts.retain(|c| c.path != path);
+    if contracts.schema_contracts.len() == num_contracts {
+        return Ok(false);
+    }
+    let contracts_path = api::local::schemas::write_contracts(repo, &contracts)?;
+    command::add(repo, contracts_path)?;
+    Ok(true)
+}
+
+/// How the schemas of tabular files changed between two revisions (branch names or commit ids),
+/// optionally only for the files with schema `schema_ref`
+pub fn diff(
+    repo: &LocalRepository,
+    base_revision: &str,
+    head_revision: &str,
+    schema_ref: Option<&str>,
+) -> Result<Vec<SchemaFileDiff>, OxenError> {
+    let base = get_commit(repo, base_revision)?;
+    let head = get_commit(repo, head_revision)?;
+    api::local::schemas::diff(repo, &base, &head, schema_ref)
+}
+
+/// The commits from HEAD back that changed the schema of the file at `path`, newest first
+pub fn log(
+    repo: &LocalRepository,
+    path: impl AsRef<Path>,
+) -> Result<Vec<SchemaLogEntry>, OxenError> {
+    let head = api::local::commits::head_commit(repo)?;
+    api::local::schemas::log(repo, &head, path)
+}
+
+fn get_commit(repo: &LocalRepository, revision: &str) -> Result<Commit, OxenError> {
+    match api::local::commits::get_by_id_or_branch(repo, revision)? {
+        Some(commit) => Ok(commit),
+        None => Err(OxenError::committish_not_found(revision.into())),
+    }
+}
@@ -0,0 +1,77 @@
+//! # oxen split
+//!
+//! Split a tabular file into reproducible train/val/test files
+//!
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::command;
+use crate::core::df::{split, tabular};
+use crate::error::OxenError;
+use crate::model::{Commit, LocalRepository};
+use crate::opts::{DFOpts, SplitOpts};
+use crate::util;
+
+/// Split the rows of `input` into one file per ratio and return the paths that were written.
+/// If the split files already exist, the rows in them stay in the same split.
+pub fn split(input: impl AsRef<Path>, opts: &SplitOpts) -> Result<Vec<PathBuf>, OxenError> {
+    let input = input.as_ref();
+    opts.validate()?;
+    let df = tabular::read_df(input, DFOpts::empty())?;
+    let paths = opts.output_paths(input)?;
+    if let Some(dir) = &opts.output {
+        util::fs::create_dir_all(dir)?;
+    }
+
+    let mut existing: HashMap<String, usize> = HashMap::new();
+    for (i, path) in paths.iter().enumerate() {
+        if path.exists() {
+            let split_df = tabular::read_df(path, DFOpts::empty())?;
+            for key in split::row_keys(&split_df, &opts.key)? {
+                existing.insert(key, i);
+            }
+        }
+    }
+    log::debug!(
+        "split {:?} with {} rows already in a split",
+        input,
+        existing.len()
+    );
+
+    let splits = split::split_df(&df, opts, &existing)?;
+    for ((mut split_df, path), name) in splits
+        .into_iter()
+        .zip(paths.iter())
+        .zip(opts.split_names().iter())
+    {
+        println!(
+            "Writing {} rows to {} split {:?}",
+            split_df.height(),
+            name,
+            path
+        );
+        tabular::write_df(&mut split_df, path)?;
+    }
+    Ok(paths)
+}
+
+/// Split `input` and stage the split files if `opts.add` is set, committing them if there is a commit message
+pub fn split_in_repo(
+    repo: &LocalRepository,
+    input: impl AsRef<Path>,
+    opts: &SplitOpts,
+) -> Result<Option<Commit>, OxenError> {
+    let paths = split(input, opts)?;
+    if !opts.add && opts.commit_message.is_none() {
+        return Ok(None);
+    }
+
+    for path in paths.iter() {
+        command::add(repo, path)?;
+    }
+    match &opts.commit_message {
+        Some(message) => Ok(Some(command::commit(repo, message)?)),
+        None => Ok(None),
+    }
+}
@@ -9,6 +9,8 @@ pub const OXEN_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub const OXEN_HIDDEN_DIR: &str = ".oxen";
 /// .oxenignore is the name of the file that contains the ignore patterns
 pub const OXEN_IGNORE_FILE: &str = ".oxenignore";
+/// .oxencontracts is the versioned file that pins schemas to path globs
+pub const SCHEMA_CONTRACTS_FILE: &str = ".oxencontracts";
 /// Config file for the repository
 pub const REPO_CONFIG_FILENAME: &str = "config.toml";
 /// HEAD file holds onto where the head commit is (commit_id or branch name)
@@ -25,10 +27,18 @@ pub const SCHEMAS_DIR: &str = "schemas";
 pub const ROWS_DIR: &str = "rows";
 /// prefix for the commit entry files
 pub const FILES_DIR: &str = "files";
+/// name of the db of csv dialects per file, next to the schemas
+pub const DIALECTS_DIR: &str = "dialects";
 /// prefix for the commit entry dirs
 pub const DIRS_DIR: &str = "dirs";
 /// prefix for the cached stats dirs
 pub const CACHE_DIR: &str = "cache";
+/// prefix for the cached per column stats of the tabular files in a commit
+pub const COLUMN_STATS_DIR: &str = "column_stats";
+/// prefix for the cached meta data and thumbnails of images, by content hash
+pub const IMAGES_DIR: &str = "images";
+/// file holding the cached size and composition of the repository at a commit
+pub const REPO_STATS_FILE: &str = "repo_stats.json";
 /// prefix for the staged dirs
 pub const STAGED_DIR: &str = "staged";
 /// prefix for the sync status dirs to tell if commits are synced locally
@@ -79,6 +89,8 @@ pub const ROW_NUM_COL_NAME: &str = "_row_num";
 pub const ROW_HASH_COL_NAME: &str = "_row_hash";
 /// Internal Name When Performing Computation
 pub const FILE_ROW_NUM_COL_NAME: &str = "_file_row_num";
+/// Table name the data frame is registered as when running sql queries
+pub const SQL_DF_TABLE_NAME: &str = "df";
 
 // Data transfer
 // Average chunk size of ~4mb
@@ -92,3 +104,9 @@ pub const NUM_HTTP_RETRIES: u64 = 6;
 pub const DEFAULT_PAGE_SIZE: usize = 10;
 /// Pagination page number of 1
 pub const DEFAULT_PAGE_NUM: usize = 1;
+/// Pagination of binary data frame responses, where there is no json body to put it in
+pub const PAGE_NUMBER_HEADER: &str = "oxen-page-number";
+pub const PAGE_SIZE_HEADER: &str = "oxen-page-size";
+pub const TOTAL_PAGES_HEADER: &str = "oxen-total-pages";
+pub const TOTAL_ENTRIES_HEADER: &str = "oxen-total-entries";
+pub const FULL_WIDTH_HEADER: &str = "oxen-full-width";
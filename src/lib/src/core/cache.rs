@@ -1,2 +1,5 @@
+pub mod cacher;
 pub mod cachers;
 pub mod commit_cacher;
+
+pub use cacher::Cacher;
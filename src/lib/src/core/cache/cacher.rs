@@ -0,0 +1,49 @@
+//! A Cacher computes values on a commit that will never change but are expensive to compute at runtime
+
+use std::path::PathBuf;
+
+use crate::error::OxenError;
+use crate::model::{Commit, CommitEntry, LocalRepository};
+
+pub trait Cacher: Send + Sync {
+    /// Unique name the status of the cacher is saved under
+    fn name(&self) -> &str;
+
+    /// Bump when the values change, so that values computed by older versions are computed again
+    fn version(&self) -> u32 {
+        1
+    }
+
+    /// Names of the cachers that have to succeed on a commit before this one runs on it
+    fn dependencies(&self) -> Vec<&str> {
+        vec![]
+    }
+
+    /// Whether the cacher computes a value for this file. Cachers that only compute one
+    /// value for the whole commit apply to none.
+    fn applies_to(&self, _entry: &CommitEntry) -> bool {
+        false
+    }
+
+    /// Compute the values for the commit. `entries` are the files the cacher applies to that
+    /// changed since the parent commit. Returns the files that could not be computed.
+    fn compute(
+        &self,
+        repo: &LocalRepository,
+        commit: &Commit,
+        entries: &[CommitEntry],
+    ) -> Result<Vec<(PathBuf, OxenError)>, OxenError>;
+
+    /// The files the cacher applies to that did not change since `parent`, where their values
+    /// were already computed. Cachers that save values by commit instead of by content copy them over.
+    /// Returns the files whose values are missing on the parent, they are computed instead.
+    fn carry_over(
+        &self,
+        _repo: &LocalRepository,
+        _parent: &Commit,
+        _commit: &Commit,
+        _entries: &[CommitEntry],
+    ) -> Result<Vec<CommitEntry>, OxenError> {
+        Ok(vec![])
+    }
+}
@@ -1,2 +1,5 @@
+pub mod column_stats;
 pub mod content_validator;
 pub mod convert_to_arrow;
+pub mod image_meta_data;
+pub mod repo_stats;
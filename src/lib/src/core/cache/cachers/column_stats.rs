@@ -0,0 +1,152 @@
+//! column_stats computes the row count and per column stats of every tabular file in a commit
+
+use std::path::{Path, PathBuf};
+
+use rocksdb::{DBWithThreadMode, MultiThreaded};
+
+use crate::api;
+use crate::constants::{CACHE_DIR, COLUMN_STATS_DIR, HISTORY_DIR};
+use crate::core::cache::Cacher;
+use crate::core::db::{self, str_json_db};
+use crate::core::df::tabular;
+use crate::error::OxenError;
+use crate::model::{Commit, CommitEntry, LocalRepository, TabularStats};
+use crate::opts::DFOpts;
+use crate::util;
+
+/// In the cache dir of the commit, next to the status of the cachers
+fn stats_db_path(repo: &LocalRepository, commit_id: &str) -> PathBuf {
+    util::fs::oxen_hidden_dir(&repo.path)
+        .join(HISTORY_DIR)
+        .join(commit_id)
+        .join(CACHE_DIR)
+        .join(COLUMN_STATS_DIR)
+}
+
+fn open_read_only(
+    repo: &LocalRepository,
+    commit_id: &str,
+) -> Result<Option<DBWithThreadMode<MultiThreaded>>, OxenError> {
+    let db_path = stats_db_path(repo, commit_id);
+    if !db_path.exists() {
+        return Ok(None);
+    }
+    let opts = db::opts::default();
+    let db = DBWithThreadMode::open_for_read_only(&opts, dunce::simplified(&db_path), false)?;
+    Ok(Some(db))
+}
+
+pub struct ColumnStatsCacher;
+
+impl Cacher for ColumnStatsCacher {
+    fn name(&self) -> &str {
+        "COLUMN_STATS"
+    }
+
+    fn applies_to(&self, entry: &CommitEntry) -> bool {
+        util::fs::is_tabular(&entry.path)
+    }
+
+    fn compute(
+        &self,
+        repo: &LocalRepository,
+        commit: &Commit,
+        entries: &[CommitEntry],
+    ) -> Result<Vec<(PathBuf, OxenError)>, OxenError> {
+        log::debug!("computing column stats {} -> {}", commit.id, commit.message);
+        let db = open(repo, &commit.id)?;
+        let mut failures: Vec<(PathBuf, OxenError)> = vec![];
+        for entry in entries {
+            match compute_entry(repo, entry) {
+                Ok(stats) => {
+                    str_json_db::put(&db, entry.path.to_string_lossy(), &stats)?;
+                }
+                Err(err) => {
+                    log::warn!("Could not compute column stats for {:?}: {err}", entry.path);
+                    failures.push((entry.path.to_owned(), err));
+                }
+            }
+        }
+        Ok(failures)
+    }
+
+    fn carry_over(
+        &self,
+        repo: &LocalRepository,
+        parent: &Commit,
+        commit: &Commit,
+        entries: &[CommitEntry],
+    ) -> Result<Vec<CommitEntry>, OxenError> {
+        let parent_db = match open_read_only(repo, &parent.id)? {
+            Some(db) => db,
+            None => {
+                log::warn!("Column stats of {} are missing, computing them", parent.id);
+                return Ok(entries.to_vec());
+            }
+        };
+        let db = open(repo, &commit.id)?;
+        let mut missing: Vec<CommitEntry> = vec![];
+        for entry in entries {
+            let key = entry.path.to_string_lossy();
+            let stats: Option<TabularStats> = str_json_db::get(&parent_db, &key)?;
+            match stats {
+                Some(stats) => str_json_db::put(&db, &key, &stats)?,
+                None => {
+                    log::warn!(
+                        "Column stats of {:?} are missing on {}, computing them",
+                        entry.path,
+                        parent.id
+                    );
+                    missing.push(entry.to_owned());
+                }
+            }
+        }
+        Ok(missing)
+    }
+}
+
+fn open(
+    repo: &LocalRepository,
+    commit_id: &str,
+) -> Result<DBWithThreadMode<MultiThreaded>, OxenError> {
+    let db_path = stats_db_path(repo, commit_id);
+    let opts = db::opts::default();
+    let db = DBWithThreadMode::open(&opts, dunce::simplified(&db_path))?;
+    Ok(db)
+}
+
+fn compute_entry(repo: &LocalRepository, entry: &CommitEntry) -> Result<TabularStats, OxenError> {
+    let version_path = util::fs::version_path(repo, entry);
+    let mut opts = DFOpts::empty();
+    // The schema and its dialect are saved on the commit that last changed the file
+    opts.dialect = api::local::schemas::get_csv_dialect(repo, Some(&entry.commit_id), &entry.path)?;
+    let df = tabular::read_df(&version_path, opts)?;
+    Ok(TabularStats::from_df(&entry.path, &df))
+}
+
+/// The stats of the tabular file at `path`, None if the file is not tabular or the cacher has not run
+pub fn get_stats(
+    repo: &LocalRepository,
+    commit: &Commit,
+    path: impl AsRef<Path>,
+) -> Result<Option<TabularStats>, OxenError> {
+    match open_read_only(repo, &commit.id)? {
+        Some(db) => {
+            let key = path.as_ref().to_string_lossy();
+            str_json_db::get(&db, key)
+        }
+        None => Ok(None),
+    }
+}
+
+/// The stats of every tabular file in the commit, sorted by path
+pub fn list_stats(repo: &LocalRepository, commit: &Commit) -> Result<Vec<TabularStats>, OxenError> {
+    match open_read_only(repo, &commit.id)? {
+        Some(db) => {
+            let mut stats = str_json_db::list_vals::<MultiThreaded, TabularStats>(&db)?;
+            stats.sort_by(|a, b| a.path.cmp(&b.path));
+            Ok(stats)
+        }
+        None => Ok(vec![]),
+    }
+}
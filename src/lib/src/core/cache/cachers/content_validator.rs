@@ -1,10 +1,31 @@
 //! entry_hash_cacher goes through the commit entry list and pre-computes the hash to verify everything is synced
 
+use std::path::PathBuf;
+
+use crate::core::cache::Cacher;
 use crate::core::index::{commit_validator, CommitEntryReader};
 use crate::error::OxenError;
-use crate::model::{Commit, LocalRepository, NewCommit};
+use crate::model::{Commit, CommitEntry, LocalRepository, NewCommit};
 use crate::util;
 
+pub struct ContentValidator;
+
+impl Cacher for ContentValidator {
+    fn name(&self) -> &str {
+        "COMMIT_CONTENT_IS_VALID"
+    }
+
+    fn compute(
+        &self,
+        repo: &LocalRepository,
+        commit: &Commit,
+        _entries: &[CommitEntry],
+    ) -> Result<Vec<(PathBuf, OxenError)>, OxenError> {
+        compute(repo, commit)?;
+        Ok(vec![])
+    }
+}
+
 pub fn compute(repo: &LocalRepository, commit: &Commit) -> Result<(), OxenError> {
     log::debug!("Running compute_and_write_hash");
 
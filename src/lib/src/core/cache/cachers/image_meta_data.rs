@@ -0,0 +1,306 @@
+//! image_meta_data records the dimensions, format and EXIF orientation of every image in a commit
+//! and generates a small thumbnail for it. Both are saved by content hash, so they are shared
+//! between all the commits and paths an image is in.
+
+use image::imageops::FilterType;
+use image::DynamicImage;
+use std::io::Cursor;
+use std::path::PathBuf;
+
+use crate::constants::{CACHE_DIR, IMAGES_DIR};
+use crate::core::cache::Cacher;
+use crate::error::OxenError;
+use crate::model::{Commit, CommitEntry, ImageMetaData, LocalRepository};
+use crate::util;
+
+/// Thumbnails fit in a square of this many pixels
+pub const THUMBNAIL_SIZE: u32 = 256;
+
+const META_DATA_FILE: &str = "meta_data.json";
+const THUMBNAIL_FILE: &str = "thumbnail.jpg";
+
+const EXIF_ORIENTATION_TAG: u16 = 0x0112;
+
+fn image_cache_dir(repo: &LocalRepository, hash: &str) -> PathBuf {
+    let (prefix, suffix) = hash.split_at(hash.len().min(2));
+    util::fs::oxen_hidden_dir(&repo.path)
+        .join(CACHE_DIR)
+        .join(IMAGES_DIR)
+        .join(prefix)
+        .join(suffix)
+}
+
+pub struct ImageMetaDataCacher;
+
+impl Cacher for ImageMetaDataCacher {
+    fn name(&self) -> &str {
+        "IMAGE_META_DATA"
+    }
+
+    fn applies_to(&self, entry: &CommitEntry) -> bool {
+        util::fs::is_image(&entry.path)
+    }
+
+    fn compute(
+        &self,
+        repo: &LocalRepository,
+        commit: &Commit,
+        entries: &[CommitEntry],
+    ) -> Result<Vec<(PathBuf, OxenError)>, OxenError> {
+        log::debug!(
+            "computing image meta data {} -> {}",
+            commit.id,
+            commit.message
+        );
+        let mut failures: Vec<(PathBuf, OxenError)> = vec![];
+        for entry in entries {
+            if let Err(err) = compute_entry(repo, entry) {
+                log::warn!(
+                    "Could not compute image meta data for {:?}: {err}",
+                    entry.path
+                );
+                failures.push((entry.path.to_owned(), err));
+            }
+        }
+        Ok(failures)
+    }
+}
+
+fn compute_entry(repo: &LocalRepository, entry: &CommitEntry) -> Result<(), OxenError> {
+    let cache_dir = image_cache_dir(repo, &entry.hash);
+    if cache_dir.join(META_DATA_FILE).exists() && cache_dir.join(THUMBNAIL_FILE).exists() {
+        // Another path or commit had the same content
+        return Ok(());
+    }
+
+    let version_path = util::fs::version_path(repo, entry);
+    let bytes = std::fs::read(&version_path)?;
+    let reader = image::io::Reader::new(Cursor::new(&bytes)).with_guessed_format()?;
+    let format = match reader.format() {
+        Some(format) => format!("{format:?}").to_lowercase(),
+        None => String::from("unknown"),
+    };
+    let img = reader.decode()?;
+    let orientation = exif_orientation(&bytes);
+    let meta_data = ImageMetaData {
+        format,
+        width: img.width(),
+        height: img.height(),
+        channels: img.color().channel_count(),
+        orientation,
+    };
+
+    std::fs::create_dir_all(&cache_dir)?;
+    let thumbnail = img.resize(THUMBNAIL_SIZE, THUMBNAIL_SIZE, FilterType::Triangle);
+    let thumbnail = apply_orientation(thumbnail, orientation);
+    // JPEG has no alpha channel
+    DynamicImage::ImageRgb8(thumbnail.to_rgb8()).save(cache_dir.join(THUMBNAIL_FILE))?;
+    util::fs::write_to_path(
+        &cache_dir.join(META_DATA_FILE),
+        &serde_json::to_string(&meta_data)?,
+    )?;
+    Ok(())
+}
+
+/// Rotate and flip the image so that it displays upright
+fn apply_orientation(img: DynamicImage, orientation: Option<u16>) -> DynamicImage {
+    match orientation {
+        Some(2) => img.fliph(),
+        Some(3) => img.rotate180(),
+        Some(4) => img.flipv(),
+        Some(5) => img.rotate90().fliph(),
+        Some(6) => img.rotate90(),
+        Some(7) => img.rotate270().fliph(),
+        Some(8) => img.rotate270(),
+        _ => img,
+    }
+}
+
+/// Find the EXIF orientation in the APP1 segment of a JPEG or the eXIf chunk of a PNG
+fn exif_orientation(bytes: &[u8]) -> Option<u16> {
+    if bytes.starts_with(&[0xFF, 0xD8]) {
+        let mut i = 2;
+        while i + 4 <= bytes.len() && bytes[i] == 0xFF {
+            let marker = bytes[i + 1];
+            // End of image or start of the compressed data, the meta data comes before it
+            if marker == 0xD9 || marker == 0xDA {
+                return None;
+            }
+            let len = u16::from_be_bytes([bytes[i + 2], bytes[i + 3]]) as usize;
+            let segment = bytes.get(i + 4..i + 2 + len)?;
+            if marker == 0xE1 && segment.starts_with(b"Exif\0\0") {
+                return tiff_orientation(&segment[6..]);
+            }
+            i += 2 + len;
+        }
+    } else if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        let mut i = 8;
+        while i + 8 <= bytes.len() {
+            let len = u32::from_be_bytes(bytes[i..i + 4].try_into().ok()?) as usize;
+            let chunk_type = &bytes[i + 4..i + 8];
+            let data = bytes.get(i + 8..i + 8 + len)?;
+            if chunk_type == b"eXIf" {
+                return tiff_orientation(data);
+            }
+            if chunk_type == b"IDAT" {
+                return None;
+            }
+            // length, type, data and crc
+            i += 12 + len;
+        }
+    }
+    None
+}
+
+/// Look up the orientation tag in the first IFD of TIFF formatted EXIF data
+fn tiff_orientation(tiff: &[u8]) -> Option<u16> {
+    let little_endian = match tiff.get(0..2)? {
+        b"II" => true,
+        b"MM" => false,
+        _ => return None,
+    };
+    let read_u16 = |offset: usize| -> Option<u16> {
+        let bytes: [u8; 2] = tiff.get(offset..offset + 2)?.try_into().ok()?;
+        Some(if little_endian {
+            u16::from_le_bytes(bytes)
+        } else {
+            u16::from_be_bytes(bytes)
+        })
+    };
+    let read_u32 = |offset: usize| -> Option<u32> {
+        let bytes: [u8; 4] = tiff.get(offset..offset + 4)?.try_into().ok()?;
+        Some(if little_endian {
+            u32::from_le_bytes(bytes)
+        } else {
+            u32::from_be_bytes(bytes)
+        })
+    };
+
+    let ifd = read_u32(4)? as usize;
+    let num_entries = read_u16(ifd)? as usize;
+    for n in 0..num_entries {
+        let entry = ifd + 2 + n * 12;
+        if read_u16(entry)? == EXIF_ORIENTATION_TAG {
+            // SHORT values are stored in the first bytes of the value field
+            return read_u16(entry + 8);
+        }
+    }
+    None
+}
+
+/// The meta data of the image, None if it is not an image or the cacher has not run on it yet
+pub fn get_meta_data(
+    repo: &LocalRepository,
+    entry: &CommitEntry,
+) -> Result<Option<ImageMetaData>, OxenError> {
+    let path = image_cache_dir(repo, &entry.hash).join(META_DATA_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = util::fs::read_from_path(&path)?;
+    Ok(Some(serde_json::from_str(&contents)?))
+}
+
+/// Path to the JPEG thumbnail of the image, None if the cacher has not generated it yet
+pub fn get_thumbnail_path(repo: &LocalRepository, entry: &CommitEntry) -> Option<PathBuf> {
+    let path = image_cache_dir(repo, &entry.hash).join(THUMBNAIL_FILE);
+    if path.exists() {
+        Some(path)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::command;
+    use crate::core::cache::cachers::image_meta_data::{
+        exif_orientation, get_meta_data, get_thumbnail_path, ImageMetaDataCacher, THUMBNAIL_SIZE,
+    };
+    use crate::core::cache::Cacher;
+    use crate::core::index::CommitEntryReader;
+    use crate::error::OxenError;
+    use crate::test;
+    use crate::util;
+
+    /// A JPEG header with an APP1 segment holding a single orientation entry
+    fn jpeg_with_orientation(little_endian: bool, orientation: u16) -> Vec<u8> {
+        let mut tiff: Vec<u8> = vec![];
+        if little_endian {
+            tiff.extend_from_slice(b"II");
+            tiff.extend_from_slice(&42u16.to_le_bytes());
+            tiff.extend_from_slice(&8u32.to_le_bytes());
+            tiff.extend_from_slice(&1u16.to_le_bytes());
+            tiff.extend_from_slice(&0x0112u16.to_le_bytes());
+            tiff.extend_from_slice(&3u16.to_le_bytes());
+            tiff.extend_from_slice(&1u32.to_le_bytes());
+            tiff.extend_from_slice(&orientation.to_le_bytes());
+        } else {
+            tiff.extend_from_slice(b"MM");
+            tiff.extend_from_slice(&42u16.to_be_bytes());
+            tiff.extend_from_slice(&8u32.to_be_bytes());
+            tiff.extend_from_slice(&1u16.to_be_bytes());
+            tiff.extend_from_slice(&0x0112u16.to_be_bytes());
+            tiff.extend_from_slice(&3u16.to_be_bytes());
+            tiff.extend_from_slice(&1u32.to_be_bytes());
+            tiff.extend_from_slice(&orientation.to_be_bytes());
+        }
+        tiff.extend_from_slice(&[0, 0]);
+
+        let mut bytes: Vec<u8> = vec![0xFF, 0xD8, 0xFF, 0xE1];
+        let len = (2 + 6 + tiff.len()) as u16;
+        bytes.extend_from_slice(&len.to_be_bytes());
+        bytes.extend_from_slice(b"Exif\0\0");
+        bytes.extend_from_slice(&tiff);
+        bytes.extend_from_slice(&[0xFF, 0xD9]);
+        bytes
+    }
+
+    #[test]
+    fn test_exif_orientation_in_jpeg() {
+        assert_eq!(exif_orientation(&jpeg_with_orientation(true, 6)), Some(6));
+        assert_eq!(exif_orientation(&jpeg_with_orientation(false, 8)), Some(8));
+    }
+
+    #[test]
+    fn test_exif_orientation_missing() {
+        assert_eq!(exif_orientation(&[0xFF, 0xD8, 0xFF, 0xD9]), None);
+        assert_eq!(exif_orientation(b"not an image"), None);
+    }
+
+    #[test]
+    fn test_image_meta_data_of_a_committed_jpeg() -> Result<(), OxenError> {
+        test::run_empty_local_repo_test(|repo| {
+            let image_path = repo.path.join("dog_1.jpg");
+            util::fs::copy(test::test_jpeg_file_with_name("dog_1.jpg"), &image_path)?;
+            command::add(&repo, &image_path)?;
+            let commit = command::commit(&repo, "Adding a dog")?;
+            let entry = CommitEntryReader::new(&repo, &commit)?
+                .get_entry(Path::new("dog_1.jpg"))?
+                .unwrap();
+
+            let failures = ImageMetaDataCacher.compute(&repo, &commit, &[entry.to_owned()])?;
+            assert!(failures.is_empty());
+
+            let meta_data = get_meta_data(&repo, &entry)?.unwrap();
+            assert_eq!(meta_data.format, "jpeg");
+            assert_eq!((meta_data.width, meta_data.height), (381, 499));
+            assert_eq!(meta_data.channels, 3);
+            assert_eq!(meta_data.orientation, None);
+
+            // The thumbnail is a jpeg that fits in the square and keeps the aspect ratio
+            let thumbnail_path = get_thumbnail_path(&repo, &entry).unwrap();
+            let reader = image::io::Reader::open(&thumbnail_path)?.with_guessed_format()?;
+            assert_eq!(reader.format(), Some(image::ImageFormat::Jpeg));
+            let thumbnail = reader.decode()?;
+            assert_eq!(thumbnail.height(), THUMBNAIL_SIZE);
+            assert!(thumbnail.width() < THUMBNAIL_SIZE);
+            let aspect = thumbnail.width() as f64 / thumbnail.height() as f64;
+            assert!((aspect - 381.0 / 499.0).abs() < 0.01);
+
+            Ok(())
+        })
+    }
+}
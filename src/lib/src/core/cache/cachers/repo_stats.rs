@@ -0,0 +1,83 @@
+//! repo_stats records the total size, file count, size per directory and rows per tabular file of a commit
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use crate::constants::{CACHE_DIR, HISTORY_DIR, REPO_STATS_FILE};
+use crate::core::cache::cachers::column_stats;
+use crate::core::cache::Cacher;
+use crate::core::index::CommitEntryReader;
+use crate::error::OxenError;
+use crate::model::{Commit, CommitEntry, FileStats, LocalRepository, RepoSizeStats};
+use crate::util;
+
+/// In the cache dir of the commit, next to the status of the cachers
+fn stats_file_path(repo: &LocalRepository, commit_id: &str) -> PathBuf {
+    util::fs::oxen_hidden_dir(&repo.path)
+        .join(HISTORY_DIR)
+        .join(commit_id)
+        .join(CACHE_DIR)
+        .join(REPO_STATS_FILE)
+}
+
+pub struct RepoStatsCacher;
+
+impl Cacher for RepoStatsCacher {
+    fn name(&self) -> &str {
+        "REPO_STATS"
+    }
+
+    /// Row counts come from the column stats
+    fn dependencies(&self) -> Vec<&str> {
+        vec!["COLUMN_STATS"]
+    }
+
+    fn compute(
+        &self,
+        repo: &LocalRepository,
+        commit: &Commit,
+        _entries: &[CommitEntry],
+    ) -> Result<Vec<(PathBuf, OxenError)>, OxenError> {
+        log::debug!("computing repo stats {} -> {}", commit.id, commit.message);
+        let stats = compute_stats(repo, commit)?;
+        util::fs::write_to_path(
+            &stats_file_path(repo, &commit.id),
+            &serde_json::to_string(&stats)?,
+        )?;
+        Ok(vec![])
+    }
+}
+
+/// Compute the stats from the entries of the commit. Tabular files only have a row count if
+/// their column stats were already computed.
+pub fn compute_stats(repo: &LocalRepository, commit: &Commit) -> Result<RepoSizeStats, OxenError> {
+    let num_rows: HashMap<PathBuf, usize> = column_stats::list_stats(repo, commit)?
+        .into_iter()
+        .map(|stats| (stats.path, stats.num_rows))
+        .collect();
+
+    let entries = CommitEntryReader::new(repo, commit)?.list_entries()?;
+    let files: Vec<FileStats> = entries
+        .iter()
+        .map(|entry| FileStats {
+            path: entry.path.to_owned(),
+            data_type: util::fs::file_datatype(&util::fs::version_path(repo, entry)),
+            data_size: entry.num_bytes,
+            num_rows: num_rows.get(&entry.path).cloned(),
+        })
+        .collect();
+    Ok(RepoSizeStats::from_files(files))
+}
+
+/// The cached stats of the commit, None if the cacher has not run on it
+pub fn get_stats(
+    repo: &LocalRepository,
+    commit: &Commit,
+) -> Result<Option<RepoSizeStats>, OxenError> {
+    let path = stats_file_path(repo, &commit.id);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let contents = util::fs::read_from_path(&path)?;
+    Ok(Some(serde_json::from_str(&contents)?))
+}
@@ -1,19 +1,22 @@
 //! This module goes through a repo and caches values on commits that will never change
 //! but are expensive to compute at runtime
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
+use crate::api;
 use crate::constants::{CACHE_DIR, HISTORY_DIR};
 use crate::core::db::{self, str_json_db};
+use crate::core::index::CommitEntryReader;
 use crate::error::OxenError;
-use crate::model::{Commit, LocalRepository};
+use crate::model::{Commit, CommitEntry, LocalRepository};
 use crate::util;
 
-use super::cachers::content_validator;
+use super::cachers::{column_stats, content_validator, image_meta_data, repo_stats};
+use super::Cacher;
 use lazy_static::lazy_static;
 use rocksdb::{DBWithThreadMode, MultiThreaded};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 #[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
 pub enum CacherStatusType {
@@ -26,6 +29,20 @@ pub enum CacherStatusType {
 pub struct CacherStatus {
     pub status: CacherStatusType,
     pub status_message: String,
+    /// Name of the cacher, only empty on statuses saved before cachers were versioned
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub version: u32,
+    /// The file the status is for, None for the status of the cacher on the whole commit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<PathBuf>,
+    /// Hash of the file the value was computed from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    /// Version of each dependency the cacher ran after, it runs again when one of them changes
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub dependencies: HashMap<String, u32>,
 }
 
 impl CacherStatus {
@@ -33,13 +50,18 @@ impl CacherStatus {
         CacherStatus {
             status: CacherStatusType::Pending,
             status_message: String::from(""),
+            name: String::from(""),
+            version: 0,
+            path: None,
+            hash: None,
+            dependencies: HashMap::new(),
         }
     }
 
     pub fn success() -> CacherStatus {
         CacherStatus {
             status: CacherStatusType::Success,
-            status_message: String::from(""),
+            ..CacherStatus::pending()
         }
     }
 
@@ -47,35 +69,65 @@ impl CacherStatus {
         CacherStatus {
             status: CacherStatusType::Failed,
             status_message: String::from(msg),
+            ..CacherStatus::pending()
         }
     }
-}
 
-type CommitCacher = fn(&LocalRepository, &Commit) -> Result<(), OxenError>;
+    fn for_cacher(mut self, cacher: &dyn Cacher) -> CacherStatus {
+        self.name = cacher.name().to_string();
+        self.version = cacher.version();
+        self
+    }
+
+    fn with_dependencies(mut self, dependencies: &HashMap<String, u32>) -> CacherStatus {
+        self.dependencies = dependencies.to_owned();
+        self
+    }
+
+    fn for_entry(mut self, entry: &CommitEntry) -> CacherStatus {
+        self.path = Some(entry.path.to_owned());
+        self.hash = Some(entry.hash.to_owned());
+        self
+    }
+
+    fn key(&self) -> String {
+        match &self.path {
+            Some(path) => entry_status_key(&self.name, path),
+            None => self.name.to_owned(),
+        }
+    }
+}
 
 lazy_static! {
-    /// These are all the cachers we are going to run in `run_all`
-    static ref CACHERS: HashMap<String, CommitCacher> = {
-        let mut cachers = HashMap::new();
-        cachers.insert(String::from("COMMIT_CONTENT_IS_VALID"), content_validator::compute as CommitCacher);
-        // cachers.insert(String::from("ARROW_CONVERSION"), convert_to_arrow::convert_to_arrow as CommitCacher);
-        cachers
-    };
+    /// These are all the cachers we are going to run in `run_all`, in order of their dependencies
+    static ref CACHERS: Vec<Box<dyn Cacher>> = vec![
+        Box::new(content_validator::ContentValidator),
+        Box::new(column_stats::ColumnStatsCacher),
+        Box::new(image_meta_data::ImageMetaDataCacher),
+        Box::new(repo_stats::RepoStatsCacher),
+    ];
 }
 
-fn cached_status_db_path(repo: &LocalRepository, commit: &Commit) -> PathBuf {
+fn cached_status_db_path(repo: &LocalRepository, commit_id: &str) -> PathBuf {
     util::fs::oxen_hidden_dir(&repo.path)
         .join(HISTORY_DIR)
-        .join(&commit.id)
+        .join(commit_id)
         .join(CACHE_DIR)
 }
 
-/// Pick most appropriate status to return given the status's in the db
+fn entry_status_key(name: &str, path: &Path) -> String {
+    format!("{name}/{}", path.to_string_lossy())
+}
+
+/// Pick most appropriate status to return given the status's of the cachers on the whole commit
 pub fn get_status(
     repo: &LocalRepository,
     commit: &Commit,
 ) -> Result<Option<CacherStatusType>, OxenError> {
-    let vals = get_all_statuses(repo, commit)?;
+    let vals: Vec<CacherStatus> = get_all_statuses(repo, commit)?
+        .into_iter()
+        .filter(|v| v.path.is_none())
+        .collect();
     if vals.iter().any(|v| CacherStatusType::Pending == v.status) {
         return Ok(Some(CacherStatusType::Pending));
     }
@@ -90,29 +142,24 @@ pub fn get_status(
         .map(|v| v.status))
 }
 
-/// Return all the statuses from cacher processes that were run
+/// Return the cachers that failed on the whole commit
 pub fn get_failures(
     repo: &LocalRepository,
     commit: &Commit,
 ) -> Result<Vec<CacherStatus>, OxenError> {
-    let db_path = cached_status_db_path(repo, commit);
-    let opts = db::opts::default();
-    let db: DBWithThreadMode<MultiThreaded> =
-        DBWithThreadMode::open(&opts, dunce::simplified(&db_path))?;
-
-    let vals = str_json_db::list_vals::<MultiThreaded, CacherStatus>(&db)?
+    let vals = get_all_statuses(repo, commit)?
         .into_iter()
-        .filter(|v| v.status == CacherStatusType::Failed)
+        .filter(|v| v.path.is_none() && v.status == CacherStatusType::Failed)
         .collect();
     Ok(vals)
 }
 
-/// Return all the statuses from cacher processes that were run
+/// Return the status of every cacher on the commit, followed by the status of each file it computed a value for
 pub fn get_all_statuses(
     repo: &LocalRepository,
     commit: &Commit,
 ) -> Result<Vec<CacherStatus>, OxenError> {
-    let db_path = cached_status_db_path(repo, commit);
+    let db_path = cached_status_db_path(repo, &commit.id);
     // Check if db path exists
     if !db_path.exists() {
         return Ok(vec![]);
@@ -121,7 +168,19 @@ pub fn get_all_statuses(
     let db = DBWithThreadMode::open(&opts, dunce::simplified(&db_path));
     match db {
         Ok(db) => {
-            let vals = str_json_db::list_vals::<MultiThreaded, CacherStatus>(&db)?;
+            let mut vals: Vec<CacherStatus> =
+                str_json_db::list::<MultiThreaded, CacherStatus>(&db)?
+                    .into_iter()
+                    .map(|(key, mut val)| {
+                        if val.name.is_empty() {
+                            val.name = key;
+                        }
+                        val
+                    })
+                    .collect();
+            vals.sort_by(|a, b| {
+                (a.path.is_some(), &a.name, &a.path).cmp(&(b.path.is_some(), &b.name, &b.path))
+            });
             Ok(vals)
         }
         Err(_) => {
@@ -132,36 +191,156 @@ pub fn get_all_statuses(
     }
 }
 
+/// Order the cachers so that each one comes after the cachers it depends on
+pub fn sort_by_dependencies(cachers: &[Box<dyn Cacher>]) -> Result<Vec<&dyn Cacher>, OxenError> {
+    let names: HashSet<&str> = cachers.iter().map(|c| c.name()).collect();
+    for cacher in cachers.iter() {
+        for dependency in cacher.dependencies() {
+            if !names.contains(dependency) {
+                return Err(OxenError::basic_str(format!(
+                    "Cacher {} depends on unknown cacher {dependency}",
+                    cacher.name()
+                )));
+            }
+        }
+    }
+
+    let mut sorted: Vec<&dyn Cacher> = vec![];
+    let mut done: HashSet<&str> = HashSet::new();
+    while sorted.len() < cachers.len() {
+        let ready: Vec<&dyn Cacher> = cachers
+            .iter()
+            .map(|c| c.as_ref())
+            .filter(|c| !done.contains(c.name()))
+            .filter(|c| c.dependencies().iter().all(|d| done.contains(d)))
+            .collect();
+        if ready.is_empty() {
+            return Err(OxenError::basic_str("Cachers have circular dependencies"));
+        }
+        for cacher in ready {
+            done.insert(cacher.name());
+            sorted.push(cacher);
+        }
+    }
+    Ok(sorted)
+}
+
+/// The version of the cacher named `name`, looked up in `cachers` and then in the ones `run_all` runs
+fn cacher_version(cachers: &[&dyn Cacher], name: &str) -> Option<u32> {
+    match cachers.iter().find(|c| c.name() == name) {
+        Some(cacher) => Some(cacher.version()),
+        None => CACHERS
+            .iter()
+            .find(|c| c.name() == name)
+            .map(|c| c.version()),
+    }
+}
+
 /// Run all the cachers and update their status's as you go
 pub fn run_all(repo: &LocalRepository, commit: &Commit) -> Result<(), OxenError> {
-    // Create kvdb of NAME -> STATUS
-    let db_path = cached_status_db_path(repo, commit);
+    let cachers = sort_by_dependencies(&CACHERS)?;
+    run(repo, commit, &cachers)
+}
+
+/// Run the cachers in order, skipping the ones that already succeeded on the commit with the same version
+/// after the same versions of their dependencies. Files that did not change since a parent commit the cacher succeeded on are carried over instead of computed.
+pub fn run(
+    repo: &LocalRepository,
+    commit: &Commit,
+    cachers: &[&dyn Cacher],
+) -> Result<(), OxenError> {
+    // Create kvdb of NAME -> STATUS and NAME/PATH -> STATUS
+    let db_path = cached_status_db_path(repo, &commit.id);
     let opts = db::opts::default();
     let db: DBWithThreadMode<MultiThreaded> =
         DBWithThreadMode::open(&opts, dunce::simplified(&db_path))?;
 
-    for (name, cacher) in CACHERS.iter() {
+    let mut parents: Vec<(Commit, DBWithThreadMode<MultiThreaded>)> = vec![];
+    for parent_id in commit.parent_ids.iter() {
+        let parent_db_path = cached_status_db_path(repo, parent_id);
+        if !parent_db_path.exists() {
+            continue;
+        }
+        if let Some(parent) = api::local::commits::get_by_id(repo, parent_id)? {
+            let parent_db = DBWithThreadMode::open_for_read_only(
+                &opts,
+                dunce::simplified(&parent_db_path),
+                false,
+            )?;
+            parents.push((parent, parent_db));
+        }
+    }
+
+    let mut entries: Option<Vec<CommitEntry>> = None;
+    for cacher in cachers.iter() {
+        let name = cacher.name();
+        let dependencies: HashMap<String, u32> = cacher
+            .dependencies()
+            .into_iter()
+            .filter_map(|d| cacher_version(cachers, d).map(|v| (d.to_string(), v)))
+            .collect();
+
         // Skip ones that are already cached successfully
         if let Some(val) = str_json_db::get::<MultiThreaded, &str, CacherStatus>(&db, name)? {
-            if CacherStatusType::Success == val.status {
+            if CacherStatusType::Success == val.status
+                && val.version == cacher.version()
+                && val.dependencies == dependencies
+            {
                 continue;
             }
         }
 
+        // Dependencies have to have succeeded with their current version
+        let failed_dependency = cacher.dependencies().into_iter().find(|dependency| {
+            !matches!(
+                str_json_db::get::<MultiThreaded, &str, CacherStatus>(&db, *dependency),
+                Ok(Some(status)) if status.status == CacherStatusType::Success
+                    && dependencies.get(*dependency).map_or(true, |v| *v == status.version)
+            )
+        });
+        if let Some(dependency) = failed_dependency {
+            let err = format!("Err: depends on {dependency} which did not succeed");
+            log::error!("{name} {err}");
+            let status_failed = CacherStatus::failed(&err)
+                .for_cacher(*cacher)
+                .with_dependencies(&dependencies);
+            str_json_db::put(&db, name, &status_failed)?;
+            continue;
+        }
+
         // set as pending
-        let pending_status = CacherStatus::pending();
+        let pending_status = CacherStatus::pending()
+            .for_cacher(*cacher)
+            .with_dependencies(&dependencies);
         str_json_db::put(&db, name, &pending_status)?;
 
+        if entries.is_none() {
+            entries = Some(CommitEntryReader::new(repo, commit)?.list_entries()?);
+        }
+        let applicable: Vec<&CommitEntry> = entries
+            .iter()
+            .flatten()
+            .filter(|e| cacher.applies_to(e))
+            .collect();
+
         // run and set appropriate status
-        match cacher(repo, commit) {
-            Ok(_) => {
-                let status_success = CacherStatus::success();
+        match run_cacher(repo, commit, *cacher, &applicable, &parents, &db) {
+            Ok(num_failed) => {
+                let mut status_success = CacherStatus::success()
+                    .for_cacher(*cacher)
+                    .with_dependencies(&dependencies);
+                if num_failed > 0 {
+                    status_success.status_message =
+                        format!("{num_failed} of {} files failed", applicable.len());
+                }
                 str_json_db::put(&db, name, &status_success)?;
             }
             Err(err) => {
                 let err = format!("Err: {err}");
                 log::error!("{}", err);
-                let status_failed = CacherStatus::failed(&err);
+                let status_failed = CacherStatus::failed(&err)
+                    .for_cacher(*cacher)
+                    .with_dependencies(&dependencies);
                 str_json_db::put(&db, name, &status_failed)?;
             }
         }
@@ -169,3 +348,179 @@ pub fn run_all(repo: &LocalRepository, commit: &Commit) -> Result<(), OxenError>
 
     Ok(())
 }
+
+/// Returns the number of files that failed
+fn run_cacher(
+    repo: &LocalRepository,
+    commit: &Commit,
+    cacher: &dyn Cacher,
+    entries: &[&CommitEntry],
+    parents: &[(Commit, DBWithThreadMode<MultiThreaded>)],
+    db: &DBWithThreadMode<MultiThreaded>,
+) -> Result<usize, OxenError> {
+    // Find the parent each unchanged file was already computed on
+    let mut unchanged: HashMap<usize, Vec<CommitEntry>> = HashMap::new();
+    let mut changed: Vec<CommitEntry> = vec![];
+    for entry in entries {
+        let key = entry_status_key(cacher.name(), &entry.path);
+        let parent = parents.iter().position(|(_, parent_db)| {
+            matches!(
+                str_json_db::get::<MultiThreaded, &str, CacherStatus>(parent_db, key.as_str()),
+                Ok(Some(status)) if status.status == CacherStatusType::Success
+                    && status.version == cacher.version()
+                    && status.hash.as_ref() == Some(&entry.hash)
+            )
+        });
+        match parent {
+            Some(i) => unchanged.entry(i).or_default().push((*entry).to_owned()),
+            None => changed.push((*entry).to_owned()),
+        }
+    }
+
+    for (i, entries) in unchanged {
+        let (parent, _) = &parents[i];
+        log::debug!(
+            "{} carrying over {} files from {}",
+            cacher.name(),
+            entries.len(),
+            parent.id
+        );
+        let missing = cacher.carry_over(repo, parent, commit, &entries)?;
+        for entry in entries.iter().filter(|e| !missing.contains(e)) {
+            let status = CacherStatus::success().for_cacher(cacher).for_entry(entry);
+            str_json_db::put(db, status.key(), &status)?;
+        }
+        changed.extend(missing);
+    }
+
+    log::debug!("{} computing {} files", cacher.name(), changed.len());
+    let failures: HashMap<PathBuf, OxenError> = cacher
+        .compute(repo, commit, &changed)?
+        .into_iter()
+        .collect();
+    for entry in changed.iter() {
+        let status = match failures.get(&entry.path) {
+            Some(err) => CacherStatus::failed(&format!("Err: {err}")),
+            None => CacherStatus::success(),
+        };
+        let status = status.for_cacher(cacher).for_entry(entry);
+        str_json_db::put(db, status.key(), &status)?;
+    }
+
+    Ok(failures.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::api;
+    use crate::core::cache::commit_cacher::{self, sort_by_dependencies};
+    use crate::core::cache::Cacher;
+    use crate::error::OxenError;
+    use crate::model::{Commit, CommitEntry, LocalRepository};
+    use crate::test;
+
+    struct TestCacher {
+        name: &'static str,
+        version: u32,
+        dependencies: Vec<&'static str>,
+        runs: AtomicUsize,
+    }
+
+    impl Cacher for TestCacher {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn version(&self) -> u32 {
+            self.version
+        }
+
+        fn dependencies(&self) -> Vec<&str> {
+            self.dependencies.to_owned()
+        }
+
+        fn compute(
+            &self,
+            _repo: &LocalRepository,
+            _commit: &Commit,
+            _entries: &[CommitEntry],
+        ) -> Result<Vec<(PathBuf, OxenError)>, OxenError> {
+            self.runs.fetch_add(1, Ordering::SeqCst);
+            Ok(vec![])
+        }
+    }
+
+    fn test_cacher(
+        name: &'static str,
+        version: u32,
+        dependencies: Vec<&'static str>,
+    ) -> TestCacher {
+        TestCacher {
+            name,
+            version,
+            dependencies,
+            runs: AtomicUsize::new(0),
+        }
+    }
+
+    fn cacher(name: &'static str, dependencies: Vec<&'static str>) -> Box<dyn Cacher> {
+        Box::new(test_cacher(name, 1, dependencies))
+    }
+
+    #[test]
+    fn test_sort_cachers_by_dependencies() -> Result<(), OxenError> {
+        let cachers = vec![
+            cacher("THUMBNAILS", vec!["IMAGE_METADATA"]),
+            cacher("IMAGE_METADATA", vec![]),
+            cacher("REPO_STATS", vec!["THUMBNAILS", "IMAGE_METADATA"]),
+        ];
+        let names: Vec<&str> = sort_by_dependencies(&cachers)?
+            .iter()
+            .map(|c| c.name())
+            .collect();
+        assert_eq!(names, vec!["IMAGE_METADATA", "THUMBNAILS", "REPO_STATS"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort_cachers_rejects_cycles_and_unknown_dependencies() {
+        let cachers = vec![cacher("A", vec!["B"]), cacher("B", vec!["A"])];
+        assert!(sort_by_dependencies(&cachers).is_err());
+
+        let cachers = vec![cacher("A", vec!["MISSING"])];
+        assert!(sort_by_dependencies(&cachers).is_err());
+    }
+
+    #[test]
+    fn test_run_reruns_dependents_when_a_dependency_version_changes() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let commit = api::local::commits::head_commit(&repo)?;
+            let metadata = test_cacher("TEST_METADATA", 1, vec![]);
+            let thumbnails = test_cacher("TEST_THUMBNAILS", 1, vec!["TEST_METADATA"]);
+            commit_cacher::run(&repo, &commit, &[&metadata, &thumbnails])?;
+            assert_eq!(thumbnails.runs.load(Ordering::SeqCst), 1);
+
+            // Nothing changed, nothing runs again
+            commit_cacher::run(&repo, &commit, &[&metadata, &thumbnails])?;
+            assert_eq!(metadata.runs.load(Ordering::SeqCst), 1);
+            assert_eq!(thumbnails.runs.load(Ordering::SeqCst), 1);
+
+            // A new version of the dependency runs the dependent again
+            let metadata = test_cacher("TEST_METADATA", 2, vec![]);
+            commit_cacher::run(&repo, &commit, &[&metadata, &thumbnails])?;
+            assert_eq!(metadata.runs.load(Ordering::SeqCst), 1);
+            assert_eq!(thumbnails.runs.load(Ordering::SeqCst), 2);
+
+            // The dependency has to have succeeded with the version it is run with
+            let metadata = test_cacher("TEST_METADATA", 3, vec![]);
+            let thumbnails = test_cacher("TEST_THUMBNAILS", 2, vec!["TEST_METADATA"]);
+            commit_cacher::run(&repo, &commit, &[&thumbnails, &metadata])?;
+            assert_eq!(thumbnails.runs.load(Ordering::SeqCst), 0);
+
+            Ok(())
+        })
+    }
+}
@@ -1,6 +1,6 @@
 use crate::error::OxenError;
 
-use rocksdb::{DBWithThreadMode, IteratorMode, MultiThreaded};
+use rocksdb::{DBWithThreadMode, Direction, IteratorMode, MultiThreaded};
 use std::collections::HashMap;
 use std::mem;
 use std::str;
@@ -44,6 +44,24 @@ pub fn get_indices<S: AsRef<str>>(
     }
 }
 
+/// Delete every key that starts with `prefix`
+pub fn delete_prefix<S: AsRef<str>>(
+    db: &DBWithThreadMode<MultiThreaded>,
+    prefix: S,
+) -> Result<(), OxenError> {
+    let prefix = prefix.as_ref().as_bytes();
+    // Keys are sorted, so the matches are all together starting at the prefix
+    let iter = db.iterator(IteratorMode::From(prefix, Direction::Forward));
+    for item in iter {
+        let (key, _) = item?;
+        if !key.starts_with(prefix) {
+            break;
+        }
+        db.delete(&key)?;
+    }
+    Ok(())
+}
+
 pub fn list_indices(
     db: &DBWithThreadMode<MultiThreaded>,
 ) -> Result<Vec<(String, Vec<u32>)>, OxenError> {
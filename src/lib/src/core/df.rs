@@ -2,5 +2,7 @@
 //!
 
 pub mod agg;
+pub mod expr;
 pub mod filter;
+pub mod split;
 pub mod tabular;
@@ -1,18 +1,20 @@
 /// # Aggregations
 /// ('group_by_col') -> (list('col_1'), min('col_2'), n_unique('col_3'))
+/// ('label', 'split') -> (count('file') as n, mean(width * height) as area, quantile('score', 0.9))
+use crate::core::df::expr::{self, DFColumnExp};
 use crate::error::OxenError;
 
 use nom::{
-    bytes::complete::is_not,
-    bytes::complete::tag,
-    bytes::complete::take_while,
-    character::complete::char,
-    character::complete::one_of,
-    sequence::{delimited, separated_pair},
+    branch::alt,
+    bytes::complete::{tag, take_while1},
+    character::complete::{char, multispace0, multispace1, one_of},
+    combinator::{all_consuming, consumed, map, opt, peek},
+    multi::{separated_list0, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
     IResult,
 };
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DFAggFnType {
     List,
     Count,
@@ -21,14 +23,17 @@ pub enum DFAggFnType {
     Max,
     ArgMin,
     ArgMax,
+    Sum,
     Mean,
     Median,
+    Quantile,
     Std,
     Var,
     First,
     Last,
     Head,
     Tail,
+    ValueCounts,
     Unknown,
 }
 
@@ -42,197 +47,252 @@ impl DFAggFnType {
             "max" => DFAggFnType::Max,
             "arg_min" => DFAggFnType::ArgMin,
             "arg_max" => DFAggFnType::ArgMax,
+            "sum" => DFAggFnType::Sum,
             "mean" => DFAggFnType::Mean,
             "median" => DFAggFnType::Median,
+            "quantile" => DFAggFnType::Quantile,
             "std" => DFAggFnType::Std,
             "var" => DFAggFnType::Var,
             "first" => DFAggFnType::First,
             "last" => DFAggFnType::Last,
             "head" => DFAggFnType::Head,
             "tail" => DFAggFnType::Tail,
+            "value_counts" => DFAggFnType::ValueCounts,
             _ => DFAggFnType::Unknown,
         }
     }
+
+    /// Whether the aggregation returns several rows instead of one when there is no group by
+    pub fn returns_rows(&self) -> bool {
+        matches!(
+            self,
+            DFAggFnType::List | DFAggFnType::Head | DFAggFnType::Tail
+        )
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct DFAggFn {
     pub name: String,
-    pub args: Vec<String>,
+    // the column or expression to aggregate, followed by any literal params
+    // like the quantile in quantile('score', 0.9) or n in head('file', 3)
+    pub args: Vec<DFColumnExp>,
+    // name of the output column, from "as name" or else the call as written
+    pub alias: String,
 }
 
-#[derive(Clone, Debug)]
+impl DFAggFn {
+    pub fn fn_type(&self) -> DFAggFnType {
+        DFAggFnType::from_fn_name(&self.name)
+    }
+
+    /// The numeric param at `index` in the args, if it was given
+    pub fn param(&self, index: usize) -> Result<Option<f64>, OxenError> {
+        match self.args.get(index) {
+            Some(DFColumnExp::Int(val)) => Ok(Some(*val as f64)),
+            Some(DFColumnExp::Float(val)) => Ok(Some(*val)),
+            Some(DFColumnExp::Neg(exp)) => match **exp {
+                DFColumnExp::Int(val) => Ok(Some(-(val as f64))),
+                DFColumnExp::Float(val) => Ok(Some(-val)),
+                _ => Err(OxenError::parse_error(format!(
+                    "{}() expects a number, got {exp:?}",
+                    self.name
+                ))),
+            },
+            Some(arg) => Err(OxenError::parse_error(format!(
+                "{}() expects a number, got {arg:?}",
+                self.name
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// The number of rows param at `index` for head() and tail(), `default` if it was not given
+    pub fn num_rows_param(&self, index: usize, default: usize) -> Result<usize, OxenError> {
+        match self.param(index)? {
+            Some(n) if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+            Some(n) => Err(OxenError::parse_error(format!(
+                "{}() expects a whole number of rows, got {n}",
+                self.name
+            ))),
+            None => Ok(default),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
 pub struct DFAggregation {
     pub group_by: Vec<String>,
     pub agg: Vec<DFAggFn>,
 }
 
-fn is_whitespace(c: char) -> bool {
-    c == ' '
+fn is_fn_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
 }
 
-fn is_quote(c: char) -> bool {
-    c == '\'' || c == '"'
+fn quoted(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('\''), take_while1(|c| c != '\''), char('\'')),
+        delimited(char('"'), take_while1(|c| c != '"'), char('"')),
+    ))(input)
 }
 
-fn is_quote_or_whitespace(c: char) -> bool {
-    is_quote(c) || c == ' '
+fn group_by_col(input: &str) -> IResult<&str, String> {
+    map(
+        alt((quoted, take_while1(|c: char| is_fn_char(c) || c == '.'))),
+        String::from,
+    )(input)
 }
 
-fn is_comma_or_whitespace(c: char) -> bool {
-    c == ',' || c == ' '
+fn comma(input: &str) -> IResult<&str, char> {
+    delimited(multispace0, char(','), multispace0)(input)
 }
 
-fn is_open_paren(c: char) -> bool {
-    c == '('
+fn group_by(input: &str) -> IResult<&str, Vec<String>> {
+    delimited(
+        pair(char('('), multispace0),
+        separated_list0(comma, group_by_col),
+        pair(multispace0, char(')')),
+    )(input)
 }
 
-fn is_valid_fn_char(c: char) -> bool {
-    c.is_alphanumeric() || c == '_'
+// A quoted arg on its own is a column name, to stay compatible with ('col') style queries,
+// anything else is parsed as an expression where single quotes are string literals
+fn agg_arg(input: &str) -> IResult<&str, DFColumnExp> {
+    alt((
+        map(
+            terminated(quoted, peek(preceded(multispace0, one_of(",)")))),
+            |name| DFColumnExp::Col(name.to_string()),
+        ),
+        expr::column_exp,
+    ))(input)
 }
 
-fn contained_in_quotes(input: &str) -> Result<(&str, &str), OxenError> {
-    let result: IResult<&str, &str> = delimited(
-        take_while(is_quote_or_whitespace),
-        is_not("'\""),
-        one_of("'\""),
-    )(input);
-    match result {
-        Ok(result) => Ok(result),
-        _ => Err(OxenError::parse_error(input)),
-    }
+fn alias(input: &str) -> IResult<&str, String> {
+    preceded(
+        tuple((multispace1, tag("as"), multispace1)),
+        map(alt((quoted, take_while1(is_fn_char))), String::from),
+    )(input)
 }
 
-fn contained_in_parens(input: &str) -> Result<(&str, &str), OxenError> {
-    let result: IResult<&str, &str> = delimited(char('('), is_not(")"), char(')'))(input);
-    match result {
-        Ok(result) => Ok(result),
-        _ => Err(OxenError::parse_error(input)),
-    }
-}
+fn agg_fn(input: &str) -> IResult<&str, DFAggFn> {
+    let (input, name) = take_while1(is_fn_char)(input)?;
+    let (input, (text, args)) = delimited(
+        pair(char('('), multispace0),
+        consumed(separated_list1(comma, agg_arg)),
+        pair(multispace0, char(')')),
+    )(input)?;
+    let (input, alias) = opt(alias)(input)?;
 
-fn split_on_arrow(input: &str) -> Result<(&str, (&str, &str)), OxenError> {
-    let result: IResult<&str, (&str, &str)> = separated_pair(
-        take_while(is_whitespace),
-        tag("->"),
-        take_while(is_whitespace),
-    )(input);
-    match result {
-        Ok(result) => Ok(result),
-        _ => Err(OxenError::parse_error(input)),
-    }
+    let alias = alias.unwrap_or_else(|| match args.as_slice() {
+        [DFColumnExp::Col(col)] => format!("{name}('{col}')"),
+        _ => format!("{name}({text})"),
+    });
+    Ok((
+        input,
+        DFAggFn {
+            name: name.to_string(),
+            args,
+            alias,
+        },
+    ))
 }
 
-fn take_open_paren(input: &str) -> Result<(&str, &str), OxenError> {
-    let result: IResult<&str, &str> = take_while(is_open_paren)(input);
-    match result {
-        Ok(result) => Ok(result),
-        _ => Err(OxenError::parse_error(input)),
-    }
+fn agg_fns(input: &str) -> IResult<&str, Vec<DFAggFn>> {
+    delimited(
+        pair(char('('), multispace0),
+        separated_list1(comma, agg_fn),
+        pair(multispace0, char(')')),
+    )(input)
 }
 
-fn take_fn_name(input: &str) -> Result<(&str, &str), OxenError> {
-    let result: IResult<&str, &str> = take_while(is_valid_fn_char)(input);
-    match result {
-        Ok(result) => Ok(result),
-        _ => Err(OxenError::parse_error(input)),
-    }
-}
+/// Parse a query such as "('label') -> (count('file'), mean(width * height) as area)"
+/// Group by columns are optional, "() -> (sum('count'))" aggregates the whole data frame
+pub fn parse_query(input: &str) -> Result<DFAggregation, OxenError> {
+    log::debug!("GOT input: {}", input);
+    let result: IResult<&str, (Vec<String>, Vec<DFAggFn>)> = all_consuming(delimited(
+        multispace0,
+        separated_pair(
+            group_by,
+            delimited(multispace0, tag("->"), multispace0),
+            agg_fns,
+        ),
+        multispace0,
+    ))(input);
+
+    let (group_by, agg) = match result {
+        Ok((_, parsed)) => parsed,
+        Err(err) => {
+            log::debug!("Could not parse aggregation {input:?}: {err}");
+            return Err(OxenError::parse_error(input));
+        }
+    };
 
-fn take_comma(input: &str) -> Result<(&str, &str), OxenError> {
-    let result: IResult<&str, &str> = take_while(is_comma_or_whitespace)(input);
-    match result {
-        Ok(result) => Ok(result),
-        _ => Err(OxenError::parse_error(input)),
+    for agg_fn in agg.iter() {
+        if agg_fn.fn_type() == DFAggFnType::Unknown {
+            return Err(OxenError::unknown_agg_fn(&agg_fn.name));
+        }
     }
+
+    Ok(DFAggregation { group_by, agg })
 }
 
-pub fn parse_query(input: &str) -> Result<DFAggregation, OxenError> {
-    log::debug!("GOT input: {}", input);
-    // ('col_0', 'col_1') -> (list('col_3'), max('col_2'), n_unique('col_2'))
-    let (remaining, parsed) = contained_in_parens(input)?;
-    log::debug!(
-        "contained_in_parens remaining: {}, parsed: {}",
-        remaining,
-        parsed
-    );
-
-    // parsed: 'col_0', 'col_1'
-    let mut first_args: Vec<String> = vec![];
-    for s in parsed.split(',') {
-        let (_, parsed) = contained_in_quotes(s)?;
-        log::debug!(
-            "contained_in_quotes remaining: {}, parsed: {}",
-            remaining,
-            parsed
-        );
-        first_args.push(parsed.to_string());
-    }
+#[cfg(test)]
+mod tests {
+    use crate::core::df::agg::{parse_query, DFAggFnType};
+    use crate::core::df::expr::{DFArithOp, DFColumnExp};
+    use crate::error::OxenError;
 
-    // remaining: -> (list('col_3'), max('col_2'), n_unique('col_2'))
-    let (remaining, parsed) = split_on_arrow(remaining)?;
-    log::debug!(
-        "split_on_arrow remaining: {}, parsed: {:?}",
-        remaining,
-        parsed
-    );
-
-    // remaining: (list('col_3'), max('col_2'), n_unique('col_2'))
-    let (remaining, parsed) = take_open_paren(remaining)?;
-    log::debug!(
-        "take_open_paren remaining: {}, parsed: {}",
-        remaining,
-        parsed
-    );
-
-    // remaining: list('col_3'), max('col_2'), n_unique('col_2'))
-    let mut agg_fns: Vec<DFAggFn> = vec![];
-    let mut result = remaining;
-    while result != ")" {
-        log::debug!("START result {}", result);
-        // result: , max('col_2'), n_unique('col_2'))
-        if result.starts_with(',') {
-            (result, _) = take_comma(result)?;
-        }
+    #[test]
+    fn test_parse_agg_expression_alias_and_params() -> Result<(), OxenError> {
+        let query =
+            "(label, 'split') -> (mean(width * height) as area, quantile('score', 0.9), value_counts(source))";
+        let agg = parse_query(query)?;
+
+        assert_eq!(agg.group_by, vec!["label", "split"]);
+        assert_eq!(agg.agg.len(), 3);
+
+        assert_eq!(agg.agg[0].fn_type(), DFAggFnType::Mean);
+        assert_eq!(agg.agg[0].alias, "area");
+        assert!(matches!(
+            agg.agg[0].args[0],
+            DFColumnExp::Arith(DFArithOp::Mul, _, _)
+        ));
+
+        assert_eq!(agg.agg[1].fn_type(), DFAggFnType::Quantile);
+        assert_eq!(agg.agg[1].alias, "quantile('score', 0.9)");
+        assert_eq!(agg.agg[1].args[0], DFColumnExp::Col(String::from("score")));
+        assert_eq!(agg.agg[1].param(1)?, Some(0.9));
 
-        // result: max('col_2'), n_unique('col_2'))
-        log::debug!("take_comma result {}", result);
-        let (remaining, parsed) = take_fn_name(result)?;
-        log::debug!(
-            "take_alphanumeric remaining: {}, parsed: {}",
-            remaining,
-            parsed
-        );
-        let fn_name = parsed;
-
-        // remaining: ('col_2'), n_unique('col_2'))
-        let (remaining, parsed) = contained_in_parens(remaining)?;
-        log::debug!(
-            "contained_in_parens remaining: {}, parsed: {}",
-            remaining,
-            parsed
-        );
-
-        // parsed: 'col_2'
-        let (_, parsed) = contained_in_quotes(parsed)?;
-        log::debug!(
-            "contained_in_quotes remaining: {}, parsed: {}",
-            remaining,
-            parsed
-        );
-        let arg = parsed;
-
-        agg_fns.push(DFAggFn {
-            name: String::from(fn_name),
-            args: vec![String::from(arg)],
-        });
-        result = remaining;
+        assert_eq!(agg.agg[2].fn_type(), DFAggFnType::ValueCounts);
+        assert_eq!(agg.agg[2].alias, "value_counts('source')");
+        Ok(())
     }
 
-    log::debug!("GOT remaining: {:?}", remaining);
+    #[test]
+    fn test_parse_agg_without_group_by() -> Result<(), OxenError> {
+        let agg = parse_query("() -> (sum('count'))")?;
+        assert!(agg.group_by.is_empty());
+        assert_eq!(agg.agg[0].fn_type(), DFAggFnType::Sum);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_agg_num_rows_param() -> Result<(), OxenError> {
+        let agg = parse_query(
+            "() -> (head('file', 3), tail('file'), head('file', -3), tail('file', 1.5))",
+        )?;
+        assert_eq!(agg.agg[0].num_rows_param(1, 5)?, 3);
+        assert_eq!(agg.agg[1].num_rows_param(1, 5)?, 5);
+        assert_eq!(agg.agg[2].param(1)?, Some(-3.0));
+        assert!(agg.agg[2].num_rows_param(1, 5).is_err());
+        assert!(agg.agg[3].num_rows_param(1, 5).is_err());
+        Ok(())
+    }
 
-    Ok(DFAggregation {
-        group_by: first_args,
-        agg: agg_fns,
-    })
+    #[test]
+    fn test_parse_agg_unknown_fn() -> Result<(), OxenError> {
+        assert!(parse_query("('label') -> (average('score'))").is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,335 @@
+/// # Column expressions
+/// area = width * height
+/// path = concat('images/', file)
+use crate::error::OxenError;
+
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::{char, digit1, multispace0, one_of, satisfy},
+    combinator::{all_consuming, map, map_res, not, opt, peek, recognize, value},
+    multi::{fold_many0, separated_list0},
+    sequence::{delimited, pair, preceded, separated_pair, terminated},
+    IResult,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DFArithOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Mod,
+}
+
+impl DFArithOp {
+    pub fn from_char(c: char) -> Result<DFArithOp, OxenError> {
+        match c {
+            '+' => Ok(DFArithOp::Add),
+            '-' => Ok(DFArithOp::Sub),
+            '*' => Ok(DFArithOp::Mul),
+            '/' => Ok(DFArithOp::Div),
+            '%' => Ok(DFArithOp::Mod),
+            _ => Err(OxenError::parse_error(c.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DFArithOp::Add => "+",
+            DFArithOp::Sub => "-",
+            DFArithOp::Mul => "*",
+            DFArithOp::Div => "/",
+            DFArithOp::Mod => "%",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DFColumnFn {
+    // concat('images/', file, '.jpg'), every arg is cast to a string
+    Concat,
+    Lower,
+    Upper,
+    // cast(width, 'f64')
+    Cast,
+    // fill_null(label, 'unknown')
+    FillNull,
+}
+
+impl DFColumnFn {
+    pub fn from_fn_name(name: &str) -> Result<DFColumnFn, OxenError> {
+        match name {
+            "concat" => Ok(DFColumnFn::Concat),
+            "lower" => Ok(DFColumnFn::Lower),
+            "upper" => Ok(DFColumnFn::Upper),
+            "cast" => Ok(DFColumnFn::Cast),
+            "fill_null" => Ok(DFColumnFn::FillNull),
+            _ => Err(OxenError::parse_error(format!("Unknown function '{name}'"))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DFColumnFn::Concat => "concat",
+            DFColumnFn::Lower => "lower",
+            DFColumnFn::Upper => "upper",
+            DFColumnFn::Cast => "cast",
+            DFColumnFn::FillNull => "fill_null",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum DFColumnExp {
+    // a column by name, bare like width or double quoted like "image width"
+    Col(String),
+    // a single quoted string like 'images/'
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+    Null,
+    // "-score"
+    Neg(Box<DFColumnExp>),
+    // two sub expressions combined with "+", "-", "*", "/" or "%"
+    Arith(DFArithOp, Box<DFColumnExp>, Box<DFColumnExp>),
+    // a function call like "concat('images/', file)"
+    Fn(DFColumnFn, Vec<DFColumnExp>),
+}
+
+impl DFColumnExp {
+    /// All the columns the expression reads from, from left to right
+    pub fn columns(&self) -> Vec<&str> {
+        match self {
+            DFColumnExp::Col(name) => vec![name],
+            DFColumnExp::Neg(exp) => exp.columns(),
+            DFColumnExp::Arith(_, lhs, rhs) => {
+                let mut cols = lhs.columns();
+                cols.extend(rhs.columns());
+                cols
+            }
+            DFColumnExp::Fn(_, args) => args.iter().flat_map(|arg| arg.columns()).collect(),
+            _ => vec![],
+        }
+    }
+}
+
+/// A computed column "name = expr"
+#[derive(Clone, Debug, PartialEq)]
+pub struct DFWithColumn {
+    pub name: String,
+    pub exp: DFColumnExp,
+}
+
+fn is_ident_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+fn ident(input: &str) -> IResult<&str, &str> {
+    recognize(pair(
+        satisfy(|c| c.is_alphabetic() || c == '_'),
+        take_while(is_ident_char),
+    ))(input)
+}
+
+fn quoted_name(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('"'), take_while1(|c| c != '"'), char('"')),
+        delimited(char('`'), take_while1(|c| c != '`'), char('`')),
+    ))(input)
+}
+
+fn column_name(input: &str) -> IResult<&str, String> {
+    map(alt((quoted_name, ident)), String::from)(input)
+}
+
+fn string_lit(input: &str) -> IResult<&str, DFColumnExp> {
+    map(
+        delimited(char('\''), take_while(|c| c != '\''), char('\'')),
+        |s: &str| DFColumnExp::Str(s.to_string()),
+    )(input)
+}
+
+fn number(input: &str) -> IResult<&str, DFColumnExp> {
+    map_res(
+        recognize(pair(digit1, opt(pair(char('.'), digit1)))),
+        |s: &str| {
+            if s.contains('.') {
+                s.parse::<f64>().map(DFColumnExp::Float).map_err(|_| ())
+            } else {
+                s.parse::<i64>().map(DFColumnExp::Int).map_err(|_| ())
+            }
+        },
+    )(input)
+}
+
+// a keyword that is not the start of a longer column name like "true_label"
+fn keyword<'a>(word: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, &'a str> {
+    terminated(tag(word), not(peek(satisfy(is_ident_char))))
+}
+
+fn constant(input: &str) -> IResult<&str, DFColumnExp> {
+    alt((
+        value(DFColumnExp::Bool(true), keyword("true")),
+        value(DFColumnExp::Bool(false), keyword("false")),
+        value(DFColumnExp::Null, keyword("null")),
+    ))(input)
+}
+
+fn call(input: &str) -> IResult<&str, DFColumnExp> {
+    let (input, func) = map_res(terminated(ident, pair(multispace0, char('('))), |name| {
+        DFColumnFn::from_fn_name(name)
+    })(input)?;
+    let (input, args) = terminated(
+        separated_list0(preceded(multispace0, char(',')), sum),
+        preceded(multispace0, char(')')),
+    )(input)?;
+    Ok((input, DFColumnExp::Fn(func, args)))
+}
+
+fn parens(input: &str) -> IResult<&str, DFColumnExp> {
+    delimited(char('('), sum, preceded(multispace0, char(')')))(input)
+}
+
+fn atom(input: &str) -> IResult<&str, DFColumnExp> {
+    preceded(
+        multispace0,
+        alt((
+            map(preceded(char('-'), atom), |exp| {
+                DFColumnExp::Neg(Box::new(exp))
+            }),
+            parens,
+            number,
+            string_lit,
+            constant,
+            call,
+            map(column_name, DFColumnExp::Col),
+        )),
+    )(input)
+}
+
+fn arith_op<'a>(ops: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, DFArithOp> {
+    map_res(delimited(multispace0, one_of(ops), multispace0), |c| {
+        DFArithOp::from_char(c)
+    })
+}
+
+fn product(input: &str) -> IResult<&str, DFColumnExp> {
+    let (input, first) = atom(input)?;
+    fold_many0(
+        pair(arith_op("*/%"), atom),
+        move || first.clone(),
+        |lhs, (op, rhs)| DFColumnExp::Arith(op, Box::new(lhs), Box::new(rhs)),
+    )(input)
+}
+
+fn sum(input: &str) -> IResult<&str, DFColumnExp> {
+    let (input, first) = product(input)?;
+    fold_many0(
+        pair(arith_op("+-"), product),
+        move || first.clone(),
+        |lhs, (op, rhs)| DFColumnExp::Arith(op, Box::new(lhs), Box::new(rhs)),
+    )(input)
+}
+
+/// Parse a single expression, for grammars that take expressions as arguments
+pub(crate) fn column_exp(input: &str) -> IResult<&str, DFColumnExp> {
+    sum(input)
+}
+
+/// Can parse a computed column such as "area = width * height" or "path = concat('images/', file)"
+/// `*`, `/` and `%` bind tighter than `+` and `-`, use parentheses to group
+pub fn parse_with_column(query: &str) -> Result<DFWithColumn, OxenError> {
+    let result: IResult<&str, (String, DFColumnExp)> = all_consuming(delimited(
+        multispace0,
+        separated_pair(
+            column_name,
+            delimited(multispace0, char('='), multispace0),
+            sum,
+        ),
+        multispace0,
+    ))(query);
+    match result {
+        Ok((_, (name, exp))) => Ok(DFWithColumn { name, exp }),
+        Err(err) => {
+            log::debug!("Could not parse column expression {query:?}: {err}");
+            Err(OxenError::parse_error(format!(
+                "Invalid column expression '{query}'. Format: 'name = expr'"
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::df::expr::{parse_with_column, DFArithOp, DFColumnExp, DFColumnFn};
+    use crate::error::OxenError;
+
+    #[test]
+    fn test_parse_with_column_arithmetic_precedence() -> Result<(), OxenError> {
+        let with_col = parse_with_column("area = width * height + 1")?;
+        assert_eq!(with_col.name, "area");
+        match &with_col.exp {
+            DFColumnExp::Arith(DFArithOp::Add, lhs, rhs) => {
+                assert!(matches!(**lhs, DFColumnExp::Arith(DFArithOp::Mul, _, _)));
+                assert_eq!(**rhs, DFColumnExp::Int(1));
+            }
+            _ => panic!("Expected + at the root, got {:?}", with_col.exp),
+        }
+        assert_eq!(with_col.exp.columns(), vec!["width", "height"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_with_column_function_and_literals() -> Result<(), OxenError> {
+        let with_col = parse_with_column("path = concat('images/', file, '.jpg')")?;
+        assert_eq!(with_col.name, "path");
+        assert_eq!(
+            with_col.exp,
+            DFColumnExp::Fn(
+                DFColumnFn::Concat,
+                vec![
+                    DFColumnExp::Str(String::from("images/")),
+                    DFColumnExp::Col(String::from("file")),
+                    DFColumnExp::Str(String::from(".jpg")),
+                ]
+            )
+        );
+
+        let with_col = parse_with_column("\"is ok\" = fill_null(true_label, false)")?;
+        assert_eq!(with_col.name, "is ok");
+        assert_eq!(
+            with_col.exp,
+            DFColumnExp::Fn(
+                DFColumnFn::FillNull,
+                vec![
+                    DFColumnExp::Col(String::from("true_label")),
+                    DFColumnExp::Bool(false),
+                ]
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_with_column_parens_and_neg() -> Result<(), OxenError> {
+        let with_col = parse_with_column("x = -(min_x + 0.5) / 2")?;
+        match &with_col.exp {
+            DFColumnExp::Arith(DFArithOp::Div, lhs, _) => {
+                assert!(matches!(**lhs, DFColumnExp::Neg(_)));
+            }
+            _ => panic!("Expected / at the root, got {:?}", with_col.exp),
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_with_column_invalid_returns_error() -> Result<(), OxenError> {
+        assert!(parse_with_column("width * height").is_err());
+        assert!(parse_with_column("area = width *").is_err());
+        assert!(parse_with_column("area = (width * height").is_err());
+        assert!(parse_with_column("area = unknown_fn(width)").is_err());
+        Ok(())
+    }
+}
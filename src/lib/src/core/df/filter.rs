@@ -1,6 +1,16 @@
+/// # Filters
+/// label == dog && (min_x > 10 || not width < 5) && source in [web, 'hand labeled']
 use crate::error::OxenError;
 
-use crate::util;
+use nom::{
+    branch::alt,
+    bytes::complete::{tag, take_while, take_while1},
+    character::complete::{char, multispace0, multispace1},
+    combinator::{all_consuming, map, peek, value},
+    multi::{fold_many0, separated_list0},
+    sequence::{delimited, pair, preceded, terminated, tuple},
+    IResult,
+};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DFLogicalOp {
@@ -9,11 +19,11 @@ pub enum DFLogicalOp {
 }
 
 impl DFLogicalOp {
-    pub fn from_str_op(s: &str) -> DFLogicalOp {
+    pub fn from_str_op(s: &str) -> Result<DFLogicalOp, OxenError> {
         match s {
-            "&&" => DFLogicalOp::AND,
-            "||" => DFLogicalOp::OR,
-            _ => panic!("Unknown DFLogicalOp"),
+            "&&" => Ok(DFLogicalOp::AND),
+            "||" => Ok(DFLogicalOp::OR),
+            _ => Err(OxenError::parse_error(s)),
         }
     }
 
@@ -33,18 +43,28 @@ pub enum DFFilterOp {
     GTE,
     LTE,
     NEQ,
+    In,
+    Contains,
+    Regex,
+    IsNull,
+    IsNotNull,
 }
 
 impl DFFilterOp {
-    pub fn from_str_op(s: &str) -> DFFilterOp {
+    pub fn from_str_op(s: &str) -> Result<DFFilterOp, OxenError> {
         match s {
-            "==" => DFFilterOp::EQ,
-            "<" => DFFilterOp::LT,
-            ">" => DFFilterOp::GT,
-            "<=" => DFFilterOp::LTE,
-            ">=" => DFFilterOp::GTE,
-            "!=" => DFFilterOp::NEQ,
-            _ => panic!("Unknown DFFilterOp"),
+            "==" => Ok(DFFilterOp::EQ),
+            "<" => Ok(DFFilterOp::LT),
+            ">" => Ok(DFFilterOp::GT),
+            "<=" => Ok(DFFilterOp::LTE),
+            ">=" => Ok(DFFilterOp::GTE),
+            "!=" => Ok(DFFilterOp::NEQ),
+            "in" => Ok(DFFilterOp::In),
+            "contains" => Ok(DFFilterOp::Contains),
+            "=~" => Ok(DFFilterOp::Regex),
+            "is null" => Ok(DFFilterOp::IsNull),
+            "is not null" => Ok(DFFilterOp::IsNotNull),
+            _ => Err(OxenError::parse_error(s)),
         }
     }
 
@@ -56,103 +76,198 @@ impl DFFilterOp {
             DFFilterOp::LTE => "<=",
             DFFilterOp::GTE => ">=",
             DFFilterOp::NEQ => "!=",
+            DFFilterOp::In => "in",
+            DFFilterOp::Contains => "contains",
+            DFFilterOp::Regex => "=~",
+            DFFilterOp::IsNull => "is null",
+            DFFilterOp::IsNotNull => "is not null",
         }
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DFFilterVal {
     pub op: DFFilterOp,
     pub field: String,
+    // value to compare against, empty for the null checks
     pub value: String,
+    // all the values for `in [..]`
+    pub values: Vec<String>,
 }
 
-#[derive(Clone, Debug)]
-pub struct DFFilterExp {
-    // logical ops are all the ops we want to chain "&&","||"
-    pub logical_ops: Vec<DFLogicalOp>,
-    // vals are all the sub expressions like "label != person"
-    pub vals: Vec<DFFilterVal>,
-}
-
-// Scan query to find the first logical op if there is one
-fn find_next_logical_op(
-    query: &str,
-    logical_ops: &Vec<DFLogicalOp>,
-) -> Option<(DFLogicalOp, usize)> {
-    let mut logical_op: Option<(DFLogicalOp, usize)> = None;
-    let mut min_idx = query.len();
-    for op in logical_ops {
-        if let Some(idx) = query.find(op.as_str()) {
-            if idx < min_idx {
-                logical_op = Some((op.to_owned(), idx));
-                min_idx = idx;
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DFFilterExp {
+    // a single comparison like "label != person"
+    Val(DFFilterVal),
+    // "not label == person"
+    Not(Box<DFFilterExp>),
+    // two sub expressions chained with "&&" or "||"
+    Logical(DFLogicalOp, Box<DFFilterExp>, Box<DFFilterExp>),
+}
+
+impl DFFilterExp {
+    /// All the comparisons in the expression, from left to right
+    pub fn vals(&self) -> Vec<&DFFilterVal> {
+        match self {
+            DFFilterExp::Val(val) => vec![val],
+            DFFilterExp::Not(exp) => exp.vals(),
+            DFFilterExp::Logical(_, lhs, rhs) => {
+                let mut vals = lhs.vals();
+                vals.extend(rhs.vals());
+                vals
             }
         }
     }
-    logical_op
 }
 
-/// Can parse an expression such as "pred_label == person && is_correct == true"
-pub fn parse(query: Option<String>) -> Result<Option<DFFilterExp>, OxenError> {
-    if let Some(mut filter) = query {
-        if filter.is_empty() {
-            return Err(OxenError::parse_error(filter));
-        }
+fn is_field_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.' || c == '-' || c == '/'
+}
 
-        // 1) Iterate over string finding DFLogicalOps and collecting the sub expressions
-        let mut found_ops: Vec<DFLogicalOp> = vec![];
-        let mut sub_exprs: Vec<String> = vec![];
-        let candidate_ops = vec![DFLogicalOp::AND, DFLogicalOp::OR];
+// `&` and `|` end a bare value so that "a==b&&c==d" parses, quote values that contain them
+fn is_bare_value_char(c: char) -> bool {
+    !c.is_whitespace() && !"()[],&|".contains(c)
+}
 
-        while let Some((op, idx)) = find_next_logical_op(&filter, &candidate_ops) {
-            // split at idx
-            let op_str = op.as_str();
-            let (sub_expr, remaining) = filter.split_at(idx);
-            let (_, remaining) = remaining.split_at(op_str.len());
+fn quoted(input: &str) -> IResult<&str, &str> {
+    alt((
+        delimited(char('"'), take_while(|c| c != '"'), char('"')),
+        delimited(char('\''), take_while(|c| c != '\''), char('\'')),
+    ))(input)
+}
 
-            // add sub_expr to eval later
-            sub_exprs.push(sub_expr.trim().to_string());
+fn field(input: &str) -> IResult<&str, String> {
+    map(alt((quoted, take_while1(is_field_char))), String::from)(input)
+}
 
-            // add op
-            found_ops.push(op);
-            filter = remaining.to_string();
-        }
+fn literal(input: &str) -> IResult<&str, String> {
+    map(alt((quoted, take_while1(is_bare_value_char))), String::from)(input)
+}
 
-        // push remaining
-        sub_exprs.push(filter.trim().to_string());
-
-        // 2) Parse each sub expression
-        // Order in which we check matters because some ops are substrings of others, put the longest ones first
-        let filter_ops = vec![
-            DFFilterOp::NEQ,
-            DFFilterOp::GTE,
-            DFFilterOp::LTE,
-            DFFilterOp::EQ,
-            DFFilterOp::GT,
-            DFFilterOp::LT,
-        ];
-
-        let mut filter_vals: Vec<DFFilterVal> = vec![];
-        for sub_expr in sub_exprs {
-            for op in filter_ops.iter() {
-                if sub_expr.contains(op.as_str()) {
-                    let split = util::str::split_and_trim(&sub_expr, op.as_str());
-                    let val = DFFilterVal {
-                        op: op.clone(),
-                        field: split[0].to_owned(),
-                        value: split[1].to_owned(),
-                    };
-                    filter_vals.push(val);
-                    break;
-                }
-            }
+fn literal_list(input: &str) -> IResult<&str, Vec<String>> {
+    delimited(
+        pair(char('['), multispace0),
+        separated_list0(delimited(multispace0, char(','), multispace0), literal),
+        pair(multispace0, char(']')),
+    )(input)
+}
+
+fn comparison_op(input: &str) -> IResult<&str, DFFilterOp> {
+    // Order matters because some ops are prefixes of others, put the longest ones first
+    alt((
+        value(DFFilterOp::Regex, tag("=~")),
+        value(DFFilterOp::EQ, tag("==")),
+        value(DFFilterOp::NEQ, tag("!=")),
+        value(DFFilterOp::GTE, tag(">=")),
+        value(DFFilterOp::LTE, tag("<=")),
+        value(DFFilterOp::GT, tag(">")),
+        value(DFFilterOp::LT, tag("<")),
+        value(DFFilterOp::EQ, tag("=")),
+    ))(input)
+}
+
+// field followed by an op and its values, ie: "label in [cat, dog]"
+fn predicate(input: &str) -> IResult<&str, DFFilterExp> {
+    let (input, field) = terminated(field, multispace0)(input)?;
+    let (input, (op, values)) = alt((
+        map(
+            tuple((tag("is"), multispace1, tag("not"), multispace1, tag("null"))),
+            |_| (DFFilterOp::IsNotNull, vec![]),
+        ),
+        map(tuple((tag("is"), multispace1, tag("null"))), |_| {
+            (DFFilterOp::IsNull, vec![])
+        }),
+        map(
+            preceded(pair(tag("in"), multispace0), literal_list),
+            |values| (DFFilterOp::In, values),
+        ),
+        map(
+            preceded(pair(tag("contains"), multispace1), literal),
+            |value| (DFFilterOp::Contains, vec![value]),
+        ),
+        map(
+            pair(comparison_op, preceded(multispace0, literal)),
+            |(op, value)| (op, vec![value]),
+        ),
+    ))(input)?;
+
+    let value = match op {
+        DFFilterOp::In | DFFilterOp::IsNull | DFFilterOp::IsNotNull => String::new(),
+        _ => values[0].to_owned(),
+    };
+    let values = if op == DFFilterOp::In { values } else { vec![] };
+
+    Ok((
+        input,
+        DFFilterExp::Val(DFFilterVal {
+            op,
+            field,
+            value,
+            values,
+        }),
+    ))
+}
+
+fn parens(input: &str) -> IResult<&str, DFFilterExp> {
+    delimited(char('('), or_expr, preceded(multispace0, char(')')))(input)
+}
+
+fn not_keyword(input: &str) -> IResult<&str, &str> {
+    alt((
+        terminated(tag("not"), multispace1),
+        terminated(tag("not"), peek(char('('))),
+        tag("!"),
+    ))(input)
+}
+
+fn unary(input: &str) -> IResult<&str, DFFilterExp> {
+    preceded(
+        multispace0,
+        alt((
+            map(preceded(not_keyword, unary), |exp| {
+                DFFilterExp::Not(Box::new(exp))
+            }),
+            parens,
+            predicate,
+        )),
+    )(input)
+}
+
+fn and_expr(input: &str) -> IResult<&str, DFFilterExp> {
+    let (input, first) = unary(input)?;
+    fold_many0(
+        preceded(delimited(multispace0, tag("&&"), multispace0), unary),
+        move || first.clone(),
+        |lhs, rhs| DFFilterExp::Logical(DFLogicalOp::AND, Box::new(lhs), Box::new(rhs)),
+    )(input)
+}
+
+fn or_expr(input: &str) -> IResult<&str, DFFilterExp> {
+    let (input, first) = and_expr(input)?;
+    fold_many0(
+        preceded(delimited(multispace0, tag("||"), multispace0), and_expr),
+        move || first.clone(),
+        |lhs, rhs| DFFilterExp::Logical(DFLogicalOp::OR, Box::new(lhs), Box::new(rhs)),
+    )(input)
+}
+
+/// Can parse an expression such as "pred_label == person && (is_correct == true || score > 0.5)"
+/// `&&` binds tighter than `||`, use parentheses to group
+pub fn parse(query: Option<String>) -> Result<Option<DFFilterExp>, OxenError> {
+    if let Some(filter) = query {
+        if filter.trim().is_empty() {
+            return Err(OxenError::parse_error(filter));
         }
 
-        return Ok(Some(DFFilterExp {
-            logical_ops: found_ops,
-            vals: filter_vals,
-        }));
+        let result: IResult<&str, DFFilterExp> =
+            all_consuming(terminated(or_expr, multispace0))(filter.as_str());
+        return match result {
+            Ok((_, exp)) => Ok(Some(exp)),
+            Err(err) => {
+                log::debug!("Could not parse filter {filter:?}: {err}");
+                Err(OxenError::parse_error(filter))
+            }
+        };
     }
     Ok(None)
 }
@@ -160,7 +275,7 @@ pub fn parse(query: Option<String>) -> Result<Option<DFFilterExp>, OxenError> {
 #[cfg(test)]
 mod tests {
     use crate::{
-        core::df::filter::{parse, DFFilterOp, DFLogicalOp},
+        core::df::filter::{parse, DFFilterExp, DFFilterOp, DFLogicalOp},
         error::OxenError,
     };
 
@@ -169,13 +284,12 @@ mod tests {
         let query = Some("label == person".to_string());
 
         let opt = parse(query)?.unwrap();
+        let vals = opt.vals();
 
-        assert_eq!(opt.logical_ops.len(), 0);
-
-        assert_eq!(opt.vals.len(), 1);
-        assert_eq!(opt.vals[0].field, "label");
-        assert_eq!(opt.vals[0].op, DFFilterOp::EQ);
-        assert_eq!(opt.vals[0].value, "person");
+        assert_eq!(vals.len(), 1);
+        assert_eq!(vals[0].field, "label");
+        assert_eq!(vals[0].op, DFFilterOp::EQ);
+        assert_eq!(vals[0].value, "person");
         Ok(())
     }
 
@@ -184,17 +298,16 @@ mod tests {
         let query = Some("label == person && is_true == false".to_string());
 
         let opt = parse(query)?.unwrap();
-
-        assert_eq!(opt.logical_ops.len(), 1);
-        assert_eq!(opt.logical_ops[0], DFLogicalOp::AND);
-
-        assert_eq!(opt.vals.len(), 2);
-        assert_eq!(opt.vals[0].field, "label");
-        assert_eq!(opt.vals[0].op, DFFilterOp::EQ);
-        assert_eq!(opt.vals[0].value, "person");
-        assert_eq!(opt.vals[1].field, "is_true");
-        assert_eq!(opt.vals[1].op, DFFilterOp::EQ);
-        assert_eq!(opt.vals[1].value, "false");
+        assert!(matches!(opt, DFFilterExp::Logical(DFLogicalOp::AND, _, _)));
+
+        let vals = opt.vals();
+        assert_eq!(vals.len(), 2);
+        assert_eq!(vals[0].field, "label");
+        assert_eq!(vals[0].op, DFFilterOp::EQ);
+        assert_eq!(vals[0].value, "person");
+        assert_eq!(vals[1].field, "is_true");
+        assert_eq!(vals[1].op, DFFilterOp::EQ);
+        assert_eq!(vals[1].value, "false");
         Ok(())
     }
 
@@ -204,20 +317,100 @@ mod tests {
 
         let opt = parse(query)?.unwrap();
 
-        assert_eq!(opt.logical_ops.len(), 2);
-        assert_eq!(opt.logical_ops[0], DFLogicalOp::AND);
-        assert_eq!(opt.logical_ops[1], DFLogicalOp::OR);
-
-        assert_eq!(opt.vals.len(), 3);
-        assert_eq!(opt.vals[0].field, "label");
-        assert_eq!(opt.vals[0].op, DFFilterOp::EQ);
-        assert_eq!(opt.vals[0].value, "person");
-        assert_eq!(opt.vals[1].field, "min_x");
-        assert_eq!(opt.vals[1].op, DFFilterOp::GT);
-        assert_eq!(opt.vals[1].value, "0");
-        assert_eq!(opt.vals[2].field, "max_x");
-        assert_eq!(opt.vals[2].op, DFFilterOp::GTE);
-        assert_eq!(opt.vals[2].value, "1.0");
+        // && binds tighter, so this is (label == person && min_x > 0) || max_x >= 1.0
+        match &opt {
+            DFFilterExp::Logical(DFLogicalOp::OR, lhs, _) => {
+                assert!(matches!(
+                    **lhs,
+                    DFFilterExp::Logical(DFLogicalOp::AND, _, _)
+                ));
+            }
+            _ => panic!("Expected || at the root, got {opt:?}"),
+        }
+
+        let vals = opt.vals();
+        assert_eq!(vals.len(), 3);
+        assert_eq!(vals[0].field, "label");
+        assert_eq!(vals[0].op, DFFilterOp::EQ);
+        assert_eq!(vals[0].value, "person");
+        assert_eq!(vals[1].field, "min_x");
+        assert_eq!(vals[1].op, DFFilterOp::GT);
+        assert_eq!(vals[1].value, "0");
+        assert_eq!(vals[2].field, "max_x");
+        assert_eq!(vals[2].op, DFFilterOp::GTE);
+        assert_eq!(vals[2].value, "1.0");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_without_spaces() -> Result<(), OxenError> {
+        let query = Some("label==person&&min_x>0||file=~'^train/(cat|dog)'".to_string());
+
+        let opt = parse(query)?.unwrap();
+        assert!(matches!(opt, DFFilterExp::Logical(DFLogicalOp::OR, _, _)));
+
+        let vals = opt.vals();
+        assert_eq!(vals.len(), 3);
+        assert_eq!(vals[0].field, "label");
+        assert_eq!(vals[0].value, "person");
+        assert_eq!(vals[1].field, "min_x");
+        assert_eq!(vals[1].op, DFFilterOp::GT);
+        assert_eq!(vals[1].value, "0");
+        assert_eq!(vals[2].op, DFFilterOp::Regex);
+        assert_eq!(vals[2].value, "^train/(cat|dog)");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_parens_and_not() -> Result<(), OxenError> {
+        let query = Some("not (label == person || label == 'hot dog') && score < 0.5".to_string());
+
+        let opt = parse(query)?.unwrap();
+        match &opt {
+            DFFilterExp::Logical(DFLogicalOp::AND, lhs, _) => match &**lhs {
+                DFFilterExp::Not(exp) => {
+                    assert!(matches!(**exp, DFFilterExp::Logical(DFLogicalOp::OR, _, _)));
+                }
+                _ => panic!("Expected not on the left, got {lhs:?}"),
+            },
+            _ => panic!("Expected && at the root, got {opt:?}"),
+        }
+
+        let vals = opt.vals();
+        assert_eq!(vals[1].value, "hot dog");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_in_null_contains_regex() -> Result<(), OxenError> {
+        let query = Some(
+            "label in [cat, 'hot dog'] && bbox is not null && note is null && text contains 'good boy' && file =~ ^train/"
+                .to_string(),
+        );
+
+        let opt = parse(query)?.unwrap();
+        let vals = opt.vals();
+        assert_eq!(vals.len(), 5);
+
+        assert_eq!(vals[0].op, DFFilterOp::In);
+        assert_eq!(vals[0].values, vec!["cat", "hot dog"]);
+        assert_eq!(vals[1].op, DFFilterOp::IsNotNull);
+        assert_eq!(vals[1].field, "bbox");
+        assert_eq!(vals[2].op, DFFilterOp::IsNull);
+        assert_eq!(vals[2].field, "note");
+        assert_eq!(vals[3].op, DFFilterOp::Contains);
+        assert_eq!(vals[3].value, "good boy");
+        assert_eq!(vals[4].op, DFFilterOp::Regex);
+        assert_eq!(vals[4].value, "^train/");
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_filter_invalid_returns_error() -> Result<(), OxenError> {
+        assert!(parse(Some("label ~~ person".to_string())).is_err());
+        assert!(parse(Some("(label == person".to_string())).is_err());
+        assert!(parse(Some("label == person &&".to_string())).is_err());
+        assert!(parse(Some("label in [cat".to_string())).is_err());
         Ok(())
     }
 }
@@ -0,0 +1,206 @@
+//! Deterministic train/val/test splits
+//!
+//! Rows are identified by their key columns. Rows that already belong to a split keep it,
+//! and new rows fill each split up to its ratio in an order given by hashing the key with the seed,
+//! so running the split again after adding rows never moves a row to another split.
+
+use std::collections::{BTreeMap, HashMap};
+
+use polars::prelude::*;
+use xxhash_rust::xxh3::xxh3_64_with_seed;
+
+use crate::error::OxenError;
+use crate::opts::SplitOpts;
+
+// Separates the values of multi column keys so ("a", "bc") and ("ab", "c") differ
+const KEY_SEPARATOR: &str = "\u{1f}";
+
+/// The key of every row, the key columns joined as strings. Nulls are empty strings.
+pub fn row_keys(df: &DataFrame, key: &Option<Vec<String>>) -> Result<Vec<String>, OxenError> {
+    let columns: Vec<String> = match key {
+        Some(columns) => columns.to_owned(),
+        None => df
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .collect(),
+    };
+
+    let mut values: Vec<Utf8Chunked> = vec![];
+    for column in columns.iter() {
+        let series = df
+            .column(column)
+            .map_err(|_| OxenError::basic_str(format!("Key column '{column}' not found")))?;
+        let series = series
+            .cast(&DataType::Utf8)
+            .map_err(|err| OxenError::basic_str(format!("{err}")))?;
+        values.push(series.utf8().unwrap().clone());
+    }
+
+    Ok((0..df.height())
+        .map(|i| {
+            values
+                .iter()
+                .map(|vals| vals.get(i).unwrap_or(""))
+                .collect::<Vec<&str>>()
+                .join(KEY_SEPARATOR)
+        })
+        .collect())
+}
+
+/// Split the rows of `df` into one data frame per ratio, keeping the original row order within each.
+/// `existing` maps the keys that are already in a split to the index of that split.
+pub fn split_df(
+    df: &DataFrame,
+    opts: &SplitOpts,
+    existing: &HashMap<String, usize>,
+) -> Result<Vec<DataFrame>, OxenError> {
+    opts.validate()?;
+    let keys = row_keys(df, &opts.key)?;
+    let strata = match &opts.stratify {
+        Some(column) => row_keys(df, &Some(vec![column.to_owned()]))?,
+        None => vec![String::new(); df.height()],
+    };
+
+    // Sorted so the strata are always assigned in the same order
+    let mut rows_by_stratum: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, stratum) in strata.iter().enumerate() {
+        rows_by_stratum.entry(stratum).or_default().push(i);
+    }
+
+    let mut assigned: HashMap<String, usize> = existing.to_owned();
+    let mut split_rows: Vec<Vec<IdxSize>> = vec![vec![]; opts.ratios.len()];
+    for rows in rows_by_stratum.values() {
+        let mut counts: Vec<usize> = vec![0; opts.ratios.len()];
+        let mut new_rows: Vec<(u64, usize)> = vec![];
+        for &i in rows.iter() {
+            match assigned.get(&keys[i]) {
+                Some(&split) => {
+                    counts[split] += 1;
+                    split_rows[split].push(i as IdxSize);
+                }
+                None => new_rows.push((xxh3_64_with_seed(keys[i].as_bytes(), opts.seed), i)),
+            }
+        }
+
+        new_rows.sort();
+        for (_, i) in new_rows {
+            // A duplicate key earlier in this run already picked a split
+            let split = match assigned.get(&keys[i]) {
+                Some(&split) => split,
+                None => {
+                    let split = most_behind_split(&opts.ratios, &counts);
+                    assigned.insert(keys[i].to_owned(), split);
+                    split
+                }
+            };
+            counts[split] += 1;
+            split_rows[split].push(i as IdxSize);
+        }
+    }
+
+    split_rows
+        .into_iter()
+        .map(|mut rows| {
+            rows.sort();
+            let idx = IdxCa::new("idx", &rows);
+            df.take(&idx)
+                .map_err(|err| OxenError::basic_str(format!("Could not take rows: {err}")))
+        })
+        .collect()
+}
+
+/// The split that is furthest below its ratio after adding one more row, the first on ties
+fn most_behind_split(ratios: &[f64], counts: &[usize]) -> usize {
+    let total = counts.iter().sum::<usize>() + 1;
+    let mut best = 0;
+    let mut best_deficit = f64::MIN;
+    for (i, (ratio, count)) in ratios.iter().zip(counts.iter()).enumerate() {
+        let deficit = ratio * total as f64 - *count as f64;
+        if deficit > best_deficit + 1e-9 {
+            best = i;
+            best_deficit = deficit;
+        }
+    }
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use polars::prelude::*;
+
+    use crate::core::df::split;
+    use crate::error::OxenError;
+    use crate::opts::SplitOpts;
+
+    fn labels_df(n: usize) -> DataFrame {
+        let files: Vec<String> = (0..n).map(|i| format!("{i:04}.jpg")).collect();
+        let labels: Vec<&str> = (0..n)
+            .map(|i| if i % 4 == 0 { "dog" } else { "cat" })
+            .collect();
+        df!("file" => &files, "label" => &labels).unwrap()
+    }
+
+    fn split_keys(df: &DataFrame) -> Vec<String> {
+        split::row_keys(df, &Some(vec![String::from("file")])).unwrap()
+    }
+
+    #[test]
+    fn test_split_df_stratified_ratios() -> Result<(), OxenError> {
+        let df = labels_df(100);
+        let mut opts = SplitOpts::from_ratios(vec![0.8, 0.1, 0.1]);
+        opts.key = Some(vec![String::from("file")]);
+        opts.stratify = Some(String::from("label"));
+        opts.seed = 42;
+
+        let splits = split::split_df(&df, &opts, &HashMap::new())?;
+        let heights: Vec<usize> = splits.iter().map(|s| s.height()).collect();
+        assert_eq!(heights[0], 80);
+        // Each label rounds on its own, so val and test can be off by one
+        assert!(heights[1].abs_diff(10) <= 1 && heights[2].abs_diff(10) <= 1);
+        assert_eq!(heights.iter().sum::<usize>(), 100);
+
+        // 25 dogs split 20/3/2 instead of by chance
+        let train_dogs = splits[0]
+            .column("label")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_iter()
+            .filter(|l| *l == Some("dog"))
+            .count();
+        assert_eq!(train_dogs, 20);
+
+        // Same seed, same splits
+        let again = split::split_df(&df, &opts, &HashMap::new())?;
+        assert!(again[1].frame_equal(&splits[1]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_df_adding_rows_keeps_existing_splits() -> Result<(), OxenError> {
+        let mut opts = SplitOpts::from_ratios(vec![0.5, 0.5]);
+        opts.key = Some(vec![String::from("file")]);
+
+        let splits = split::split_df(&labels_df(10), &opts, &HashMap::new())?;
+        let mut existing: HashMap<String, usize> = HashMap::new();
+        for (i, split_df) in splits.iter().enumerate() {
+            for key in split_keys(split_df) {
+                existing.insert(key, i);
+            }
+        }
+
+        let grown = split::split_df(&labels_df(30), &opts, &existing)?;
+        for (i, split_df) in grown.iter().enumerate() {
+            for key in split_keys(split_df) {
+                if let Some(split) = existing.get(&key) {
+                    assert_eq!(*split, i, "{key} moved splits");
+                }
+            }
+        }
+        assert_eq!(grown[0].height() + grown[1].height(), 30);
+        Ok(())
+    }
+}
@@ -1,26 +1,32 @@
+use polars::io::mmap::MmapBytesReader;
+use polars::sql::SQLContext;
 use polars::{lazy::dsl::Expr, prelude::*};
 
 use crate::constants;
 use crate::core::df::filter::DFLogicalOp;
 use crate::error::OxenError;
-use crate::model::schema::DataType;
+use crate::model::schema::{DataType, Field};
 use crate::model::ContentType;
-use crate::opts::DFOpts;
+use crate::model::CsvDialect;
+use crate::opts::{DFOpts, PivotColVals};
+use crate::util;
 use crate::util::hasher;
 
 use colored::Colorize;
 use comfy_table::Table;
+use encoding_rs::Encoding;
 use indicatif::ProgressBar;
 use qsv_sniffer;
 use rand::prelude::SliceRandom;
 use rand::thread_rng;
 use std::ffi::OsStr;
 use std::fs::File;
-use std::io::Cursor;
-use std::path::Path;
+use std::io::{Cursor, Read};
+use std::path::{Path, PathBuf};
 
 use super::{
     agg::{DFAggFn, DFAggFnType, DFAggregation},
+    expr::{DFArithOp, DFColumnExp, DFColumnFn, DFWithColumn},
     filter::{DFFilterExp, DFFilterOp, DFFilterVal},
 };
 
@@ -28,14 +34,20 @@ const DEFAULT_INFER_SCHEMA_LEN: usize = 10000;
 const READ_ERROR: &str = "Could not read tabular data from path";
 const COLLECT_ERROR: &str = "Could not collect DataFrame";
 const TAKE_ERROR: &str = "Could not take DataFrame";
+const TAKE_ROW_COL_NAME: &str = "_oxen_take_row";
 const CSV_READ_ERROR: &str = "Could not read csv from path";
+const CSV_SNIFF_SAMPLE_SIZE: u64 = 64 * 1024;
 
-fn try_infer_schema_csv(reader: CsvReader<File>, delimiter: u8) -> Result<DataFrame, OxenError> {
-    log::debug!("try_infer_schema_csv delimiter: {:?}", delimiter as char);
+fn try_infer_schema_csv<R: MmapBytesReader>(
+    reader: CsvReader<R>,
+    dialect: &CsvDialect,
+) -> Result<DataFrame, OxenError> {
+    log::debug!("try_infer_schema_csv dialect: {dialect}");
     let result = reader
         .infer_schema(Some(DEFAULT_INFER_SCHEMA_LEN))
-        .has_header(true)
-        .with_delimiter(delimiter)
+        .has_header(dialect.has_header)
+        .with_delimiter(dialect.delimiter_byte()?)
+        .with_quote_char(dialect.quote_byte()?)
         .with_encoding(CsvEncoding::LossyUtf8)
         .finish();
 
@@ -54,7 +66,10 @@ fn try_infer_schema_csv(reader: CsvReader<File>, delimiter: u8) -> Result<DataFr
 
 pub fn read_df_csv<P: AsRef<Path>>(path: P, delimiter: u8) -> Result<DataFrame, OxenError> {
     match CsvReader::from_path(path.as_ref()) {
-        Ok(reader) => Ok(try_infer_schema_csv(reader, delimiter)?),
+        Ok(reader) => Ok(try_infer_schema_csv(
+            reader,
+            &CsvDialect::with_delimiter(delimiter as char),
+        )?),
         Err(err) => {
             let err = format!("{CSV_READ_ERROR}: {err:?}");
             Err(OxenError::basic_str(err))
@@ -63,9 +78,124 @@ pub fn read_df_csv<P: AsRef<Path>>(path: P, delimiter: u8) -> Result<DataFrame,
 }
 
 pub fn scan_df_csv<P: AsRef<Path>>(path: P, delimiter: u8) -> Result<LazyFrame, OxenError> {
-    // TODO: The LazyCsvReader was acting funky here on certain csvs...
-    let df = read_df_csv(path, delimiter)?;
-    Ok(df.lazy())
+    scan_df_csv_dialect(path, &CsvDialect::with_delimiter(delimiter as char))
+}
+
+/// Scan a delimited text file laid out as `dialect`. Files that are not utf-8 are transcoded in memory first.
+pub fn scan_df_csv_dialect<P: AsRef<Path>>(
+    path: P,
+    dialect: &CsvDialect,
+) -> Result<LazyFrame, OxenError> {
+    let path = path.as_ref();
+    if dialect.is_utf8() {
+        LazyCsvReader::new(path)
+            .has_header(dialect.has_header)
+            .with_delimiter(dialect.delimiter_byte()?)
+            .with_quote_char(dialect.quote_byte()?)
+            .with_infer_schema_length(Some(DEFAULT_INFER_SCHEMA_LEN))
+            .with_encoding(CsvEncoding::LossyUtf8)
+            .finish()
+            .map_err(|err| OxenError::basic_str(format!("{CSV_READ_ERROR} {path:?}: {err}")))
+    } else {
+        let bytes = std::fs::read(path)?;
+        Ok(read_df_csv_bytes(&bytes, dialect)?.lazy())
+    }
+}
+
+/// Read the raw bytes of a delimited text file, decoding them from `dialect.encoding` to utf-8
+fn read_df_csv_bytes(bytes: &[u8], dialect: &CsvDialect) -> Result<DataFrame, OxenError> {
+    let encoding = encoding_for_label(&dialect.encoding)?;
+    let (text, _, had_errors) = encoding.decode(bytes);
+    if had_errors {
+        log::warn!(
+            "Some characters could not be decoded as {}",
+            dialect.encoding
+        );
+    }
+    try_infer_schema_csv(CsvReader::new(Cursor::new(text.as_bytes())), dialect)
+}
+
+fn encoding_for_label(label: &str) -> Result<&'static Encoding, OxenError> {
+    Encoding::for_label(label.as_bytes())
+        .ok_or_else(|| OxenError::basic_str(format!("Unknown text encoding: {label}")))
+}
+
+/// Detect the delimiter, quote char, header and encoding of a delimited text file from the start of it
+pub fn sniff_csv_dialect<P: AsRef<Path>>(path: P) -> Result<CsvDialect, OxenError> {
+    let mut sample: Vec<u8> = vec![];
+    File::open(path.as_ref())?
+        .take(CSV_SNIFF_SAMPLE_SIZE)
+        .read_to_end(&mut sample)?;
+    Ok(sniff_csv_dialect_bytes(&sample))
+}
+
+pub fn sniff_csv_dialect_bytes(sample: &[u8]) -> CsvDialect {
+    let encoding = detect_encoding(sample);
+    let (text, _, _) = encoding.decode(sample);
+    // The sample can end part way through a row, so only sniff whole lines
+    let text: &str = match text.rfind('\n') {
+        Some(end) => &text[..=end],
+        None => &text,
+    };
+
+    let mut dialect = CsvDialect {
+        encoding: encoding.name().to_string(),
+        ..CsvDialect::default()
+    };
+    match qsv_sniffer::Sniffer::new().sniff_reader(Cursor::new(text.as_bytes())) {
+        Ok(metadata) => {
+            dialect.delimiter = metadata.dialect.delimiter as char;
+            if let qsv_sniffer::metadata::Quote::Some(quote) = metadata.dialect.quote {
+                dialect.quote = Some(quote as char);
+            }
+            // The sniffer decides there is no header when every column is text,
+            // so only trust it if the first row has numbers like the data does
+            dialect.has_header = metadata.dialect.header.has_header_row
+                || !first_row_has_number(text, dialect.delimiter);
+        }
+        Err(err) => {
+            log::warn!("Error sniffing csv {:?}", err);
+        }
+    }
+    dialect
+}
+
+fn detect_encoding(sample: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _bom_len)) = Encoding::for_bom(sample) {
+        return encoding;
+    }
+
+    match std::str::from_utf8(sample) {
+        Ok(_) => encoding_rs::UTF_8,
+        // The sample may cut a multi byte character in half at the end
+        Err(err) if err.error_len().is_none() => encoding_rs::UTF_8,
+        // Latin-1 exports are decoded as its superset windows-1252
+        Err(_) => encoding_rs::WINDOWS_1252,
+    }
+}
+
+fn first_row_has_number(text: &str, delimiter: char) -> bool {
+    match text.lines().next() {
+        Some(line) => line
+            .split(delimiter)
+            .any(|field| field.trim().trim_matches('"').parse::<f64>().is_ok()),
+        None => false,
+    }
+}
+
+/// The dialect to read a csv with: `opts.dialect` if set or else sniffed, and `opts.delimiter` always wins
+fn csv_dialect_from_opts(
+    opts: &DFOpts,
+    sniff: impl FnOnce() -> Result<CsvDialect, OxenError>,
+) -> Result<CsvDialect, OxenError> {
+    let mut dialect = match &opts.dialect {
+        Some(dialect) => dialect.to_owned(),
+        None => sniff()?,
+    };
+    if let Some(delimiter) = delimiter_from_opts(opts)? {
+        dialect.delimiter = delimiter as char;
+    }
+    Ok(dialect)
 }
 
 pub fn read_df_json<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError> {
@@ -91,15 +221,11 @@ pub fn read_df_jsonl<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError> {
 }
 
 pub fn scan_df_jsonl<P: AsRef<Path>>(path: P) -> Result<LazyFrame, OxenError> {
-    Ok(LazyJsonLineReader::new(
-        path.as_ref()
-            .to_str()
-            .expect("Invalid json path.")
-            .to_string(),
-    )
-    .with_infer_schema_length(Some(DEFAULT_INFER_SCHEMA_LEN))
-    .finish()
-    .unwrap_or_else(|_| panic!("{}: {:?}", READ_ERROR, path.as_ref())))
+    let path = path.as_ref();
+    LazyJsonLineReader::new(path.to_string_lossy().to_string())
+        .with_infer_schema_length(Some(DEFAULT_INFER_SCHEMA_LEN))
+        .finish()
+        .map_err(|err| OxenError::basic_str(format!("{READ_ERROR} {path:?}: {err}")))
 }
 
 pub fn read_df_parquet<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError> {
@@ -111,11 +237,12 @@ pub fn read_df_parquet<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError>
 }
 
 pub fn scan_df_parquet<P: AsRef<Path>>(path: P) -> Result<LazyFrame, OxenError> {
-    Ok(LazyFrame::scan_parquet(&path, ScanArgsParquet::default())
-        .unwrap_or_else(|_| panic!("{}: {:?}", READ_ERROR, path.as_ref())))
+    let path = path.as_ref();
+    LazyFrame::scan_parquet(path, ScanArgsParquet::default())
+        .map_err(|err| OxenError::basic_str(format!("{READ_ERROR} {path:?}: {err}")))
 }
 
-fn read_df_arrow<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError> {
+pub fn read_df_arrow<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError> {
     let file = File::open(path.as_ref())?;
     Ok(IpcReader::new(file)
         .finish()
@@ -123,16 +250,96 @@ fn read_df_arrow<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError> {
 }
 
 fn scan_df_arrow<P: AsRef<Path>>(path: P) -> Result<LazyFrame, OxenError> {
-    Ok(LazyFrame::scan_ipc(&path, ScanArgsIpc::default())
-        .unwrap_or_else(|_| panic!("{}: {:?}", READ_ERROR, path.as_ref())))
+    let path = path.as_ref();
+    LazyFrame::scan_ipc(path, ScanArgsIpc::default())
+        .map_err(|err| OxenError::basic_str(format!("{READ_ERROR} {path:?}: {err}")))
+}
+
+/// Read an arrow file written in the IPC streaming format rather than the file format
+pub fn read_df_arrow_stream<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    IpcStreamReader::new(file)
+        .finish()
+        .map_err(|err| OxenError::basic_str(format!("{READ_ERROR} {path:?}: {err}")))
+}
+
+pub fn read_df_avro<P: AsRef<Path>>(path: P) -> Result<DataFrame, OxenError> {
+    let path = path.as_ref();
+    let file = File::open(path)?;
+    AvroReader::new(file)
+        .finish()
+        .map_err(|err| OxenError::basic_str(format!("{READ_ERROR} {path:?}: {err}")))
+}
+
+/// Read a gzip or zstd compressed tabular file, ex) `data.csv.gz`.
+/// There is no way to scan compressed data lazily, so it is decompressed into memory.
+pub fn read_df_compressed<P: AsRef<Path>>(path: P, opts: &DFOpts) -> Result<DataFrame, OxenError> {
+    let path = path.as_ref();
+    let bytes = util::fs::read_decompressed(path)?;
+    let extension = util::fs::tabular_ext(path);
+    log::debug!("read_df_compressed {path:?} -> {extension:?}");
+
+    let cursor = Cursor::new(bytes);
+    let result = match extension.as_deref() {
+        Some("ndjson") | Some("jsonl") => JsonLineReader::new(cursor)
+            .infer_schema_len(Some(DEFAULT_INFER_SCHEMA_LEN))
+            .finish(),
+        Some("csv") => {
+            let bytes = cursor.get_ref();
+            let dialect = csv_dialect_from_opts(opts, || Ok(sniff_csv_dialect_bytes(bytes)))?;
+            return read_df_csv_bytes(bytes, &dialect);
+        }
+        Some("tsv") => {
+            let bytes = cursor.get_ref();
+            let dialect = csv_dialect_from_opts(opts, || {
+                let mut dialect = sniff_csv_dialect_bytes(bytes);
+                dialect.delimiter = '\t';
+                Ok(dialect)
+            })?;
+            return read_df_csv_bytes(bytes, &dialect);
+        }
+        Some("parquet") => ParquetReader::new(cursor).finish(),
+        Some("arrow") => IpcReader::new(cursor).finish(),
+        Some("arrows") => IpcStreamReader::new(cursor).finish(),
+        Some("avro") => AvroReader::new(cursor).finish(),
+        _ => {
+            let err = format!("Unknown file type read_df_compressed {path:?} -> {extension:?}");
+            return Err(OxenError::basic_str(err));
+        }
+    };
+    result.map_err(|err| OxenError::basic_str(format!("{READ_ERROR} {path:?}: {err}")))
 }
 
 pub fn take(df: LazyFrame, indices: Vec<u32>) -> Result<DataFrame, OxenError> {
     let idx = IdxCa::new("idx", &indices);
-    let collected = df.collect().expect(COLLECT_ERROR);
+    let collected = df
+        .collect()
+        .map_err(|err| OxenError::basic_str(format!("{COLLECT_ERROR}: {err}")))?;
     // log::debug!("take indices {:?}", indices);
     // log::debug!("from df {:?}", collected);
-    Ok(collected.take(&idx).expect(TAKE_ERROR))
+    collected
+        .take(&idx)
+        .map_err(|err| OxenError::basic_str(format!("{TAKE_ERROR}: {err}")))
+}
+
+/// Collect only the rows at the sorted, unique `rows` of a lazy frame, in their original order.
+/// The row filter is pushed into the scan, so the rest of the file is never collected.
+pub fn take_rows(df: LazyFrame, rows: &[u32]) -> Result<DataFrame, OxenError> {
+    let row_nums = Series::new(TAKE_ROW_COL_NAME, rows);
+    let df = df
+        .with_row_count(TAKE_ROW_COL_NAME, None)
+        .filter(col(TAKE_ROW_COL_NAME).is_in(lit(row_nums)))
+        .drop_columns([TAKE_ROW_COL_NAME]);
+    let df = collect_df(df)?;
+    if df.height() != rows.len() {
+        return Err(OxenError::basic_str(format!(
+            "{TAKE_ERROR}: {} of {} rows are out of range",
+            rows.len() - df.height(),
+            rows.len()
+        )));
+    }
+    Ok(df)
 }
 
 pub fn add_col_lazy(
@@ -141,17 +348,10 @@ pub fn add_col_lazy(
     val: &str,
     dtype: &str,
 ) -> Result<LazyFrame, OxenError> {
-    let mut df = df.collect().expect(COLLECT_ERROR);
-
+    // A literal is broadcast to the height of the frame, so nothing needs to be collected
     let dtype = DataType::from_string(dtype).to_polars();
-
-    let column = Series::new_empty(name, &dtype);
-    let column = column
-        .extend_constant(val_from_str_and_dtype(val, &dtype), df.height())
-        .expect("Could not extend df");
-    df.with_column(column).expect(COLLECT_ERROR);
-    let df = df.lazy();
-    Ok(df)
+    let value = lit_from_str_and_dtype(val, &dtype)?.cast(dtype).alias(name);
+    Ok(df.with_column(value))
 }
 
 pub fn add_col(
@@ -171,12 +371,17 @@ pub fn add_col(
 }
 
 pub fn add_row(df: LazyFrame, data: String, opts: &DFOpts) -> Result<LazyFrame, OxenError> {
-    let df = df.collect().expect(COLLECT_ERROR);
-
-    let schema = crate::model::Schema::from_polars(&df.schema());
+    let schema = df
+        .schema()
+        .map_err(|err| OxenError::basic_str(format!("{READ_ERROR}: {err}")))?;
+    let schema = crate::model::Schema::from_polars(&schema);
     let new_row = parse_data_into_df(&data, &schema, opts.content_type.to_owned())?;
-    let df = df.vstack(&new_row).unwrap().lazy();
-    Ok(df)
+    concat_lazy(vec![df, new_row.lazy()])
+}
+
+fn concat_lazy(dfs: Vec<LazyFrame>) -> Result<LazyFrame, OxenError> {
+    concat(dfs, false, true)
+        .map_err(|err| OxenError::basic_str(format!("Could not stack DataFrames: {err}")))
 }
 
 pub fn parse_data_into_df(
@@ -203,12 +408,16 @@ pub fn parse_data_into_df(
             }
         }
         ContentType::Csv => {
-            let fields = schema.fields_to_csv();
+            // Appended rows are written in the same dialect as the file they are appended to
+            let dialect = schema.dialect.to_owned().unwrap_or_default();
+            let fields = schema.fields_names().join(&dialect.delimiter.to_string());
             let data = format!("{}\n{}", fields, data);
             let cursor = Cursor::new(data.as_bytes());
             let schema = schema.to_polars();
             match CsvReader::new(cursor)
                 .with_schema(Arc::new(schema))
+                .with_delimiter(dialect.delimiter_byte()?)
+                .with_quote_char(dialect.quote_byte()?)
                 .finish()
             {
                 Ok(df) => Ok(df),
@@ -255,104 +464,393 @@ fn val_from_str_and_dtype<'a>(s: &'a str, dtype: &polars::prelude::DataType) ->
     }
 }
 
-fn val_from_df_and_filter<'a>(df: &'a LazyFrame, filter: &'a DFFilterVal) -> AnyValue<'a> {
-    if let Some(value) = df
-        .schema()
-        .expect("Unable to get schema from data frame")
-        .iter_fields()
-        .find(|f| f.name == filter.field)
-    {
-        val_from_str_and_dtype(&filter.value, value.data_type())
-    } else {
-        log::error!("Unknown field {:?}", filter.field);
-        AnyValue::Null
+/// Parse a filter value into a literal of the column's dtype so comparisons are typed
+fn lit_from_str_and_dtype(
+    value: &str,
+    dtype: &polars::prelude::DataType,
+) -> Result<Expr, OxenError> {
+    let parse_err = || OxenError::parse_error(format!("Could not parse '{value}' as {dtype}"));
+    match dtype {
+        polars::prelude::DataType::Boolean => {
+            Ok(lit(value.parse::<bool>().map_err(|_| parse_err())?))
+        }
+        polars::prelude::DataType::UInt8
+        | polars::prelude::DataType::UInt16
+        | polars::prelude::DataType::UInt32
+        | polars::prelude::DataType::UInt64 => {
+            let val = value.parse::<u64>().map_err(|_| parse_err())?;
+            Ok(lit(val).cast(dtype.to_owned()))
+        }
+        polars::prelude::DataType::Int8
+        | polars::prelude::DataType::Int16
+        | polars::prelude::DataType::Int32
+        | polars::prelude::DataType::Int64 => {
+            let val = value.parse::<i64>().map_err(|_| parse_err())?;
+            Ok(lit(val).cast(dtype.to_owned()))
+        }
+        polars::prelude::DataType::Float32 | polars::prelude::DataType::Float64 => {
+            Ok(lit(value.parse::<f64>().map_err(|_| parse_err())?))
+        }
+        polars::prelude::DataType::Date => {
+            let date =
+                chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| parse_err())?;
+            let epoch = chrono::NaiveDate::from_ymd_opt(1970, 1, 1).unwrap();
+            let days = (date - epoch).num_days() as i32;
+            Ok(lit(days).cast(dtype.to_owned()))
+        }
+        polars::prelude::DataType::Datetime(unit, _) => {
+            let datetime = parse_datetime(value).ok_or_else(parse_err)?;
+            let timestamp = match unit {
+                TimeUnit::Nanoseconds => datetime.timestamp_nanos(),
+                TimeUnit::Microseconds => datetime.timestamp_micros(),
+                TimeUnit::Milliseconds => datetime.timestamp_millis(),
+            };
+            Ok(lit(timestamp).cast(dtype.to_owned()))
+        }
+        _ => Ok(lit(value)),
     }
 }
 
-fn lit_from_any(value: &AnyValue) -> Expr {
-    match value {
-        AnyValue::Boolean(val) => lit(*val),
-        AnyValue::Float64(val) => lit(*val),
-        AnyValue::Float32(val) => lit(*val),
-        AnyValue::Int64(val) => lit(*val),
-        AnyValue::Int32(val) => lit(*val),
-        AnyValue::Utf8(val) => lit(*val),
-        val => panic!("Unknown data type for [{}] to create literal", val),
+fn parse_datetime(value: &str) -> Option<chrono::NaiveDateTime> {
+    let formats = ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"];
+    for format in formats.iter() {
+        if let Ok(datetime) = chrono::NaiveDateTime::parse_from_str(value, format) {
+            return Some(datetime);
+        }
     }
+
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+}
+
+/// Evaluate a predicate on every value of a str column
+fn str_predicate<F>(column: Expr, predicate: F) -> Expr
+where
+    F: Fn(&str) -> bool + Send + Sync + 'static,
+{
+    column.map(
+        move |s| {
+            let ca = s.utf8()?;
+            let out: BooleanChunked = ca.into_iter().map(|v| v.map(&predicate)).collect();
+            Ok(Some(out.into_series()))
+        },
+        GetOutput::from_type(polars::prelude::DataType::Boolean),
+    )
 }
 
-fn filter_from_val(df: &LazyFrame, filter: &DFFilterVal) -> Expr {
-    let val = val_from_df_and_filter(df, filter);
-    let val = lit_from_any(&val);
+fn filter_from_val(schema: &Schema, filter: &DFFilterVal) -> Result<Expr, OxenError> {
+    let dtype = schema.get(&filter.field).ok_or_else(|| {
+        OxenError::parse_error(format!("Unknown field '{}' in filter", filter.field))
+    })?;
+    let column = col(&filter.field);
+
     match filter.op {
-        DFFilterOp::EQ => col(&filter.field).eq(val),
-        DFFilterOp::GT => col(&filter.field).gt(val),
-        DFFilterOp::LT => col(&filter.field).lt(val),
-        DFFilterOp::GTE => col(&filter.field).gt_eq(val),
-        DFFilterOp::LTE => col(&filter.field).lt_eq(val),
-        DFFilterOp::NEQ => col(&filter.field).neq(val),
+        DFFilterOp::IsNull => return Ok(column.is_null()),
+        DFFilterOp::IsNotNull => return Ok(column.is_not_null()),
+        DFFilterOp::Contains | DFFilterOp::Regex => {
+            if dtype != &polars::prelude::DataType::Utf8 {
+                return Err(OxenError::parse_error(format!(
+                    "'{}' requires a str column, '{}' is {dtype}",
+                    filter.op.as_str(),
+                    filter.field
+                )));
+            }
+        }
+        _ => {}
+    }
+
+    match filter.op {
+        DFFilterOp::EQ => Ok(column.eq(lit_from_str_and_dtype(&filter.value, dtype)?)),
+        DFFilterOp::GT => Ok(column.gt(lit_from_str_and_dtype(&filter.value, dtype)?)),
+        DFFilterOp::LT => Ok(column.lt(lit_from_str_and_dtype(&filter.value, dtype)?)),
+        DFFilterOp::GTE => Ok(column.gt_eq(lit_from_str_and_dtype(&filter.value, dtype)?)),
+        DFFilterOp::LTE => Ok(column.lt_eq(lit_from_str_and_dtype(&filter.value, dtype)?)),
+        DFFilterOp::NEQ => Ok(column.neq(lit_from_str_and_dtype(&filter.value, dtype)?)),
+        DFFilterOp::In => {
+            let mut expr = lit(false);
+            for value in filter.values.iter() {
+                let val = lit_from_str_and_dtype(value, dtype)?;
+                expr = expr.or(column.clone().eq(val));
+            }
+            Ok(expr)
+        }
+        DFFilterOp::Contains => {
+            let pattern = filter.value.to_owned();
+            Ok(str_predicate(column, move |s| s.contains(&pattern)))
+        }
+        DFFilterOp::Regex => {
+            let re = regex::Regex::new(&filter.value).map_err(|err| {
+                OxenError::parse_error(format!("Invalid regex '{}': {err}", filter.value))
+            })?;
+            Ok(str_predicate(column, move |s| re.is_match(s)))
+        }
+        DFFilterOp::IsNull | DFFilterOp::IsNotNull => unreachable!(),
     }
 }
 
-fn filter_df(df: LazyFrame, filter: &DFFilterExp) -> Result<LazyFrame, OxenError> {
-    log::debug!("Got filter: {:?}", filter);
-    let mut vals = filter.vals.iter();
-    let mut expr: Expr = filter_from_val(&df, vals.next().unwrap());
-    for op in &filter.logical_ops {
-        let chain_expr: Expr = filter_from_val(&df, vals.next().unwrap());
-
-        match op {
-            DFLogicalOp::AND => expr = expr.and(chain_expr),
-            DFLogicalOp::OR => expr = expr.or(chain_expr),
+fn filter_exp_to_expr(schema: &Schema, filter: &DFFilterExp) -> Result<Expr, OxenError> {
+    match filter {
+        DFFilterExp::Val(val) => filter_from_val(schema, val),
+        DFFilterExp::Not(exp) => Ok(filter_exp_to_expr(schema, exp)?.not()),
+        DFFilterExp::Logical(op, lhs, rhs) => {
+            let lhs = filter_exp_to_expr(schema, lhs)?;
+            let rhs = filter_exp_to_expr(schema, rhs)?;
+            match op {
+                DFLogicalOp::AND => Ok(lhs.and(rhs)),
+                DFLogicalOp::OR => Ok(lhs.or(rhs)),
+            }
         }
     }
+}
 
+fn filter_df(df: LazyFrame, filter: &DFFilterExp) -> Result<LazyFrame, OxenError> {
+    log::debug!("Got filter: {:?}", filter);
+    let schema = df
+        .schema()
+        .map_err(|err| OxenError::basic_str(format!("Could not get schema: {err}")))?;
+    let expr = filter_exp_to_expr(&schema, filter)?;
     Ok(df.filter(expr))
 }
 
 fn agg_fn_to_expr(agg: &DFAggFn) -> Result<Expr, OxenError> {
-    let col_name = &agg.args[0];
-    match DFAggFnType::from_fn_name(&agg.name) {
-        DFAggFnType::List => Ok(col(col_name).alias(&format!("list('{col_name}')"))),
-        DFAggFnType::Count => Ok(col(col_name).count().alias(&format!("count('{col_name}')"))),
-        DFAggFnType::NUnique => Ok(col(col_name)
-            .n_unique()
-            .alias(&format!("n_unique('{col_name}')"))),
-        DFAggFnType::Min => Ok(col(col_name).min().alias(&format!("min('{col_name}')"))),
-        DFAggFnType::Max => Ok(col(col_name).max().alias(&format!("max('{col_name}')"))),
-        DFAggFnType::ArgMin => Ok(col(col_name)
-            .arg_min()
-            .alias(&format!("arg_min('{col_name}')"))),
-        DFAggFnType::ArgMax => Ok(col(col_name).arg_max().alias(&format!("max('{col_name}')"))),
-        DFAggFnType::Mean => Ok(col(col_name).mean().alias(&format!("mean('{col_name}')"))),
-        DFAggFnType::Median => Ok(col(col_name)
-            .median()
-            .alias(&format!("median('{col_name}')"))),
-        DFAggFnType::Std => Ok(col(col_name).std(0).alias(&format!("std('{col_name}')"))),
-        DFAggFnType::Var => Ok(col(col_name).var(0).alias(&format!("var('{col_name}')"))),
-        DFAggFnType::First => Ok(col(col_name).first().alias(&format!("first('{col_name}')"))),
-        DFAggFnType::Last => Ok(col(col_name).last().alias(&format!("last('{col_name}')"))),
-        DFAggFnType::Head => Ok(col(col_name)
-            .head(Some(5))
-            .alias(&format!("head('{col_name}', 5)"))),
-        DFAggFnType::Tail => Ok(col(col_name)
-            .tail(Some(5))
-            .alias(&format!("tail('{col_name}', 5)"))),
-        DFAggFnType::Unknown => Err(OxenError::unknown_agg_fn(&agg.name)),
-    }
+    let arg = match agg.args.first() {
+        Some(arg) => column_exp_to_expr(arg)?,
+        None => return Err(OxenError::parse_error(&agg.alias)),
+    };
+    let expr = match agg.fn_type() {
+        DFAggFnType::List => arg,
+        DFAggFnType::Count => arg.count(),
+        DFAggFnType::NUnique => arg.n_unique(),
+        DFAggFnType::Min => arg.min(),
+        DFAggFnType::Max => arg.max(),
+        DFAggFnType::ArgMin => arg.arg_min(),
+        DFAggFnType::ArgMax => arg.arg_max(),
+        DFAggFnType::Sum => arg.sum(),
+        DFAggFnType::Mean => arg.mean(),
+        DFAggFnType::Median => arg.median(),
+        DFAggFnType::Quantile => match agg.param(1)? {
+            Some(quantile) if (0.0..=1.0).contains(&quantile) => {
+                arg.quantile(lit(quantile), QuantileInterpolOptions::Linear)
+            }
+            _ => {
+                return Err(OxenError::parse_error(format!(
+                    "{} needs a quantile between 0 and 1",
+                    agg.alias
+                )))
+            }
+        },
+        DFAggFnType::Std => arg.std(0),
+        DFAggFnType::Var => arg.var(0),
+        DFAggFnType::First => arg.first(),
+        DFAggFnType::Last => arg.last(),
+        DFAggFnType::Head => arg.head(Some(agg.num_rows_param(1, 5)?)),
+        DFAggFnType::Tail => arg.tail(Some(agg.num_rows_param(1, 5)?)),
+        DFAggFnType::ValueCounts => {
+            return Err(OxenError::basic_str(
+                "value_counts() cannot be combined with other aggregations",
+            ))
+        }
+        DFAggFnType::Unknown => return Err(OxenError::unknown_agg_fn(&agg.name)),
+    };
+    Ok(expr.alias(&agg.alias))
 }
 
 fn aggregate_df(df: LazyFrame, aggregation: &DFAggregation) -> Result<LazyFrame, OxenError> {
     log::debug!("Got agg: {:?}", aggregation);
 
     let group_by: Vec<Expr> = aggregation.group_by.iter().map(|c| col(c)).collect();
-    let agg: Vec<Expr> = aggregation
+    if let [agg] = aggregation.agg.as_slice() {
+        if agg.fn_type() == DFAggFnType::ValueCounts {
+            return value_counts_df(df, group_by, agg);
+        }
+    }
+
+    let agg = aggregation
         .agg
         .iter()
-        .map(|f| agg_fn_to_expr(f).expect("Err:"))
+        .map(agg_fn_to_expr)
+        .collect::<Result<Vec<Expr>, OxenError>>()?;
+
+    if group_by.is_empty() {
+        // Without groups every aggregation is a column of the result, so they must all be the same length
+        if aggregation.agg.len() > 1 {
+            if let Some(agg) = aggregation.agg.iter().find(|a| a.fn_type().returns_rows()) {
+                return Err(OxenError::parse_error(format!(
+                    "{} returns several rows without a group by, it cannot be combined with other aggregations",
+                    agg.alias
+                )));
+            }
+        }
+        return Ok(df.select(agg));
+    }
+    // Keep the groups in the order they first appear so pages are consistent
+    Ok(df.groupby_stable(group_by).agg(agg))
+}
+
+/// Count the rows with each distinct value within each group, the most common first
+fn value_counts_df(
+    df: LazyFrame,
+    mut group_by: Vec<Expr>,
+    agg: &DFAggFn,
+) -> Result<LazyFrame, OxenError> {
+    let value = match agg.args.first() {
+        Some(arg) => column_exp_to_expr(arg)?,
+        None => return Err(OxenError::parse_error(&agg.alias)),
+    };
+    group_by.push(value);
+    Ok(df.groupby(group_by).agg([count().alias(&agg.alias)]).sort(
+        &agg.alias,
+        SortOptions {
+            descending: true,
+            ..Default::default()
+        },
+    ))
+}
+
+fn column_exp_to_expr(exp: &DFColumnExp) -> Result<Expr, OxenError> {
+    match exp {
+        DFColumnExp::Col(name) => Ok(col(name)),
+        DFColumnExp::Str(val) => Ok(lit(val.to_owned())),
+        DFColumnExp::Int(val) => Ok(lit(*val)),
+        DFColumnExp::Float(val) => Ok(lit(*val)),
+        DFColumnExp::Bool(val) => Ok(lit(*val)),
+        DFColumnExp::Null => Ok(Expr::Literal(LiteralValue::Null)),
+        DFColumnExp::Neg(exp) => Ok(lit(0) - column_exp_to_expr(exp)?),
+        DFColumnExp::Arith(op, lhs, rhs) => {
+            let lhs = column_exp_to_expr(lhs)?;
+            let rhs = column_exp_to_expr(rhs)?;
+            match op {
+                DFArithOp::Add => Ok(lhs + rhs),
+                DFArithOp::Sub => Ok(lhs - rhs),
+                DFArithOp::Mul => Ok(lhs * rhs),
+                // Integer division would floor, so always divide as floats
+                DFArithOp::Div => Ok(lhs.cast(polars::prelude::DataType::Float64)
+                    / rhs.cast(polars::prelude::DataType::Float64)),
+                DFArithOp::Mod => Ok(lhs % rhs),
+            }
+        }
+        DFColumnExp::Fn(func, args) => column_fn_to_expr(func, args),
+    }
+}
+
+fn column_fn_to_expr(func: &DFColumnFn, args: &[DFColumnExp]) -> Result<Expr, OxenError> {
+    let arity_error = |expected: &str| {
+        OxenError::parse_error(format!(
+            "{}() takes {expected}, got {}",
+            func.as_str(),
+            args.len()
+        ))
+    };
+    match func {
+        DFColumnFn::Concat => {
+            // Adding strings concatenates them
+            let mut exprs = args.iter().map(|arg| {
+                column_exp_to_expr(arg).map(|e| e.cast(polars::prelude::DataType::Utf8))
+            });
+            let first = exprs
+                .next()
+                .ok_or_else(|| arity_error("at least 1 arg"))??;
+            exprs.try_fold(first, |acc, e| -> Result<Expr, OxenError> { Ok(acc + e?) })
+        }
+        DFColumnFn::Lower | DFColumnFn::Upper => {
+            if args.len() != 1 {
+                return Err(arity_error("1 arg"));
+            }
+            let arg = column_exp_to_expr(&args[0])?.cast(polars::prelude::DataType::Utf8);
+            match func {
+                DFColumnFn::Lower => Ok(arg.str().to_lowercase()),
+                _ => Ok(arg.str().to_uppercase()),
+            }
+        }
+        DFColumnFn::Cast => match args {
+            [arg, DFColumnExp::Str(dtype)] => {
+                Ok(column_exp_to_expr(arg)?.cast(dtype_from_str(dtype)?))
+            }
+            _ => Err(arity_error("a value and a quoted dtype")),
+        },
+        DFColumnFn::FillNull => match args {
+            [arg, default] => Ok(column_exp_to_expr(arg)?.fill_null(column_exp_to_expr(default)?)),
+            _ => Err(arity_error("2 args")),
+        },
+    }
+}
+
+fn dtype_from_str(dtype: &str) -> Result<polars::prelude::DataType, OxenError> {
+    match DataType::from_string(dtype) {
+        DataType::Unknown => Err(OxenError::parse_error(format!("Unknown dtype '{dtype}'"))),
+        dtype => Ok(dtype.to_polars()),
+    }
+}
+
+/// Convert columns to the given types, values that cannot be converted are an error when collected
+pub fn cast_df(df: LazyFrame, opts: &DFOpts) -> Result<LazyFrame, OxenError> {
+    let casts: Vec<Expr> = opts
+        .cast_col_vals()?
+        .into_iter()
+        .map(|cast| col(&cast.name).strict_cast(cast.dtype.to_polars()))
         .collect();
+    log::debug!("Got casts: {:?}", casts);
+    Ok(df.with_columns(casts))
+}
+
+/// Add or replace a column computed from the other columns in the row
+pub fn with_column_df(df: LazyFrame, with_col: &DFWithColumn) -> Result<LazyFrame, OxenError> {
+    log::debug!("Got with column: {:?}", with_col);
+    let expr = column_exp_to_expr(&with_col.exp)?.alias(&with_col.name);
+    Ok(df.with_column(expr))
+}
 
-    Ok(df.groupby(group_by).agg(agg))
+/// Spread the distinct values of one column out into their own columns, keeping the first value for each index
+pub fn pivot_df(df: LazyFrame, vals: &PivotColVals) -> Result<LazyFrame, OxenError> {
+    log::debug!("Got pivot: {:?}", vals);
+    // Pivoting needs every distinct value to know the output columns, so it cannot be lazy
+    let df = collect_df(df)?;
+    let pivoted = pivot::pivot_stable(
+        &df,
+        [&vals.values],
+        &vals.index,
+        [&vals.columns],
+        false,
+        Some(pivot::PivotAgg::First),
+        None,
+    )
+    .map_err(|err| OxenError::basic_str(format!("Could not pivot: {err}")))?;
+    Ok(pivoted.lazy())
+}
+
+/// Unpivot every column except the id columns into 'variable' and 'value' rows
+pub fn melt_df(df: LazyFrame, id_columns: Vec<String>) -> Result<LazyFrame, OxenError> {
+    log::debug!("Got melt: {:?}", id_columns);
+    let args = MeltArgs {
+        id_vars: id_columns.into_iter().map(|c| c.into()).collect(),
+        // Empty means every column that is not an id
+        value_vars: vec![],
+        ..Default::default()
+    };
+    Ok(df.melt(args))
+}
+
+/// Give each value of a list column its own row, repeating the other columns
+pub fn explode_df(df: LazyFrame, columns: Vec<String>) -> Result<LazyFrame, OxenError> {
+    log::debug!("Got explode: {:?}", columns);
+    let schema = df
+        .schema()
+        .map_err(|err| OxenError::basic_str(format!("Could not get schema: {err}")))?;
+    for column in columns.iter() {
+        let dtype = schema
+            .get(column)
+            .map(DataType::from_polars)
+            .ok_or_else(|| OxenError::basic_str(format!("Column '{column}' not found")))?;
+        if !matches!(dtype, DataType::List(_)) {
+            return Err(OxenError::basic_str(format!(
+                "Can only explode list columns, '{column}' is {}",
+                dtype.as_str()
+            )));
+        }
+    }
+    let cols: Vec<Expr> = columns.iter().map(|c| col(c)).collect();
+    Ok(df.explode(cols))
 }
 
 fn unique_df(df: LazyFrame, columns: Vec<String>) -> Result<LazyFrame, OxenError> {
@@ -360,40 +858,134 @@ fn unique_df(df: LazyFrame, columns: Vec<String>) -> Result<LazyFrame, OxenError
     Ok(df.unique(Some(columns), UniqueKeepStrategy::First))
 }
 
+/// Join the data frame with another tabular file on the columns in `opts.join_on`
+fn join_df(df: LazyFrame, path: &Path, opts: &DFOpts) -> Result<LazyFrame, OxenError> {
+    log::debug!(
+        "Got join: {:?} on {:?} how {:?}",
+        path,
+        opts.join_on,
+        opts.join_how
+    );
+    // Validate the options before reading the other file
+    let how = opts.join_type()?;
+    let on: Vec<Expr> = opts.join_columns()?.iter().map(|c| col(c)).collect();
+    let other = scan_df(path, &DFOpts::empty())?;
+    Ok(df
+        .join_builder()
+        .with(other)
+        .left_on(on.clone())
+        .right_on(on)
+        .how(how)
+        .finish())
+}
+
+/// Run a sql query where the data frame is the table `df`, and any extra tables are read from their paths
+fn sql_df(
+    df: LazyFrame,
+    query: &str,
+    tables: Vec<(String, PathBuf)>,
+) -> Result<LazyFrame, OxenError> {
+    log::debug!("Got sql: {:?} with tables {:?}", query, tables);
+    if let Some(name) = sql_table_function(query) {
+        return Err(OxenError::parse_error(format!(
+            "Table function '{name}' is not supported in sql queries, add the file as a table instead"
+        )));
+    }
+    let mut ctx = SQLContext::new();
+    ctx.register(constants::SQL_DF_TABLE_NAME, df);
+    for (name, path) in tables.iter() {
+        let table = scan_df(path, &DFOpts::empty())?;
+        ctx.register(name, table);
+    }
+
+    match ctx.execute(query) {
+        Ok(df) => Ok(df),
+        Err(err) => Err(OxenError::ParsingError(Box::new(
+            format!("Could not execute sql query: {err}").into(),
+        ))),
+    }
+}
+
+/// Polars sql can read any file on disk through these table functions, so queries may only use
+/// the registered tables
+const SQL_TABLE_FUNCTIONS: [&str; 4] = ["read_csv", "read_parquet", "read_ipc", "read_json"];
+
+/// The first table function referenced in the query, skipping string literals
+fn sql_table_function(query: &str) -> Option<&'static str> {
+    let mut in_literal = false;
+    let mut word = String::new();
+    for c in query.chars().chain(std::iter::once(' ')) {
+        if c == '\'' {
+            in_literal = !in_literal;
+            word.clear();
+        } else if !in_literal && (c.is_alphanumeric() || c == '_') {
+            word.push(c.to_ascii_lowercase());
+        } else {
+            if let Some(name) = SQL_TABLE_FUNCTIONS.into_iter().find(|name| *name == word) {
+                return Some(name);
+            }
+            word.clear();
+        }
+    }
+    None
+}
+
 pub fn transform(df: DataFrame, opts: DFOpts) -> Result<DataFrame, OxenError> {
-    let height = df.height();
-    transform_lazy(df.lazy(), height, opts)
+    let df = transform_lazy(df.lazy(), &opts)?;
+    collect_df(slice_lazy(df, &opts)?)
 }
 
-pub fn transform_lazy(
-    mut df: LazyFrame,
-    height: usize,
-    opts: DFOpts,
-) -> Result<DataFrame, OxenError> {
+/// Build the lazy query for every op in `opts` except the ones that slice out a set of rows.
+/// Nothing is read until the result is collected or sunk to a file.
+pub fn transform_lazy(mut df: LazyFrame, opts: &DFOpts) -> Result<LazyFrame, OxenError> {
     log::debug!("Got transform ops {:?}", opts);
 
     if let Some(vstack) = &opts.vstack {
         log::debug!("Got files to stack {:?}", vstack);
+        let mut dfs = vec![df];
         for path in vstack.iter() {
-            let opts = DFOpts::empty();
-            let new_df = read_df(path, opts).expect(READ_ERROR);
-            df = df
-                .collect()
-                .expect(COLLECT_ERROR)
-                .vstack(&new_df)
-                .unwrap()
-                .lazy();
+            dfs.push(scan_df(path, &DFOpts::empty())?);
         }
+        df = concat_lazy(dfs)?;
     }
 
     if let Some(data) = &opts.add_row {
-        df = add_row(df, data.to_owned(), &opts)?;
+        df = add_row(df, data.to_owned(), opts)?;
     }
 
     if let Some(col_vals) = opts.add_col_vals() {
         df = add_col_lazy(df, &col_vals.name, &col_vals.value, &col_vals.dtype)?;
     }
 
+    if let Some(path) = &opts.join {
+        df = join_df(df, path, opts)?;
+    }
+
+    if let Some(query) = &opts.sql {
+        df = sql_df(df, query, opts.sql_table_paths()?)?;
+    }
+
+    if opts.cast.is_some() {
+        df = cast_df(df, opts)?;
+    }
+
+    // Each computed column can use the ones before it
+    for with_col in opts.get_with_columns()?.iter() {
+        df = with_column_df(df, with_col)?;
+    }
+
+    if let Some(columns) = opts.explode_columns() {
+        df = explode_df(df, columns)?;
+    }
+
+    if let Some(columns) = opts.melt_id_columns() {
+        df = melt_df(df, columns)?;
+    }
+
+    if let Some(vals) = opts.pivot_col_vals()? {
+        df = pivot_df(df, &vals)?;
+    }
+
     if let Some(columns) = opts.columns_names() {
         if !columns.is_empty() {
             let cols = columns.iter().map(|c| col(c)).collect::<Vec<Expr>>();
@@ -401,15 +993,8 @@ pub fn transform_lazy(
         }
     }
 
-    match opts.get_filter() {
-        Ok(filter) => {
-            if let Some(filter) = filter {
-                df = filter_df(df, &filter)?;
-            }
-        }
-        Err(err) => {
-            log::error!("Could not parse filter: {err}");
-        }
+    if let Some(filter) = opts.get_filter()? {
+        df = filter_df(df, &filter)?;
     }
 
     if let Some(columns) = opts.unique_columns() {
@@ -421,6 +1006,7 @@ pub fn transform_lazy(
     }
 
     if opts.should_randomize {
+        let height = count_rows(df.clone())?;
         let mut rand_indices: Vec<u32> = (0..height as u32).collect();
         rand_indices.shuffle(&mut thread_rng());
         df = take(df, rand_indices)?.lazy();
@@ -434,61 +1020,82 @@ pub fn transform_lazy(
         df = df.reverse();
     }
 
-    // These ops should be the last ops since they depends on order
+    // Depends on order, so comes after sorting
     if let Some(indices) = opts.take_indices() {
-        df = take(df, indices).unwrap().lazy();
+        df = take(df, indices)?.lazy();
     }
 
-    // Maybe slice it up
-    df = slice(df, &opts);
-    df = head(df, &opts);
-    df = tail(df, height, &opts);
+    Ok(df)
+}
+
+/// Apply the ops in `opts` that slice out a set of rows, these should be the last ops since they depend on order.
+/// Kept separate from `transform_lazy` so callers can count the full result before paging it.
+pub fn slice_lazy(df: LazyFrame, opts: &DFOpts) -> Result<LazyFrame, OxenError> {
+    let mut df = slice(df, opts)?;
+    df = head(df, opts);
+    df = tail(df, opts);
 
     if let Some(item) = opts.column_at() {
-        let full_df = df.collect().unwrap();
-        let value = full_df.column(&item.col).unwrap().get(item.index).unwrap();
-        let s1 = Series::new("", &[value]);
-        let df = DataFrame::new(vec![s1]).unwrap();
-        return Ok(df);
+        df = df.slice(item.index as i64, 1).select([col(&item.col)]);
     }
 
-    Ok(df.collect().expect(COLLECT_ERROR))
+    Ok(df)
+}
+
+/// Collect a lazy query, using the streaming engine so that data larger than memory
+/// can be filtered, selected and aggregated in batches
+pub fn collect_df(df: LazyFrame) -> Result<DataFrame, OxenError> {
+    df.with_streaming(true)
+        .collect()
+        .map_err(|err| OxenError::basic_str(format!("{COLLECT_ERROR}: {err}")))
+}
+
+/// Count the rows a lazy query would return without keeping them in memory
+pub fn count_rows(df: LazyFrame) -> Result<usize, OxenError> {
+    let counted = collect_df(df.select([count()]))?;
+    let count = counted
+        .get_columns()
+        .first()
+        .and_then(|series| series.get(0).ok())
+        .and_then(|value| value.extract::<usize>())
+        .unwrap_or(0);
+    Ok(count)
 }
 
 fn head(df: LazyFrame, opts: &DFOpts) -> LazyFrame {
     if let Some(head) = opts.head {
-        df.slice(0, head as u32)
+        df.limit(head as IdxSize)
     } else {
         df
     }
 }
 
-fn tail(df: LazyFrame, height: usize, opts: &DFOpts) -> LazyFrame {
+fn tail(df: LazyFrame, opts: &DFOpts) -> LazyFrame {
     if let Some(tail) = opts.tail {
-        let start = (height - tail) as i64;
-        let end = (height - 1) as u32;
-        df.slice(start, end)
+        df.tail(tail as IdxSize)
     } else {
         df
     }
 }
 
-fn slice(df: LazyFrame, opts: &DFOpts) -> LazyFrame {
+fn slice(df: LazyFrame, opts: &DFOpts) -> Result<LazyFrame, OxenError> {
     log::debug!("SLICE {:?}", opts.slice);
     if opts.page.is_some() || opts.page_size.is_some() {
         let page = opts.page.unwrap_or(constants::DEFAULT_PAGE_NUM);
         let page_size = opts.page_size.unwrap_or(constants::DEFAULT_PAGE_SIZE);
-        let start = (page - 1) * page_size;
-        df.slice(start as i64, page_size as u32)
+        let start = (page.max(1) - 1) * page_size;
+        Ok(df.slice(start as i64, page_size as IdxSize))
     } else if let Some((start, end)) = opts.slice_indices() {
         log::debug!("SLICE with indices {:?}..{:?}", start, end);
         if start >= end {
-            panic!("Slice error: Start must be greater than end.");
+            return Err(OxenError::parse_error(
+                "Slice error: Start must be less than end.",
+            ));
         }
         let len = end - start;
-        df.slice(start, len as u32)
+        Ok(df.slice(start, len as IdxSize))
     } else {
-        df
+        Ok(df)
     }
 }
 
@@ -585,60 +1192,39 @@ pub fn df_hash_rows(df: DataFrame) -> Result<DataFrame, OxenError> {
     Ok(df)
 }
 
-fn sniff_db_csv_delimiter(path: impl AsRef<Path>, opts: &DFOpts) -> Result<u8, OxenError> {
-    if let Some(delimiter) = &opts.delimiter {
-        if delimiter.len() != 1 {
-            return Err(OxenError::basic_str("Delimiter must be a single character"));
-        }
-        return Ok(delimiter.as_bytes()[0]);
-    }
-
-    match qsv_sniffer::Sniffer::new().sniff_path(path) {
-        Ok(metadata) => Ok(metadata.dialect.delimiter),
-        Err(err) => {
-            let err = format!("Error sniffing csv {:?}", err);
-            log::warn!("{}", err);
-            Ok(b',')
+fn delimiter_from_opts(opts: &DFOpts) -> Result<Option<u8>, OxenError> {
+    match &opts.delimiter {
+        Some(delimiter) if delimiter.len() != 1 => {
+            Err(OxenError::basic_str("Delimiter must be a single character"))
         }
+        Some(delimiter) => Ok(Some(delimiter.as_bytes()[0])),
+        None => Ok(None),
     }
 }
 
 pub fn read_df<P: AsRef<Path>>(path: P, opts: DFOpts) -> Result<DataFrame, OxenError> {
-    let path = path.as_ref();
-    if !path.exists() {
-        return Err(OxenError::entry_does_not_exist(path));
-    }
-
-    let extension = path.extension().and_then(OsStr::to_str);
-    let err = format!("Unknown file type read_df {path:?} -> {extension:?}");
-
-    let df = match extension {
-        Some(extension) => match extension {
-            "ndjson" => read_df_jsonl(path),
-            "jsonl" => read_df_jsonl(path),
-            "json" => read_df_json(path),
-            "csv" => {
-                let delimiter = sniff_db_csv_delimiter(path, &opts)?;
-                read_df_csv(path, delimiter)
-            }
-            "tsv" => read_df_csv(path, b'\t'),
-            "parquet" => read_df_parquet(path),
-            "arrow" => read_df_arrow(path),
-            _ => Err(OxenError::basic_str(err)),
-        },
-        None => Err(OxenError::basic_str(err)),
-    }?;
+    let df = scan_df(path, &opts)?;
 
     if opts.has_transform() {
-        let df = transform(df, opts)?;
-        Ok(df)
+        let df = transform_lazy(df, &opts)?;
+        collect_df(slice_lazy(df, &opts)?)
     } else {
-        Ok(df)
+        collect_df(df)
     }
 }
 
+/// Lazily scan a tabular file, no rows are read until the result is collected or sunk.
+/// Formats without a lazy reader (json, avro, arrow streams and compressed files) are read into memory first.
 pub fn scan_df<P: AsRef<Path>>(path: P, opts: &DFOpts) -> Result<LazyFrame, OxenError> {
     let input_path = path.as_ref();
+    if !input_path.exists() {
+        return Err(OxenError::entry_does_not_exist(input_path));
+    }
+
+    if util::fs::is_compressed(input_path) {
+        return Ok(read_df_compressed(input_path, opts)?.lazy());
+    }
+
     let extension = input_path.extension().and_then(OsStr::to_str);
     let err = format!("Unknown file type scan_df {input_path:?} {extension:?}");
 
@@ -646,19 +1232,36 @@ pub fn scan_df<P: AsRef<Path>>(path: P, opts: &DFOpts) -> Result<LazyFrame, Oxen
         Some(extension) => match extension {
             "ndjson" => scan_df_jsonl(path),
             "jsonl" => scan_df_jsonl(path),
+            "json" => Ok(read_df_json(path)?.lazy()),
             "csv" => {
-                let delimiter = sniff_db_csv_delimiter(&path, opts)?;
-                scan_df_csv(path, delimiter)
+                let dialect = csv_dialect_from_opts(opts, || sniff_csv_dialect(input_path))?;
+                scan_df_csv_dialect(path, &dialect)
+            }
+            "tsv" => {
+                let dialect = csv_dialect_from_opts(opts, || {
+                    let mut dialect = sniff_csv_dialect(input_path)?;
+                    dialect.delimiter = '\t';
+                    Ok(dialect)
+                })?;
+                scan_df_csv_dialect(path, &dialect)
             }
-            "tsv" => scan_df_csv(path, b'\t'),
             "parquet" => scan_df_parquet(path),
             "arrow" => scan_df_arrow(path),
+            "arrows" => Ok(read_df_arrow_stream(path)?.lazy()),
+            "avro" => Ok(read_df_avro(path)?.lazy()),
             _ => Err(OxenError::basic_str(err)),
         },
         None => Err(OxenError::basic_str(err)),
     }
 }
 
+/// Scan a tabular file and apply all the ops in `opts`, including slicing
+pub fn scan_df_transformed<P: AsRef<Path>>(path: P, opts: &DFOpts) -> Result<LazyFrame, OxenError> {
+    let df = scan_df(path, opts)?;
+    let df = transform_lazy(df, opts)?;
+    slice_lazy(df, opts)
+}
+
 pub fn write_df_json<P: AsRef<Path>>(df: &mut DataFrame, output: P) -> Result<(), OxenError> {
     let output = output.as_ref();
     let error_str = format!("Could not save tabular data to path: {output:?}");
@@ -693,14 +1296,84 @@ pub fn write_df_csv<P: AsRef<Path>>(
     let error_str = format!("Could not save tabular data to path: {output:?}");
     log::debug!("Writing file {:?}", output);
     let f = std::fs::File::create(output).unwrap();
+    let mut df = stringify_list_columns(df)?;
     CsvWriter::new(f)
         .has_header(true)
         .with_delimiter(delimiter)
-        .finish(df)
+        .finish(&mut df)
         .expect(&error_str);
     Ok(())
 }
 
+/// Write a csv laid out as `dialect`, encoding it back to the original text encoding if possible
+pub fn write_df_csv_dialect<P: AsRef<Path>>(
+    df: &mut DataFrame,
+    output: P,
+    dialect: &CsvDialect,
+) -> Result<(), OxenError> {
+    let output = output.as_ref();
+    log::debug!("Writing file {:?} with dialect {}", output, dialect);
+    let mut df = stringify_list_columns(df)?;
+    let mut buffer: Vec<u8> = vec![];
+    CsvWriter::new(&mut buffer)
+        .has_header(dialect.has_header)
+        .with_delimiter(dialect.delimiter_byte()?)
+        .with_quoting_char(dialect.quote_byte()?.unwrap_or(b'"'))
+        .finish(&mut df)
+        .map_err(|err| {
+            OxenError::basic_str(format!(
+                "Could not save tabular data to path {output:?}: {err}"
+            ))
+        })?;
+
+    let bytes = if dialect.is_utf8() {
+        buffer
+    } else {
+        let encoding = encoding_for_label(&dialect.encoding)?;
+        let text = String::from_utf8_lossy(&buffer);
+        let (encoded, _, _) = encoding.encode(&text);
+        encoded.into_owned()
+    };
+    std::fs::write(output, bytes)?;
+    Ok(())
+}
+
+/// Csv has no nested types, so list columns are written as json arrays
+fn stringify_list_columns(df: &DataFrame) -> Result<DataFrame, OxenError> {
+    let mut result = df.clone();
+    for series in df.get_columns() {
+        if !matches!(series.dtype(), polars::prelude::DataType::List(_)) {
+            continue;
+        }
+        let strs: Utf8Chunked = series
+            .iter()
+            .map(|val| match val {
+                AnyValue::List(vals) => Some(list_to_json_str(&vals)),
+                _ => None,
+            })
+            .collect();
+        let mut strs = strs.into_series();
+        strs.rename(series.name());
+        result
+            .with_column(strs)
+            .map_err(|err| OxenError::basic_str(format!("{COLLECT_ERROR}: {err}")))?;
+    }
+    Ok(result)
+}
+
+fn list_to_json_str(vals: &Series) -> String {
+    let items: Vec<String> = vals
+        .iter()
+        .map(|val| match val {
+            AnyValue::Null => String::from("null"),
+            AnyValue::Utf8(val) => serde_json::Value::String(val.to_string()).to_string(),
+            AnyValue::List(vals) => list_to_json_str(&vals),
+            val => val.to_string(),
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
 pub fn write_df_parquet<P: AsRef<Path>>(df: &mut DataFrame, output: P) -> Result<(), OxenError> {
     let output = output.as_ref();
     let error_str = format!("Could not save tabular data to path: {output:?}");
@@ -719,6 +1392,61 @@ pub fn write_df_arrow<P: AsRef<Path>>(df: &mut DataFrame, output: P) -> Result<(
     Ok(())
 }
 
+pub fn write_df_arrow_stream<P: AsRef<Path>>(
+    df: &mut DataFrame,
+    output: P,
+) -> Result<(), OxenError> {
+    let output = output.as_ref();
+    log::debug!("Writing file {:?}", output);
+    let f = std::fs::File::create(output)?;
+    IpcStreamWriter::new(f).finish(df).map_err(|err| {
+        OxenError::basic_str(format!(
+            "Could not save tabular data to path: {output:?}: {err}"
+        ))
+    })
+}
+
+pub fn write_df_avro<P: AsRef<Path>>(df: &mut DataFrame, output: P) -> Result<(), OxenError> {
+    let output = output.as_ref();
+    log::debug!("Writing file {:?}", output);
+    let f = std::fs::File::create(output)?;
+    AvroWriter::new(f).finish(df).map_err(|err| {
+        OxenError::basic_str(format!(
+            "Could not save tabular data to path: {output:?}: {err}"
+        ))
+    })
+}
+
+/// Serialize a data frame to send over http, as arrow ipc stream, parquet, csv or json
+pub fn write_df_to_bytes(
+    df: &mut DataFrame,
+    content_type: &ContentType,
+) -> Result<Vec<u8>, OxenError> {
+    let mut buffer: Vec<u8> = vec![];
+    let result = match content_type {
+        ContentType::ArrowStream => IpcStreamWriter::new(&mut buffer).finish(df),
+        ContentType::Parquet => ParquetWriter::new(&mut buffer).finish(df).map(|_| ()),
+        ContentType::Csv => {
+            let mut df = stringify_list_columns(df)?;
+            CsvWriter::new(&mut buffer).has_header(true).finish(&mut df)
+        }
+        ContentType::Json => JsonWriter::new(&mut buffer)
+            .with_json_format(JsonFormat::Json)
+            .finish(df),
+        ContentType::Text => {
+            return Err(OxenError::basic_str(format!(
+                "Cannot write a data frame as {content_type:?}"
+            )))
+        }
+    };
+    result.map_err(|err| {
+        OxenError::basic_str(format!(
+            "Could not write data frame as {content_type:?}: {err}"
+        ))
+    })?;
+    Ok(buffer)
+}
+
 pub fn write_df<P: AsRef<Path>>(df: &mut DataFrame, path: P) -> Result<(), OxenError> {
     let path = path.as_ref();
     let extension = path.extension().and_then(OsStr::to_str);
@@ -733,12 +1461,43 @@ pub fn write_df<P: AsRef<Path>>(df: &mut DataFrame, path: P) -> Result<(), OxenE
             "csv" => write_df_csv(df, path, b','),
             "parquet" => write_df_parquet(df, path),
             "arrow" => write_df_arrow(df, path),
+            "arrows" => write_df_arrow_stream(df, path),
+            "avro" => write_df_avro(df, path),
             _ => Err(OxenError::basic_str(err)),
         },
         None => Err(OxenError::basic_str(err)),
     }
 }
 
+/// Write the result of a lazy query to `path`. Parquet and arrow are streamed to disk in batches,
+/// other formats fall back to collecting with the streaming engine before writing.
+pub fn sink_df<P: AsRef<Path>>(df: LazyFrame, path: P) -> Result<(), OxenError> {
+    let path = path.as_ref();
+    let extension = path.extension().and_then(OsStr::to_str);
+    let result = match extension {
+        Some("parquet") => df
+            .clone()
+            .sink_parquet(path.to_path_buf(), ParquetWriteOptions::default()),
+        Some("arrow") => df
+            .clone()
+            .sink_ipc(path.to_path_buf(), IpcWriterOptions::default()),
+        _ => {
+            let mut df = collect_df(df)?;
+            return write_df(&mut df, path);
+        }
+    };
+
+    match result {
+        Ok(_) => Ok(()),
+        Err(err) => {
+            // Not every op is supported by the streaming sinks yet
+            log::debug!("Could not sink to {path:?}, collecting instead: {err}");
+            let mut df = collect_df(df)?;
+            write_df(&mut df, path)
+        }
+    }
+}
+
 pub fn copy_df<P: AsRef<Path>>(input: P, output: P) -> Result<DataFrame, OxenError> {
     let mut df = read_df(input, DFOpts::empty())?;
     write_df_arrow(&mut df, output)?;
@@ -757,7 +1516,12 @@ pub fn copy_df_add_row_num<P: AsRef<Path>>(input: P, output: P) -> Result<DataFr
 }
 
 pub fn show_path<P: AsRef<Path>>(input: P, opts: DFOpts) -> Result<DataFrame, OxenError> {
-    let df = read_df(input, opts.clone())?;
+    show_df(scan_df_transformed(input, &opts)?, &opts)
+}
+
+/// Collect and print a query that has already been transformed with `opts`
+pub fn show_df(df: LazyFrame, opts: &DFOpts) -> Result<DataFrame, OxenError> {
+    let df = collect_df(df)?;
     if opts.column_at().is_some() {
         for val in df.get(0).unwrap() {
             match val {
@@ -786,40 +1550,59 @@ pub fn schema_to_string<P: AsRef<Path>>(
     let df = scan_df(input, opts)?;
     let schema = df.schema().expect("Could not get schema");
 
+    let fields: Vec<Field> = schema
+        .iter_fields()
+        .map(|f| Field::from_polars(&f))
+        .collect();
     if flatten {
-        let mut result = String::new();
-        for (i, field) in schema.iter_fields().enumerate() {
-            if i != 0 {
-                result = format!("{result},");
-            }
-
-            let dtype = DataType::from_polars(field.data_type());
-            let field_str = field.name().to_string();
-            let dtype_str = String::from(DataType::as_str(&dtype));
-            result = format!("{result}{field_str}:{dtype_str}");
+        // Nested struct fields are listed by their path, "bbox.min_x:f64"
+        let mut flattened: Vec<Field> = vec![];
+        for field in fields.iter() {
+            flatten_struct_fields("", field, &mut flattened);
         }
+        let result = flattened
+            .iter()
+            .map(|f| format!("{}:{}", f.name, f.dtype))
+            .collect::<Vec<String>>()
+            .join(",");
 
         Ok(result)
     } else {
         let mut table = Table::new();
         table.set_header(vec!["column", "dtype"]);
 
-        for field in schema.iter_fields() {
-            let dtype = DataType::from_polars(field.data_type());
-            let field_str = field.name().to_string();
-            let dtype_str = String::from(DataType::as_str(&dtype));
-            table.add_row(vec![field_str, dtype_str]);
+        for field in fields {
+            table.add_row(vec![field.name, field.dtype]);
         }
 
         Ok(format!("{table}"))
     }
 }
 
+fn flatten_struct_fields(prefix: &str, field: &Field, flattened: &mut Vec<Field>) {
+    let name = if prefix.is_empty() {
+        field.name.to_owned()
+    } else {
+        format!("{prefix}.{}", field.name)
+    };
+    match field.data_type() {
+        DataType::Struct(children) => {
+            for child in children.iter() {
+                flatten_struct_fields(&name, child, flattened);
+            }
+        }
+        _ => flattened.push(Field::new(name, &field.dtype)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::core::df::{filter, tabular};
+    use crate::model::{CsvDialect, CsvDialectOverride};
+    use crate::test;
     use crate::{error::OxenError, opts::DFOpts};
     use polars::prelude::*;
+    use std::io::Write;
 
     #[test]
     fn test_filter_single_expr() -> Result<(), OxenError> {
@@ -914,6 +1697,56 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_filter_parens_in_contains_regex_null() -> Result<(), OxenError> {
+        let df = df!(
+            "file" => &["train/0.jpg", "train/1.jpg", "test/2.jpg", "test/3.jpg"],
+            "label" => &["cat", "dog", "hot dog", "dog"],
+            "score" => &[0.1, 0.9, 0.5, 0.7],
+            "note" => &[Some("blurry"), None, None, Some("good boy")],
+        )
+        .unwrap();
+
+        let query =
+            Some("(label in [dog, 'hot dog'] && score >= 0.6) || note contains blur".to_string());
+        let filter = filter::parse(query)?.unwrap();
+        let filtered_df = tabular::filter_df(df.clone().lazy(), &filter)?
+            .collect()
+            .unwrap();
+        assert_eq!(filtered_df.height(), 3);
+
+        let query = Some("file =~ ^test/ && not note is null".to_string());
+        let filter = filter::parse(query)?.unwrap();
+        let filtered_df = tabular::filter_df(df.lazy(), &filter)?.collect().unwrap();
+        assert_eq!(filtered_df.height(), 1);
+        assert_eq!(
+            filtered_df.column("file").unwrap().get(0).unwrap(),
+            AnyValue::Utf8("test/3.jpg")
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_filter_invalid_value_or_field_is_error() -> Result<(), OxenError> {
+        let df = df!(
+            "label" => &["cat", "dog"],
+            "score" => &[0.1, 0.9],
+        )
+        .unwrap();
+
+        let filter = filter::parse(Some("score > high".to_string()))?.unwrap();
+        assert!(tabular::filter_df(df.clone().lazy(), &filter).is_err());
+
+        let filter = filter::parse(Some("missing == 1".to_string()))?.unwrap();
+        assert!(tabular::filter_df(df.clone().lazy(), &filter).is_err());
+
+        let filter = filter::parse(Some("score contains 1".to_string()))?.unwrap();
+        assert!(tabular::filter_df(df.lazy(), &filter).is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_unique_single_field() -> Result<(), OxenError> {
         let fields = "label";
@@ -1051,4 +1884,521 @@ mod tests {
         assert_eq!(df.width(), 2);
         Ok(())
     }
+
+    #[test]
+    fn test_transform_sql_group_by() -> Result<(), OxenError> {
+        let df = df!(
+            "image" => &["0000.jpg", "0001.jpg", "0002.jpg"],
+            "label" => &["dog", "dog", "cat"],
+        )
+        .unwrap();
+
+        let mut opts = DFOpts::empty();
+        opts.sql = Some(String::from(
+            "SELECT label, count(image) AS num FROM df GROUP BY label ORDER BY label",
+        ));
+        let df = tabular::transform(df, opts)?;
+
+        assert_eq!(df.height(), 2);
+        assert_eq!(
+            df.column("label").unwrap().get(0).unwrap(),
+            AnyValue::Utf8("cat")
+        );
+        assert_eq!(
+            df.column("num").unwrap().get(1).unwrap(),
+            AnyValue::UInt32(2)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_sql_join_named_table() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let labels_path = dir.join("labels.csv");
+            test::write_txt_file_to_path(
+                &labels_path,
+                "label,is_animal\ndog,true\nperson,false\n",
+            )?;
+
+            let df = df!(
+                "image" => &["0000.jpg", "0001.jpg", "0002.jpg"],
+                "label" => &["dog", "person", "dog"],
+            )
+            .unwrap();
+
+            let mut opts = DFOpts::empty();
+            opts.sql = Some(String::from(
+                "SELECT df.image FROM df JOIN labels ON df.label = labels.label WHERE labels.is_animal = true",
+            ));
+            opts.sql_tables = Some(vec![format!("labels={}", labels_path.to_string_lossy())]);
+            let df = tabular::transform(df, opts)?;
+
+            assert_eq!(df.height(), 2);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_transform_left_join() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let labels_path = dir.join("labels.csv");
+            test::write_txt_file_to_path(&labels_path, "file,label\n0000.jpg,cat\n0001.jpg,dog\n")?;
+
+            let df = df!(
+                "file" => &["0000.jpg", "0001.jpg", "0002.jpg"],
+                "width" => &[100, 200, 300],
+            )
+            .unwrap();
+
+            let mut opts = DFOpts::empty();
+            opts.join = Some(labels_path);
+            opts.join_on = Some(String::from("file"));
+            opts.join_how = Some(String::from("left"));
+            let df = tabular::transform(df, opts)?;
+
+            assert_eq!(df.height(), 3);
+            assert_eq!(df.width(), 3);
+            assert_eq!(df.column("label").unwrap().null_count(), 1);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_scan_filter_and_sink_parquet() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let input = dir.join("labels.csv");
+            let output = dir.join("dogs.parquet");
+            test::write_txt_file_to_path(
+                &input,
+                "file,label\n0000.jpg,cat\n0001.jpg,dog\n0002.jpg,dog\n",
+            )?;
+
+            let mut opts = DFOpts::empty();
+            opts.filter = Some(String::from("label == dog"));
+            let df = tabular::scan_df_transformed(&input, &opts)?;
+            assert_eq!(tabular::count_rows(df.clone())?, 2);
+
+            tabular::sink_df(df, &output)?;
+            let df = tabular::read_df(&output, DFOpts::empty())?;
+            assert_eq!(df.height(), 2);
+            assert_eq!(df.width(), 2);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_schema_of_parquet_with_nested_struct() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let output = dir.join("boxes.parquet");
+            let bbox = StructChunked::new(
+                "bbox",
+                &[
+                    Series::new("min_x", &[1.5, 2.5]),
+                    Series::new("min_y", &[3.5, 4.5]),
+                ],
+            )
+            .unwrap()
+            .into_series();
+            let mut df =
+                DataFrame::new(vec![Series::new("file", &["a.jpg", "b.jpg"]), bbox]).unwrap();
+            tabular::write_df_parquet(&mut df, &output)?;
+
+            let flattened = tabular::schema_to_string(&output, true, &DFOpts::empty())?;
+            assert_eq!(flattened, "file:str,bbox.min_x:f64,bbox.min_y:f64");
+
+            // The nested fields round trip through the saved schema
+            let df = tabular::read_df(&output, DFOpts::empty())?;
+            let schema = crate::model::Schema::from_polars(&df.schema());
+            assert_eq!(schema.fields[1].dtype, "struct[min_x: f64, min_y: f64]");
+            assert_eq!(schema.to_polars(), df.schema());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_read_gzip_and_zstd_csv() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let data = "file,label\n0000.jpg,cat\n0001.jpg,dog\n";
+
+            let gz_path = dir.join("labels.csv.gz");
+            let mut encoder = flate2::write::GzEncoder::new(
+                std::fs::File::create(&gz_path)?,
+                flate2::Compression::default(),
+            );
+            encoder.write_all(data.as_bytes())?;
+            encoder.finish()?;
+
+            let zst_path = dir.join("labels.csv.zst");
+            let compressed = zstd::stream::encode_all(data.as_bytes(), 0)?;
+            std::fs::write(&zst_path, compressed)?;
+
+            for path in [gz_path, zst_path] {
+                let df = tabular::read_df(&path, DFOpts::empty())?;
+                assert_eq!(df.height(), 2);
+                assert_eq!(df.width(), 2);
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_write_and_read_avro_and_arrow_stream() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let df = df!(
+                "file" => &["0000.jpg", "0001.jpg", "0002.jpg"],
+                "label" => &["cat", "dog", "cat"],
+                "score" => &[0.5, 0.25, 1.0],
+            )
+            .unwrap();
+
+            for name in ["labels.avro", "labels.arrows"] {
+                let path = dir.join(name);
+                tabular::write_df(&mut df.clone(), &path)?;
+                let read = tabular::read_df(&path, DFOpts::empty())?;
+                assert!(read.frame_equal(&df), "{name} read back as {read}");
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_sniff_and_read_semicolon_latin1_csv() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let path = dir.join("ventes.csv");
+            // "café" and "crème" encoded as windows-1252, not valid utf-8
+            let data: &[u8] = b"produit;prix\ncaf\xe9;2,5\ncr\xe8me;3,0\n";
+            std::fs::write(&path, data)?;
+
+            let dialect = tabular::sniff_csv_dialect(&path)?;
+            assert_eq!(dialect.delimiter, ';');
+            assert!(dialect.has_header);
+            assert!(!dialect.is_utf8());
+
+            let mut opts = DFOpts::empty();
+            opts.dialect = Some(dialect);
+            let df = tabular::read_df(&path, opts)?;
+            assert_eq!(df.height(), 2);
+            assert_eq!(df.width(), 2);
+            let produit = df.column("produit").unwrap().utf8().unwrap();
+            assert_eq!(produit.get(0), Some("café"));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_read_csv_without_header_from_override() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let path = dir.join("no_header.csv");
+            std::fs::write(&path, "0000.jpg,cat\n0001.jpg,dog\n")?;
+
+            let dialect_override = CsvDialectOverride {
+                path: std::path::PathBuf::from("no_header.csv"),
+                delimiter: None,
+                quote: None,
+                has_header: Some(false),
+                encoding: None,
+            };
+            let mut opts = DFOpts::empty();
+            opts.dialect = Some(dialect_override.apply(CsvDialect::default()));
+            let df = tabular::read_df(&path, opts)?;
+            assert_eq!(df.height(), 2);
+            assert_eq!(df.width(), 2);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_transform_cast_and_with_columns() -> Result<(), OxenError> {
+        let df = df!(
+            "file" => &["0000.jpg", "0001.jpg"],
+            "width" => &["10", "20"],
+            "height" => &["3", "4"],
+        )
+        .unwrap();
+
+        let mut opts = DFOpts::empty();
+        opts.cast = Some(String::from("width:i64,height:i64"));
+        opts.with_columns = Some(vec![
+            String::from("area = width * height"),
+            String::from("path = concat('images/', file)"),
+            String::from("half_area = area / 2"),
+        ]);
+        let df = tabular::transform(df, opts)?;
+
+        assert_eq!(df.width(), 6);
+        let area = df.column("area").unwrap();
+        assert_eq!(area.dtype(), &polars::prelude::DataType::Int64);
+        assert_eq!(area.i64().unwrap().get(1), Some(80));
+        let path = df.column("path").unwrap().utf8().unwrap();
+        assert_eq!(path.get(0), Some("images/0000.jpg"));
+        let half_area = df.column("half_area").unwrap().f64().unwrap();
+        assert_eq!(half_area.get(0), Some(15.0));
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_cast_unknown_dtype() -> Result<(), OxenError> {
+        let df = df!("width" => &["10"]).unwrap();
+
+        let mut opts = DFOpts::empty();
+        opts.cast = Some(String::from("width:bigint"));
+        assert!(tabular::transform(df, opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_melt_pivot_round_trip() -> Result<(), OxenError> {
+        let df = df!(
+            "file" => &["0000.jpg", "0001.jpg"],
+            "cat" => &[0.9, 0.2],
+            "dog" => &[0.1, 0.8],
+        )
+        .unwrap();
+
+        let mut opts = DFOpts::empty();
+        opts.melt = Some(String::from("file"));
+        let long = tabular::transform(df.clone(), opts)?;
+        assert_eq!(long.height(), 4);
+        assert_eq!(long.get_column_names(), vec!["file", "variable", "value"]);
+
+        let mut opts = DFOpts::empty();
+        opts.pivot = Some(String::from("file:variable:value"));
+        let wide = tabular::transform(long, opts)?;
+        assert_eq!(wide.height(), 2);
+        assert_eq!(wide.get_column_names(), vec!["file", "cat", "dog"]);
+        assert!(wide.frame_equal(&df));
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_explode_writes_every_format() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let df = df!(
+                "file" => &["0000.jpg", "0001.jpg"],
+                "labels" => &[
+                    Series::new("", &["cat", "dog"]),
+                    Series::new("", &["person"]),
+                ],
+            )
+            .unwrap();
+
+            let mut opts = DFOpts::empty();
+            opts.explode = Some(String::from("labels"));
+            let exploded = tabular::transform(df.clone(), opts)?;
+            assert_eq!(exploded.height(), 3);
+
+            // Only list columns can be exploded
+            let mut opts = DFOpts::empty();
+            opts.explode = Some(String::from("file"));
+            assert!(tabular::transform(df.clone(), opts).is_err());
+
+            // Lists are joined with commas, so the values can be compared across formats
+            let labels = |df: &DataFrame| -> Vec<String> {
+                df.column("labels")
+                    .unwrap()
+                    .iter()
+                    .map(|val| match val {
+                        AnyValue::Utf8(val) => val.to_string(),
+                        AnyValue::Utf8Owned(val) => val.to_string(),
+                        AnyValue::List(vals) => vals
+                            .utf8()
+                            .unwrap()
+                            .into_no_null_iter()
+                            .collect::<Vec<&str>>()
+                            .join(","),
+                        val => panic!("Unexpected label {val:?}"),
+                    })
+                    .collect()
+            };
+
+            for ext in [
+                "csv", "tsv", "json", "jsonl", "parquet", "arrow", "arrows", "avro",
+            ] {
+                let mut exploded = exploded.clone();
+                let path = dir.join(format!("exploded.{ext}"));
+                tabular::write_df(&mut exploded, &path)?;
+                let read = tabular::read_df(&path, DFOpts::empty())?;
+                assert_eq!(labels(&read), vec!["cat", "dog", "person"], "{ext}");
+
+                // The list column itself is written as json text to csv and tsv
+                let mut nested = df.clone();
+                let path = dir.join(format!("nested.{ext}"));
+                tabular::write_df(&mut nested, &path)?;
+                let read = tabular::read_df(&path, DFOpts::empty())?;
+                let expected = match ext {
+                    "csv" | "tsv" => vec![r#"["cat","dog"]"#, r#"["person"]"#],
+                    _ => vec!["cat,dog", "person"],
+                };
+                assert_eq!(labels(&read), expected, "{ext}");
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_transform_pivot_invalid() -> Result<(), OxenError> {
+        let df = df!("file" => &["0000.jpg"]).unwrap();
+
+        let mut opts = DFOpts::empty();
+        opts.pivot = Some(String::from("file:label"));
+        assert!(tabular::transform(df, opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_aggregate_expressions() -> Result<(), OxenError> {
+        let df = df!(
+            "label" => &["cat", "dog", "cat", "cat"],
+            "width" => &[10, 20, 30, 40],
+            "height" => &[1, 2, 3, 4],
+        )
+        .unwrap();
+
+        let opts = DFOpts::from_agg(
+            "('label') -> (count('width') as n, sum(width * height) as area, quantile('width', 0.5))",
+        );
+        let agg = tabular::transform(df.clone(), opts)?;
+        assert_eq!(
+            agg.get_column_names(),
+            vec!["label", "n", "area", "quantile('width', 0.5)"]
+        );
+        // Groups stay in the order they first appear
+        let labels = agg.column("label").unwrap().utf8().unwrap();
+        assert_eq!(labels.get(0), Some("cat"));
+        let area = agg.column("area").unwrap().cast(&DataType::Int64).unwrap();
+        assert_eq!(area.i64().unwrap().get(0), Some(10 + 90 + 160));
+
+        let opts = DFOpts::from_agg("() -> (value_counts('label') as count)");
+        let counts = tabular::transform(df, opts)?;
+        assert_eq!(counts.get_column_names(), vec!["label", "count"]);
+        assert_eq!(counts.height(), 2);
+        let labels = counts.column("label").unwrap().utf8().unwrap();
+        assert_eq!(labels.get(0), Some("cat"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_aggregate_head_and_tail() -> Result<(), OxenError> {
+        let df = df!(
+            "label" => &["cat", "dog", "cat", "cat"],
+            "width" => &[10, 20, 30, 40],
+        )
+        .unwrap();
+
+        let opts = DFOpts::from_agg("('label') -> (head('width', 2) as first)");
+        let agg = tabular::transform(df.clone(), opts)?;
+        assert_eq!(agg.height(), 2);
+
+        let opts = DFOpts::from_agg("() -> (tail('width', 3) as last)");
+        let agg = tabular::transform(df.clone(), opts)?;
+        assert_eq!(agg.height(), 3);
+
+        for query in [
+            "('label') -> (head('width', -3))",
+            "('label') -> (tail('width', 1.5))",
+            "() -> (head('width', 2), sum('width'))",
+        ] {
+            let opts = DFOpts::from_agg(query);
+            assert!(tabular::transform(df.clone(), opts).is_err(), "{query}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_take_rows() -> Result<(), OxenError> {
+        let df = df!(
+            "label" => &["cat", "dog", "cat", "cat"],
+            "width" => &[10, 20, 30, 40],
+        )
+        .unwrap();
+
+        let taken = tabular::take_rows(df.clone().lazy(), &[1, 3])?;
+        assert_eq!(taken.get_column_names(), vec!["label", "width"]);
+        assert_eq!(
+            taken
+                .column("width")
+                .unwrap()
+                .i32()
+                .unwrap()
+                .into_no_null_iter()
+                .collect::<Vec<_>>(),
+            vec![20, 40]
+        );
+
+        assert!(tabular::take_rows(df.clone().lazy(), &[3, 4]).is_err());
+        assert!(tabular::take(df.lazy(), vec![4]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_transform_join_invalid_how() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let labels_path = dir.join("labels.csv");
+            test::write_txt_file_to_path(&labels_path, "file,label\n0000.jpg,cat\n")?;
+            let df = df!("file" => &["0000.jpg"]).unwrap();
+
+            let mut opts = DFOpts::empty();
+            opts.join = Some(labels_path);
+            opts.join_on = Some(String::from("file"));
+            opts.join_how = Some(String::from("sideways"));
+
+            match tabular::transform(df, opts) {
+                Ok(df) => panic!("Joined with an invalid join type: {df}"),
+                Err(err) => assert!(err.to_string().contains("Invalid join type 'sideways'")),
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_transform_sql_cannot_read_files() -> Result<(), OxenError> {
+        test::run_empty_dir_test(|dir| {
+            let secret_path = dir.join("secret.csv");
+            test::write_txt_file_to_path(&secret_path, "password\nhunter2\n")?;
+
+            for query in [
+                format!(
+                    "SELECT * FROM read_csv('{}')",
+                    secret_path.to_string_lossy()
+                ),
+                format!(
+                    "SELECT * FROM READ_CSV('{}')",
+                    secret_path.to_string_lossy()
+                ),
+                format!(
+                    "SELECT df.label FROM df JOIN \"read_csv\"('{}') AS s ON df.label = s.password",
+                    secret_path.to_string_lossy()
+                ),
+                String::from("SELECT * FROM read_parquet('data.parquet')"),
+            ] {
+                let df = df!("label" => &["dog", "cat"]).unwrap();
+                let mut opts = DFOpts::empty();
+                opts.sql = Some(query.to_owned());
+                match tabular::transform(df, opts) {
+                    Ok(df) => panic!("{query} read a file: {df}"),
+                    Err(err) => assert!(err.to_string().contains("is not supported"), "{err}"),
+                }
+            }
+
+            // The names are fine inside string literals
+            let df = df!("label" => &["read_csv", "cat"]).unwrap();
+            let mut opts = DFOpts::empty();
+            opts.sql = Some(String::from("SELECT * FROM df WHERE label = 'read_csv'"));
+            let df = tabular::transform(df, opts)?;
+            assert_eq!(df.height(), 1);
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_transform_sql_invalid_query() -> Result<(), OxenError> {
+        let df = df!("label" => &["dog", "cat"]).unwrap();
+
+        let mut opts = DFOpts::empty();
+        opts.sql = Some(String::from("SELEC label FROM"));
+        let result = tabular::transform(df, opts);
+
+        assert!(result.is_err());
+        Ok(())
+    }
 }
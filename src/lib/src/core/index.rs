@@ -26,6 +26,7 @@ pub mod rm;
 pub mod schema_field_val_index;
 pub mod schema_index_reader;
 pub mod schema_index_writer;
+pub mod schema_indexer;
 pub mod schema_reader;
 pub mod schema_writer;
 pub mod staged_dir_entry_db;
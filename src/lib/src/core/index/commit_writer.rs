@@ -2,8 +2,8 @@ use crate::config::UserConfig;
 use crate::constants::{COMMITS_DIR, MERGE_HEAD_FILE, ORIG_HEAD_FILE};
 use crate::core::df::tabular;
 use crate::core::index::{
-    self, mod_stager, remote_dir_stager, CommitDBReader, CommitDirEntryReader,
-    CommitDirEntryWriter, CommitEntryReader, CommitEntryWriter, RefReader, RefWriter,
+    self, mod_stager, remote_dir_stager, schema_indexer, CommitDBReader, CommitDirEntryReader,
+    CommitDirEntryWriter, CommitEntryReader, CommitEntryWriter, RefReader, RefWriter, SchemaReader,
 };
 use crate::core::{db, df};
 use crate::error::OxenError;
@@ -129,6 +129,7 @@ impl CommitWriter {
         // Create a commit object, that either points to parent or not
         // must create this before anything else so that we know if it has parent or not.
         log::debug!("---COMMIT START---"); // for debug logging / timing purposes
+        api::local::schemas::check_staged_contracts(&self.repository, status)?;
         let new_commit = self.create_new_commit_data(message)?;
         log::debug!("Created commit obj {:?}", new_commit);
         let commit = self.commit_from_new(&new_commit, status, &self.repository.path)?;
@@ -268,7 +269,13 @@ impl CommitWriter {
         entry: &CommitEntry,
         path: &Path,
     ) -> Result<String, OxenError> {
-        let mut df = df::tabular::read_df(path, DFOpts::empty())?;
+        // Read and write the file back in the dialect it was committed with
+        let dialect = SchemaReader::new(&self.repository, &entry.commit_id)?
+            .get_schema_for_file(&entry.path)?
+            .and_then(|schema| schema.dialect);
+        let mut read_opts = DFOpts::empty();
+        read_opts.dialect = dialect.clone();
+        let mut df = df::tabular::read_df(path, read_opts)?;
         let schema_fields = Schema::from_polars(&df.schema()).fields_names().join(",");
         let mods_df = mod_stager::list_mods_df(&self.repository, branch, user_id, entry)?;
         log::debug!("apply_tabular_mods [{}] {:?}", branch.name, path);
@@ -289,7 +296,12 @@ impl CommitWriter {
             df = df.vstack(&filtered).unwrap();
         }
         log::debug!("Full DF {}", df);
-        df::tabular::write_df(&mut df, path)?;
+        match dialect {
+            Some(dialect) if util::fs::is_delimited_text(path) => {
+                df::tabular::write_df_csv_dialect(&mut df, path, &dialect)?
+            }
+            _ => df::tabular::write_df(&mut df, path)?,
+        };
         let new_hash = util::hasher::hash_file_contents(path)?;
         Ok(new_hash)
     }
@@ -344,6 +356,9 @@ impl CommitWriter {
         // Commit all staged files from db
         entry_writer.commit_staged_entries(commit, status, origin_path)?;
 
+        // Carry the field indices of the parents over to the new files
+        schema_indexer::index_commit(&self.repository, commit)?;
+
         // Add to commits db id -> commit_json
         log::debug!("add_commit_from_status add commit [{}] to db", commit.id);
         self.add_commit_to_db(commit)?;
@@ -368,6 +383,9 @@ impl CommitWriter {
         // Commit all staged files from db
         entry_writer.commit_staged_entries(commit, status, origin_path)?;
 
+        // Carry the field indices of the parents over to the new files
+        schema_indexer::index_commit(&self.repository, commit)?;
+
         // Add to commits db id -> commit_json
         log::debug!("add_commit_from_status add commit [{}] to db", commit.id);
         self.add_commit_to_db(commit)?;
@@ -709,7 +727,7 @@ mod tests {
     use crate::model::entry::mod_entry::{ModType, NewMod};
     use crate::model::{ContentType, StagedData};
     use crate::opts::DFOpts;
-    use crate::{api, test, util};
+    use crate::{api, command, test, util};
 
     // This is how we initialize
     #[test]
@@ -771,6 +789,65 @@ mod tests {
         })
     }
 
+    #[test]
+    fn test_commit_rejects_schema_contract_violation() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            command::schemas::pin(
+                &repo,
+                "annotations/**/*.csv",
+                "bounding_box",
+                &[String::from("file")],
+                false,
+            )?;
+
+            // Rename a column
+            let path = Path::new("annotations")
+                .join("train")
+                .join("bounding_box.csv");
+            let full_path = repo.path.join(&path);
+            let contents = util::fs::read_from_path(&full_path)?;
+            util::fs::write_to_path(&full_path, &contents.replacen("label", "Label", 1))?;
+            command::add(&repo, &full_path)?;
+
+            let result = command::commit(&repo, "Renaming label");
+            match result {
+                Err(OxenError::SchemaContractViolation(report)) => {
+                    let report = report.to_string();
+                    assert!(report.contains("column 'label' is missing, found 'Label'"));
+                    assert!(report.contains("column 'Label' is not in the contract"));
+                }
+                _ => panic!("Expected a schema contract violation"),
+            }
+
+            // Nothing was committed
+            let history = api::local::commits::list(&repo)?;
+            assert_eq!(history.first().unwrap().message, "adding all data baby");
+
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_commit_versions_schema_contracts() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let path = "annotations/**/*.csv";
+            command::schemas::pin(&repo, path, "bounding_box", &[], false)?;
+            let commit = command::commit(&repo, "Pinning the bounding boxes")?;
+
+            let contracts = api::local::schemas::get_contracts_for_commit(&repo, &commit)?;
+            assert_eq!(contracts.schema_contracts.len(), 1);
+            assert_eq!(contracts.schema_contracts[0].path, path);
+            api::local::schemas::check_commit_contracts(&repo, &commit)?;
+
+            assert!(command::schemas::unpin(&repo, path)?);
+            let commit = command::commit(&repo, "Unpinning the bounding boxes")?;
+            let contracts = api::local::schemas::get_contracts_for_commit(&repo, &commit)?;
+            assert!(contracts.is_empty());
+
+            Ok(())
+        })
+    }
+
     #[test]
     fn test_commit_tabular_append_invalid_schema() -> Result<(), OxenError> {
         test::run_training_data_repo_test_fully_committed(|repo| {
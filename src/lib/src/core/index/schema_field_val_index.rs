@@ -5,7 +5,7 @@ use crate::model::{schema::Field, Commit, Schema};
 use crate::util;
 
 use rocksdb::{DBWithThreadMode, MultiThreaded};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::str;
 
 use crate::model::LocalRepository;
@@ -50,6 +50,52 @@ impl SchemaFieldValIndex {
         })
     }
 
+    /// Open an index that already exists without taking the write lock, so it can be read concurrently
+    pub fn new_read_only(
+        repository: &LocalRepository,
+        commit: &Commit,
+        schema: &Schema,
+        field: &Field,
+    ) -> Result<SchemaFieldValIndex, OxenError> {
+        let db_path = SchemaFieldValIndex::db_dir(repository, commit, schema, field);
+        let opts = db::opts::default();
+        Ok(SchemaFieldValIndex {
+            field_indices_db: DBWithThreadMode::open_for_read_only(&opts, &db_path, false)?,
+        })
+    }
+
+    // Many files can share a schema, so the values of each file are prefixed by the hash of its path
+    fn file_key(path: &Path, key: &str) -> String {
+        let path_hash = util::hasher::hash_str(path.to_string_lossy());
+        format!("{path_hash}/{key}")
+    }
+
+    /// Rows of the file at `path` where the field is equal to `key`
+    pub fn insert_file_index<S: AsRef<str>>(
+        &self,
+        path: &Path,
+        key: S,
+        indices: Vec<u32>,
+    ) -> Result<(), OxenError> {
+        self.insert_index(SchemaFieldValIndex::file_key(path, key.as_ref()), indices)
+    }
+
+    pub fn get_file_indices<S: AsRef<str>>(
+        &self,
+        path: &Path,
+        key: S,
+    ) -> Result<Option<Vec<u32>>, OxenError> {
+        self.get_indices(SchemaFieldValIndex::file_key(path, key.as_ref()))
+    }
+
+    /// Remove the rows of every value of the file at `path`
+    pub fn remove_file_indices(&self, path: &Path) -> Result<(), OxenError> {
+        db::index_db::delete_prefix(
+            &self.field_indices_db,
+            SchemaFieldValIndex::file_key(path, ""),
+        )
+    }
+
     pub fn insert_index<S: AsRef<str>>(&self, key: S, indices: Vec<u32>) -> Result<(), OxenError> {
         db::index_db::insert_indices(&self.field_indices_db, key, indices)
     }
@@ -79,10 +125,7 @@ mod tests {
                 .unwrap();
 
             // As if we aggregated up the "label" field and "dog" was a value
-            let field = schema::Field {
-                name: String::from("label"),
-                dtype: String::from("str"),
-            };
+            let field = schema::Field::new("label", "str");
 
             let reader = SchemaFieldValIndex::new(&repo, last_commit, schema, &field)?;
             let indices = reader.get_indices("cat")?;
@@ -105,10 +148,7 @@ mod tests {
                 .unwrap();
 
             // As if we aggregated up the "label" field and "dog" was a value
-            let field = schema::Field {
-                name: String::from("label"),
-                dtype: String::from("str"),
-            };
+            let field = schema::Field::new("label", "str");
 
             let index = SchemaFieldValIndex::new(&repo, last_commit, schema, &field)?;
 
@@ -134,10 +174,7 @@ mod tests {
                 .unwrap();
 
             // As if we aggregated up the "label" field and "dog" was a value
-            let field = schema::Field {
-                name: String::from("label"),
-                dtype: String::from("str"),
-            };
+            let field = schema::Field::new("label", "str");
 
             let index = SchemaFieldValIndex::new(&repo, last_commit, schema, &field)?;
 
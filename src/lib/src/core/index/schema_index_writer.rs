@@ -61,10 +61,7 @@ mod tests {
                 .find(|s| s.name.as_ref().unwrap() == "bounding_box")
                 .unwrap();
 
-            let field = schema::Field {
-                name: String::from("label"),
-                dtype: String::from("str"),
-            };
+            let field = schema::Field::new("label", "str");
             {
                 let writer = SchemaIndexWriter::new(&repo, last_commit, schema)?;
                 writer.create_field_index(&field)?;
@@ -0,0 +1,500 @@
+//! Build and query the field indices of a schema
+//!
+//! An index maps each value of a field to the rows it appears in, for every tabular file
+//! that shares the schema. Indices live with the commit they were built for:
+//! .oxen/history/COMMIT_ID/indices/SCHEMA_HASH/{indices,fields}
+//! and every new commit carries over the indices of its parents, re-indexing only the files it changed.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use polars::prelude::*;
+
+use crate::api;
+use crate::constants::{HISTORY_DIR, INDICES_DIR};
+use crate::core::df::filter::{DFFilterExp, DFFilterOp, DFFilterVal, DFLogicalOp};
+use crate::core::df::tabular;
+use crate::core::index::{
+    CommitEntryReader, SchemaFieldValIndex, SchemaIndexReader, SchemaIndexWriter, SchemaReader,
+};
+use crate::error::OxenError;
+use crate::model::schema::Field;
+use crate::model::{Commit, CommitEntry, LocalRepository, Schema};
+use crate::opts::DFOpts;
+use crate::util;
+
+// Values are indexed as strings, so only string fields can be looked up exactly
+const INDEXABLE_DTYPE: &str = "str";
+
+fn commit_indices_dir(repo: &LocalRepository, commit_id: &str) -> PathBuf {
+    // .oxen/history/COMMIT_ID/indices
+    util::fs::oxen_hidden_dir(&repo.path)
+        .join(HISTORY_DIR)
+        .join(commit_id)
+        .join(INDICES_DIR)
+}
+
+/// Group the tabular files in a commit by the hash of their schema
+pub fn tabular_entries_by_schema(
+    repo: &LocalRepository,
+    commit: &Commit,
+) -> Result<HashMap<String, (Schema, Vec<CommitEntry>)>, OxenError> {
+    let entry_reader = CommitEntryReader::new(repo, commit)?;
+    // The schema of a file is saved on the commit that last changed it
+    let mut schema_readers: HashMap<String, SchemaReader> = HashMap::new();
+    let mut entries_by_schema: HashMap<String, (Schema, Vec<CommitEntry>)> = HashMap::new();
+    for entry in entry_reader.list_entries()? {
+        if !util::fs::is_tabular(&entry.path) {
+            continue;
+        }
+
+        if !schema_readers.contains_key(&entry.commit_id) {
+            let reader = SchemaReader::new(repo, &entry.commit_id)?;
+            schema_readers.insert(entry.commit_id.to_owned(), reader);
+        }
+        let schema_reader = &schema_readers[&entry.commit_id];
+        if let Some(schema) = schema_reader.get_schema_for_file(&entry.path)? {
+            let (found, entries) = entries_by_schema
+                .entry(schema.hash.to_owned())
+                .or_insert_with(|| (schema.to_owned(), vec![]));
+            // Only some commits may have named the schema
+            if found.name.is_none() {
+                found.name = schema.name;
+            }
+            entries.push(entry);
+        }
+    }
+    Ok(entries_by_schema)
+}
+
+fn find_schema<'a>(
+    entries_by_schema: &'a HashMap<String, (Schema, Vec<CommitEntry>)>,
+    schema_ref: &str,
+) -> Result<&'a (Schema, Vec<CommitEntry>), OxenError> {
+    entries_by_schema
+        .values()
+        .find(|(schema, _)| schema.hash == schema_ref || schema.name.as_deref() == Some(schema_ref))
+        .ok_or_else(|| OxenError::schema_does_not_exist(schema_ref))
+}
+
+/// Index `field_name` for every file with the schema `schema_ref` (a name or hash) at `commit`
+pub fn create_field_index(
+    repo: &LocalRepository,
+    commit: &Commit,
+    schema_ref: &str,
+    field_name: &str,
+) -> Result<(Schema, Field), OxenError> {
+    let entries_by_schema = tabular_entries_by_schema(repo, commit)?;
+    let (schema, entries) = find_schema(&entries_by_schema, schema_ref)?;
+    let field = schema
+        .fields
+        .iter()
+        .find(|field| field.name == field_name)
+        .ok_or_else(|| OxenError::schema_does_not_have_field(field_name))?;
+    if field.dtype != INDEXABLE_DTYPE {
+        return Err(OxenError::basic_str(format!(
+            "Can only index {INDEXABLE_DTYPE} fields, '{}' is {}",
+            field.name, field.dtype
+        )));
+    }
+
+    index_field(repo, commit, schema, field, entries)?;
+    Ok((schema.to_owned(), field.to_owned()))
+}
+
+fn index_field(
+    repo: &LocalRepository,
+    commit: &Commit,
+    schema: &Schema,
+    field: &Field,
+    entries: &[CommitEntry],
+) -> Result<(), OxenError> {
+    log::debug!(
+        "index_field {} on {} files with schema {}",
+        field.name,
+        entries.len(),
+        schema.hash
+    );
+    {
+        let writer = SchemaIndexWriter::new(repo, commit, schema)?;
+        writer.create_field_index(field)?;
+    }
+
+    let index = SchemaFieldValIndex::new(repo, commit, schema, field)?;
+    for entry in entries.iter() {
+        for (value, rows) in field_value_rows(repo, schema, field, entry)? {
+            index.insert_file_index(&entry.path, value, rows)?;
+        }
+    }
+    Ok(())
+}
+
+// value -> rows for one field of a committed file, nulls are not indexed
+fn field_value_rows(
+    repo: &LocalRepository,
+    schema: &Schema,
+    field: &Field,
+    entry: &CommitEntry,
+) -> Result<HashMap<String, Vec<u32>>, OxenError> {
+    let mut opts = DFOpts::empty();
+    opts.dialect = schema.dialect.to_owned();
+    let version_path = util::fs::version_path(repo, entry);
+    let df = tabular::scan_df(&version_path, &opts)?.select([col(&field.name)]);
+    let df = tabular::collect_df(df)?;
+
+    let values = df
+        .column(&field.name)
+        .and_then(|series| series.cast(&DataType::Utf8))
+        .map_err(|err| OxenError::basic_str(format!("Could not index {:?}: {err}", entry.path)))?;
+    let mut rows: HashMap<String, Vec<u32>> = HashMap::new();
+    for (i, value) in values.utf8().unwrap().into_iter().enumerate() {
+        if let Some(value) = value {
+            rows.entry(value.to_string()).or_default().push(i as u32);
+        }
+    }
+    Ok(rows)
+}
+
+/// Remove the index on `field_name` for the schema `schema_ref` at `commit`, later commits will not rebuild it
+pub fn drop_field_index(
+    repo: &LocalRepository,
+    commit: &Commit,
+    schema_ref: &str,
+    field_name: &str,
+) -> Result<(), OxenError> {
+    let entries_by_schema = tabular_entries_by_schema(repo, commit)?;
+    let (schema, _) = find_schema(&entries_by_schema, schema_ref)?;
+    let field = {
+        let reader = SchemaIndexReader::new(repo, commit, schema)?;
+        reader.get_field_index_by_name(field_name)?
+    };
+    let field = field.ok_or_else(|| {
+        OxenError::basic_str(format!(
+            "No index on field '{field_name}' for schema {schema_ref}"
+        ))
+    })?;
+
+    {
+        let writer = SchemaIndexWriter::new(repo, commit, schema)?;
+        writer.delete_field_index(&field)?;
+    }
+    let db_dir = SchemaFieldValIndex::db_dir(repo, commit, schema, &field);
+    if db_dir.exists() {
+        util::fs::remove_dir_all(db_dir)?;
+    }
+    Ok(())
+}
+
+/// All the indexed fields at `commit` with the schema they belong to
+pub fn list_field_indices(
+    repo: &LocalRepository,
+    commit: &Commit,
+) -> Result<Vec<(Schema, Field)>, OxenError> {
+    if !commit_indices_dir(repo, &commit.id).exists() {
+        return Ok(vec![]);
+    }
+
+    let mut indices: Vec<(Schema, Field)> = vec![];
+    for (schema, _) in tabular_entries_by_schema(repo, commit)?.into_values() {
+        if !SchemaIndexReader::field_indices_db_dir(repo, commit, &schema).exists() {
+            continue;
+        }
+        let reader = SchemaIndexReader::new(repo, commit, &schema)?;
+        for field in reader.list_field_indices()? {
+            indices.push((schema.to_owned(), field));
+        }
+    }
+    indices.sort_by(|(a, fa), (b, fb)| {
+        (&a.name, &a.hash, &fa.name).cmp(&(&b.name, &b.hash, &fb.name))
+    });
+    Ok(indices)
+}
+
+/// Carry the indices of the parent commits over to `commit`, only re-indexing the files that changed.
+/// Schemas that no longer have any files lose their indices.
+pub fn index_commit(repo: &LocalRepository, commit: &Commit) -> Result<(), OxenError> {
+    let parent_ids: Vec<&String> = commit
+        .parent_ids
+        .iter()
+        .filter(|id| commit_indices_dir(repo, id).exists())
+        .collect();
+    if parent_ids.is_empty() {
+        return Ok(());
+    }
+
+    let entries_by_schema = tabular_entries_by_schema(repo, commit)?;
+    for parent_id in parent_ids {
+        let parent = api::local::commits::get_by_id(repo, parent_id)?
+            .ok_or_else(|| OxenError::committish_not_found(parent_id.to_owned().into()))?;
+        let parent_entries_by_schema = tabular_entries_by_schema(repo, &parent)?;
+        for (schema, entries) in entries_by_schema.values() {
+            if !SchemaIndexReader::field_indices_db_dir(repo, &parent, schema).exists() {
+                continue;
+            }
+            let parent_entries: HashMap<&Path, &str> = parent_entries_by_schema
+                .get(&schema.hash)
+                .map(|(_, entries)| {
+                    entries
+                        .iter()
+                        .map(|entry| (entry.path.as_path(), entry.hash.as_str()))
+                        .collect()
+                })
+                .unwrap_or_default();
+
+            let fields = SchemaIndexReader::new(repo, &parent, schema)?.list_field_indices()?;
+            for field in fields.iter() {
+                carry_over_field_index(
+                    repo,
+                    &parent,
+                    commit,
+                    schema,
+                    field,
+                    entries,
+                    &parent_entries,
+                )?;
+            }
+        }
+    }
+    Ok(())
+}
+
+// Copy the index of `field` from the parent, then drop the files that left the schema and re-index the ones that changed
+fn carry_over_field_index(
+    repo: &LocalRepository,
+    parent: &Commit,
+    commit: &Commit,
+    schema: &Schema,
+    field: &Field,
+    entries: &[CommitEntry],
+    parent_entries: &HashMap<&Path, &str>,
+) -> Result<(), OxenError> {
+    let db_dir = SchemaFieldValIndex::db_dir(repo, commit, schema, field);
+    // A merge commit may have already carried it over from another parent
+    if db_dir.exists() {
+        return Ok(());
+    }
+    let parent_db_dir = SchemaFieldValIndex::db_dir(repo, parent, schema, field);
+    if !parent_db_dir.exists() {
+        return index_field(repo, commit, schema, field, entries);
+    }
+    util::fs::copy_dir_all(parent_db_dir, &db_dir)?;
+    {
+        let writer = SchemaIndexWriter::new(repo, commit, schema)?;
+        writer.create_field_index(field)?;
+    }
+
+    let index = SchemaFieldValIndex::new(repo, commit, schema, field)?;
+    let paths: HashSet<&Path> = entries.iter().map(|entry| entry.path.as_path()).collect();
+    for path in parent_entries.keys() {
+        if !paths.contains(path) {
+            index.remove_file_indices(path)?;
+        }
+    }
+
+    let changed: Vec<&CommitEntry> = entries
+        .iter()
+        .filter(|entry| parent_entries.get(entry.path.as_path()) != Some(&entry.hash.as_str()))
+        .collect();
+    log::debug!(
+        "carry_over_field_index {} re-indexing {} of {} files",
+        field.name,
+        changed.len(),
+        entries.len()
+    );
+    for entry in changed {
+        index.remove_file_indices(&entry.path)?;
+        for (value, rows) in field_value_rows(repo, schema, field, entry)? {
+            index.insert_file_index(&entry.path, value, rows)?;
+        }
+    }
+    Ok(())
+}
+
+// The equalities that every matching row has to pass, joined by && at the top of the filter
+fn required_eq_vals(filter: &DFFilterExp) -> Vec<&DFFilterVal> {
+    match filter {
+        DFFilterExp::Val(val) if val.op == DFFilterOp::EQ => vec![val],
+        DFFilterExp::Logical(DFLogicalOp::AND, lhs, rhs) => {
+            let mut vals = required_eq_vals(lhs);
+            vals.extend(required_eq_vals(rhs));
+            vals
+        }
+        _ => vec![],
+    }
+}
+
+/// The rows of a committed file that can match `filter`, looked up from the indexed fields the filter
+/// requires to equal a value. Returns None if the filter does not use any indexed field.
+pub fn filter_row_indices(
+    repo: &LocalRepository,
+    commit: &Commit,
+    schema: &Schema,
+    entry: &CommitEntry,
+    filter: &DFFilterExp,
+) -> Result<Option<Vec<u32>>, OxenError> {
+    let eq_vals = required_eq_vals(filter);
+    if eq_vals.is_empty() || !SchemaIndexReader::field_indices_db_dir(repo, commit, schema).exists()
+    {
+        return Ok(None);
+    }
+
+    let reader = SchemaIndexReader::new(repo, commit, schema)?;
+    let mut result: Option<HashSet<u32>> = None;
+    for val in eq_vals {
+        let field = match reader.get_field_index_by_name(&val.field)? {
+            Some(field) => field,
+            None => continue,
+        };
+        log::debug!("filter_row_indices using index on {}", field.name);
+        let index = SchemaFieldValIndex::new_read_only(repo, commit, schema, &field)?;
+        let rows: HashSet<u32> = index
+            .get_file_indices(&entry.path, &val.value)?
+            .unwrap_or_default()
+            .into_iter()
+            .collect();
+        result = Some(match result {
+            Some(prev) => prev.intersection(&rows).copied().collect(),
+            None => rows,
+        });
+    }
+
+    Ok(result.map(|rows| {
+        let mut rows: Vec<u32> = rows.into_iter().collect();
+        rows.sort();
+        rows
+    }))
+}
+
+/// Scan only the rows of the file at `path` in `commit` that an index says can pass the filter in `opts`.
+/// The filter still has to be applied to the result. Returns None if no index can answer the filter.
+pub fn scan_indexed(
+    repo: &LocalRepository,
+    commit: &Commit,
+    path: impl AsRef<Path>,
+    opts: &DFOpts,
+) -> Result<Option<LazyFrame>, OxenError> {
+    if !opts.filter_reads_source_rows() || !commit_indices_dir(repo, &commit.id).exists() {
+        return Ok(None);
+    }
+    let filter = match opts.get_filter()? {
+        Some(filter) => filter,
+        None => return Ok(None),
+    };
+    let entry = match CommitEntryReader::new(repo, commit)?.get_entry(path.as_ref())? {
+        Some(entry) => entry,
+        None => return Ok(None),
+    };
+    let schema =
+        match SchemaReader::new(repo, &entry.commit_id)?.get_schema_for_file(&entry.path)? {
+            Some(schema) => schema,
+            None => return Ok(None),
+        };
+
+    match filter_row_indices(repo, commit, &schema, &entry, &filter)? {
+        Some(rows) => {
+            let mut opts = opts.clone();
+            if opts.dialect.is_none() {
+                opts.dialect = schema.dialect.to_owned();
+            }
+            let version_path = util::fs::version_path(repo, &entry);
+            let df = tabular::scan_df(version_path, &opts)?;
+            Ok(Some(tabular::take_rows(df, &rows)?.lazy()))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::core::df::tabular;
+    use crate::core::index::schema_indexer;
+    use crate::error::OxenError;
+    use crate::opts::DFOpts;
+    use crate::test;
+    use crate::{api, command, util};
+
+    #[test]
+    fn test_index_field_and_filter_from_index() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let commit = api::local::commits::head_commit(&repo)?;
+            let (schema, field) =
+                schema_indexer::create_field_index(&repo, &commit, "bounding_box", "label")?;
+            assert_eq!(field.name, "label");
+
+            let indices = schema_indexer::list_field_indices(&repo, &commit)?;
+            assert_eq!(indices.len(), 1);
+            assert_eq!(indices[0].0.hash, schema.hash);
+
+            let path = Path::new("annotations")
+                .join("train")
+                .join("bounding_box.csv");
+            let mut opts = DFOpts::empty();
+            opts.filter = Some(String::from("label == dog"));
+            let indexed = schema_indexer::scan_indexed(&repo, &commit, &path, &opts)?.unwrap();
+            let indexed = tabular::transform_lazy(indexed, &opts)?;
+            let indexed = tabular::collect_df(indexed)?;
+
+            let scanned = tabular::read_df(repo.path.join(&path), opts)?;
+            assert!(indexed.height() > 0);
+            assert!(indexed.frame_equal(&scanned));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_index_rebuilt_on_commit_and_dropped() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let commit = api::local::commits::head_commit(&repo)?;
+            schema_indexer::create_field_index(&repo, &commit, "bounding_box", "label")?;
+
+            // A new file with the same schema is indexed when it is committed
+            let path = Path::new("annotations")
+                .join("train")
+                .join("bounding_box.csv");
+            let copy_path = Path::new("annotations").join("bounding_box_copy.csv");
+            util::fs::copy(repo.path.join(&path), repo.path.join(&copy_path))?;
+            command::add(&repo, repo.path.join(&copy_path))?;
+            let commit = command::commit(&repo, "Adding a copy")?;
+
+            let mut opts = DFOpts::empty();
+            opts.filter = Some(String::from("label == cat && min_x > 0"));
+            assert!(schema_indexer::scan_indexed(&repo, &commit, &copy_path, &opts)?.is_some());
+
+            schema_indexer::drop_field_index(&repo, &commit, "bounding_box", "label")?;
+            assert!(schema_indexer::list_field_indices(&repo, &commit)?.is_empty());
+            assert!(schema_indexer::scan_indexed(&repo, &commit, &copy_path, &opts)?.is_none());
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_index_carried_over_and_changed_files_reindexed() -> Result<(), OxenError> {
+        test::run_training_data_repo_test_fully_committed(|repo| {
+            let commit = api::local::commits::head_commit(&repo)?;
+            schema_indexer::create_field_index(&repo, &commit, "bounding_box", "label")?;
+
+            // Only the changed file is re-indexed, the rest is carried over from the parent
+            let path = Path::new("annotations")
+                .join("train")
+                .join("bounding_box.csv");
+            let other_path = Path::new("annotations")
+                .join("test")
+                .join("annotations.csv");
+            test::append_line_txt_file(repo.path.join(&path), "train/dog_4.jpg,dog,1.0,2.0,3,4")?;
+            command::add(&repo, repo.path.join(&path))?;
+            let commit = command::commit(&repo, "Adding a dog")?;
+
+            let mut opts = DFOpts::empty();
+            opts.filter = Some(String::from("label == dog"));
+            for path in [&path, &other_path] {
+                let indexed = schema_indexer::scan_indexed(&repo, &commit, path, &opts)?.unwrap();
+                let indexed = tabular::collect_df(tabular::transform_lazy(indexed, &opts)?)?;
+                let scanned = tabular::read_df(repo.path.join(path), opts.clone())?;
+                assert!(indexed.frame_equal(&scanned));
+            }
+            Ok(())
+        })
+    }
+}
@@ -1,8 +1,8 @@
-use crate::constants::{FILES_DIR, HISTORY_DIR, SCHEMAS_DIR};
+use crate::constants::{DIALECTS_DIR, FILES_DIR, HISTORY_DIR, SCHEMAS_DIR};
 use crate::core::db;
 use crate::core::db::{str_json_db, str_val_db};
 use crate::error::OxenError;
-use crate::model::Schema;
+use crate::model::{CsvDialect, Schema};
 use crate::util;
 
 use rocksdb::{DBWithThreadMode, MultiThreaded};
@@ -14,6 +14,7 @@ use crate::model::LocalRepository;
 pub struct SchemaReader {
     schema_db: DBWithThreadMode<MultiThreaded>,
     schema_files_db: DBWithThreadMode<MultiThreaded>,
+    dialects_db: DBWithThreadMode<MultiThreaded>,
 }
 
 impl SchemaReader {
@@ -35,11 +36,21 @@ impl SchemaReader {
             .join(FILES_DIR)
     }
 
+    pub fn dialects_db_dir(repo: &LocalRepository, commit_id: &str) -> PathBuf {
+        // .oxen/history/COMMIT_ID/schemas/dialects
+        util::fs::oxen_hidden_dir(&repo.path)
+            .join(HISTORY_DIR)
+            .join(commit_id)
+            .join(SCHEMAS_DIR)
+            .join(DIALECTS_DIR)
+    }
+
     pub fn new(repository: &LocalRepository, commit_id: &str) -> Result<SchemaReader, OxenError> {
         let schema_db_path = SchemaReader::schemas_db_dir(repository, commit_id);
         log::debug!("SchemaReader db {:?}", schema_db_path);
         let schema_files_db_path = SchemaReader::schema_files_db_dir(repository, commit_id);
         log::debug!("SchemaReader files db {:?}", schema_files_db_path);
+        let dialects_db_path = SchemaReader::dialects_db_dir(repository, commit_id);
         let opts = db::opts::default();
         if !schema_db_path.exists() {
             std::fs::create_dir_all(&schema_db_path)?;
@@ -55,6 +66,14 @@ impl SchemaReader {
                 DBWithThreadMode::open(&opts, dunce::simplified(&schema_files_db_path))?;
         }
 
+        // Commits from before dialects were saved per file do not have the db
+        if !dialects_db_path.exists() {
+            std::fs::create_dir_all(&dialects_db_path)?;
+            // open it then lose scope to close it
+            let _db: DBWithThreadMode<MultiThreaded> =
+                DBWithThreadMode::open(&opts, dunce::simplified(&dialects_db_path))?;
+        }
+
         Ok(SchemaReader {
             schema_db: DBWithThreadMode::open_for_read_only(&opts, &schema_db_path, false)?,
             schema_files_db: DBWithThreadMode::open_for_read_only(
@@ -62,6 +81,7 @@ impl SchemaReader {
                 &schema_files_db_path,
                 false,
             )?,
+            dialects_db: DBWithThreadMode::open_for_read_only(&opts, &dialects_db_path, false)?,
         })
     }
 
@@ -82,12 +102,28 @@ impl SchemaReader {
         str_val_db::get(&self.schema_files_db, path.as_ref().to_str().unwrap())
     }
 
+    /// The csv dialect the file was committed with, None if it is the default
+    pub fn get_dialect_for_file<P: AsRef<Path>>(
+        &self,
+        path: P,
+    ) -> Result<Option<CsvDialect>, OxenError> {
+        str_json_db::get(&self.dialects_db, path.as_ref().to_str().unwrap())
+    }
+
+    /// The schema of the file, with the dialect of the file attached
     pub fn get_schema_for_file<P: AsRef<Path>>(
         &self,
         path: P,
     ) -> Result<Option<Schema>, OxenError> {
+        let path = path.as_ref();
         if let Some(hash) = self.get_schema_hash_for_file(path)? {
-            return self.get_schema_by_hash(hash);
+            return match self.get_schema_by_hash(hash)? {
+                Some(mut schema) => {
+                    schema.dialect = self.get_dialect_for_file(path)?;
+                    Ok(Some(schema))
+                }
+                None => Ok(None),
+            };
         }
         Ok(None)
     }
@@ -95,6 +131,21 @@ impl SchemaReader {
     pub fn list_schemas(&self) -> Result<Vec<Schema>, OxenError> {
         str_json_db::list_vals(&self.schema_db)
     }
+
+    /// List the files that had their schema written in this commit, with their schemas
+    pub fn list_schema_files(&self) -> Result<Vec<(PathBuf, Schema)>, OxenError> {
+        let mut results: Vec<(PathBuf, Schema)> = vec![];
+        for (path, hash) in str_val_db::list::<String>(&self.schema_files_db)? {
+            match self.get_schema_by_hash(&hash)? {
+                Some(mut schema) => {
+                    schema.dialect = self.get_dialect_for_file(&path)?;
+                    results.push((PathBuf::from(path), schema))
+                }
+                None => log::warn!("Could not find schema {hash} for file {path}"),
+            }
+        }
+        Ok(results)
+    }
 }
 
 #[cfg(test)]
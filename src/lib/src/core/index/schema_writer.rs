@@ -13,6 +13,7 @@ use crate::model::LocalRepository;
 pub struct SchemaWriter {
     db: DBWithThreadMode<MultiThreaded>,
     files_db: DBWithThreadMode<MultiThreaded>,
+    dialects_db: DBWithThreadMode<MultiThreaded>,
 }
 
 impl SchemaWriter {
@@ -34,14 +35,29 @@ impl SchemaWriter {
                 DBWithThreadMode::open(&opts, dunce::simplified(&schema_files_db_path))?;
         }
 
+        let dialects_db_path = SchemaReader::dialects_db_dir(repository, commit_id);
+        if !dialects_db_path.exists() {
+            std::fs::create_dir_all(&dialects_db_path)?;
+            // open it then lose scope to close it
+            let _db: DBWithThreadMode<MultiThreaded> =
+                DBWithThreadMode::open(&opts, dunce::simplified(&dialects_db_path))?;
+        }
+
         Ok(SchemaWriter {
             db: DBWithThreadMode::open(&opts, dunce::simplified(&db_path))?,
             files_db: DBWithThreadMode::open(&opts, dunce::simplified(&schema_files_db_path))?,
+            dialects_db: DBWithThreadMode::open(&opts, dunce::simplified(&dialects_db_path))?,
         })
     }
 
+    /// Map the file to the schema hash, and save its dialect since files with the same fields share a schema
     pub fn put_schema_for_file(&self, path: &Path, schema: &Schema) -> Result<(), OxenError> {
-        str_val_db::put(&self.files_db, path.to_str().unwrap(), &schema.hash)
+        let key = path.to_str().unwrap();
+        str_val_db::put(&self.files_db, key, &schema.hash)?;
+        match &schema.dialect {
+            Some(dialect) => str_json_db::put(&self.dialects_db, key, dialect),
+            None => str_json_db::delete(&self.dialects_db, key),
+        }
     }
 
     pub fn has_schema(&self, schema: &Schema) -> bool {
@@ -49,11 +65,11 @@ impl SchemaWriter {
     }
 
     pub fn put_schema(&self, schema: &Schema) -> Result<(), OxenError> {
-        str_json_db::put(&self.db, &schema.hash, schema)
+        str_json_db::put(&self.db, &schema.hash, &schema.without_dialect())
     }
 
     pub fn update_schema(&self, schema: &Schema) -> Result<Schema, OxenError> {
-        str_json_db::put(&self.db, &schema.hash, schema)?;
+        str_json_db::put(&self.db, &schema.hash, &schema.without_dialect())?;
         Ok(str_json_db::get(&self.db, &schema.hash)?.unwrap())
     }
 }
@@ -78,18 +94,9 @@ mod tests {
                 let schema_writer = SchemaWriter::new(&repo, &last_commit.id)?;
 
                 let schema = Schema::from_fields(vec![
-                    schema::Field {
-                        name: String::from("label"),
-                        dtype: String::from("str"),
-                    },
-                    schema::Field {
-                        name: String::from("min_x"),
-                        dtype: String::from("int"),
-                    },
-                    schema::Field {
-                        name: String::from("min_y"),
-                        dtype: String::from("int"),
-                    },
+                    schema::Field::new("label", "str"),
+                    schema::Field::new("min_x", "int"),
+                    schema::Field::new("min_y", "int"),
                 ]);
 
                 schema_writer.put_schema(&schema)?;
@@ -4,6 +4,7 @@
 //! Adds files during `oxen add` and computes files for `oxen status`
 //!
 
+use crate::api;
 use crate::constants;
 use crate::core::db;
 use crate::core::db::path_db;
@@ -11,12 +12,13 @@ use crate::core::df::tabular;
 use crate::core::index::oxenignore;
 use crate::core::index::{
     CommitDirEntryReader, CommitEntryReader, CommitReader, MergeConflictReader, Merger,
-    StagedDirEntryDB,
+    SchemaReader, StagedDirEntryDB,
 };
 use crate::error::OxenError;
 use crate::opts::DFOpts;
 
 use crate::model::schema;
+use crate::model::schema::FieldMetadata;
 use crate::model::{
     CommitEntry, LocalRepository, MergeConflict, StagedData, StagedDirStats, StagedEntry,
     StagedEntryStatus,
@@ -772,6 +774,46 @@ impl Stager {
         Ok(())
     }
 
+    /// Update the metadata of a field on the staged schemas with the name or hash `schema_ref`.
+    /// Values not set on `metadata` are kept, the ones named in `clear` are then removed.
+    pub fn update_schema_field_metadata(
+        &self,
+        schema_ref: &str,
+        field_name: &str,
+        metadata: &FieldMetadata,
+        clear: &[String],
+    ) -> Result<schema::Schema, OxenError> {
+        let mut updated: Option<schema::Schema> = None;
+        for (path, mut schema) in path_db::list_path_entries::<MultiThreaded, schema::Schema>(
+            &self.schemas_db,
+            Path::new(""),
+        )? {
+            if schema.hash != schema_ref && schema.name != Some(schema_ref.to_string()) {
+                continue;
+            }
+
+            let field = match schema.fields.iter_mut().find(|f| f.name == field_name) {
+                Some(field) => field,
+                None => return Err(OxenError::schema_does_not_have_field(field_name)),
+            };
+            let mut field_metadata = field.metadata.take().unwrap_or_default();
+            field_metadata.update(metadata);
+            for key in clear {
+                field_metadata.clear(key)?;
+            }
+            if !field_metadata.is_empty() {
+                field.metadata = Some(field_metadata);
+            }
+            path_db::put(&self.schemas_db, path, &schema)?;
+            updated = Some(schema);
+        }
+
+        match updated {
+            Some(schema) => Ok(schema),
+            None => Err(OxenError::schema_does_not_exist(schema_ref)),
+        }
+    }
+
     pub fn get_staged_schema(&self, schema_ref: &str) -> Result<Option<schema::Schema>, OxenError> {
         for schema in path_db::list_entries::<MultiThreaded, schema::Schema>(&self.schemas_db)? {
             if schema.hash == schema_ref || schema.name == Some(schema_ref.to_string()) {
@@ -781,6 +823,13 @@ impl Stager {
         Ok(None)
     }
 
+    pub fn get_staged_schema_for_path(
+        &self,
+        path: impl AsRef<Path>,
+    ) -> Result<Option<schema::Schema>, OxenError> {
+        path_db::get_entry(&self.schemas_db, path)
+    }
+
     pub fn list_staged_schemas(&self) -> Result<Vec<schema::Schema>, OxenError> {
         Ok(
             path_db::list_entries::<MultiThreaded, schema::Schema>(&self.schemas_db)?
@@ -843,7 +892,7 @@ impl Stager {
         if let Some(merger) = &self.merger {
             if merger.has_file(&path)? {
                 log::debug!("add_staged_entry_in_dir_db merger has file! {:?}", path);
-                self.add_staged_entry_to_db(&path, &staged_entry, staged_db)?;
+                self.add_staged_entry_to_db(&path, &staged_entry, None, staged_db)?;
                 merger.remove_conflict_path(&path)?;
                 return Ok(path);
             }
@@ -852,7 +901,8 @@ impl Stager {
         // Check if file has changed on disk
         // Since we are using a CommitDirEntryReader we need the base file name
         let basename = path.file_name().unwrap().to_str().unwrap();
-        if let Ok(Some(entry)) = entry_reader.get_entry(basename) {
+        let committed_entry = entry_reader.get_entry(basename).ok().flatten();
+        if let Some(entry) = &committed_entry {
             log::debug!(
                 "add_staged_entry_in_dir_db comparing hashes {:?} -> {:?}",
                 staged_entry,
@@ -876,7 +926,7 @@ impl Stager {
         }
 
         log::debug!("add_staged_entry_in_dir_db {:?} {:?}", path, staged_entry);
-        self.add_staged_entry_to_db(&path, &staged_entry, staged_db)?;
+        self.add_staged_entry_to_db(&path, &staged_entry, committed_entry.as_ref(), staged_db)?;
 
         Ok(path)
     }
@@ -885,6 +935,7 @@ impl Stager {
         &self,
         path: &Path,
         staged_entry: &StagedEntry,
+        committed_entry: Option<&CommitEntry>,
         staged_db: &StagedDirEntryDB<T>,
     ) -> Result<(), OxenError> {
         let relative = util::fs::path_relative_to_dir(path, &self.repository.path)?;
@@ -909,9 +960,24 @@ impl Stager {
                 );
                 let full_path = self.repository.path.join(path);
 
-                match tabular::read_df(&full_path, DFOpts::empty()) {
+                // Save the csv dialect on the schema so the file is always read the same way
+                let mut opts = DFOpts::empty();
+                opts.dialect = api::local::schemas::detect_csv_dialect(&self.repository, path)?;
+
+                match tabular::read_df(&full_path, opts.clone()) {
                     Ok(df) => {
-                        let schema = schema::Schema::from_polars(&df.schema());
+                        let mut schema = schema::Schema::from_polars(&df.schema());
+                        if let Some(dialect) = opts.dialect {
+                            schema = schema.with_dialect(dialect);
+                        }
+                        // Keep the field metadata that was committed with the previous version
+                        if let Some(entry) = committed_entry {
+                            let schema_reader =
+                                SchemaReader::new(&self.repository, &entry.commit_id)?;
+                            if let Some(committed) = schema_reader.get_schema_for_file(path)? {
+                                schema.copy_field_metadata(&committed);
+                            }
+                        }
                         log::debug!(
                             "add_staged_entry_to_db is tabular! got schema {:?} -> {:?}",
                             full_path,
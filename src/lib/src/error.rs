@@ -8,6 +8,7 @@ use std::fmt::Debug;
 use std::io;
 use std::path::{Path, PathBuf};
 
+use crate::model::schema::ContractViolation;
 use crate::model::Schema;
 use crate::model::{Commit, ParsedResource};
 use crate::model::{Remote, RepositoryNew};
@@ -58,6 +59,7 @@ pub enum OxenError {
 
     // Schema
     InvalidSchema(Box<Schema>),
+    SchemaContractViolation(Box<StringError>),
 
     // Generic
     ParsingError(Box<StringError>),
@@ -74,6 +76,7 @@ pub enum OxenError {
     Encoding(std::str::Utf8Error),
     DB(rocksdb::Error),
     ENV(std::env::VarError),
+    Image(image::ImageError),
 
     // Fallback
     Basic(StringError),
@@ -186,6 +189,16 @@ impl OxenError {
         OxenError::basic_str(err)
     }
 
+    pub fn schema_contract_violation(violations: &[ContractViolation]) -> OxenError {
+        let report = violations
+            .iter()
+            .map(|v| format!("  {v}"))
+            .collect::<Vec<String>>()
+            .join("\n");
+        let err = format!("Schema contract violated\n\n{report}\n");
+        OxenError::SchemaContractViolation(Box::new(StringError::from(err)))
+    }
+
     pub fn schema_has_changed(old_schema: Schema, current_schema: Schema) -> OxenError {
         let err =
             format!("\nSchema has changed\n\nOld\n{old_schema}\n\nCurrent\n{current_schema}\n");
@@ -423,3 +436,9 @@ impl From<std::env::VarError> for OxenError {
         OxenError::ENV(error)
     }
 }
+
+impl From<image::ImageError> for OxenError {
+    fn from(error: image::ImageError) -> Self {
+        OxenError::Image(error)
+    }
+}
@@ -3,10 +3,15 @@
 
 pub mod base_head;
 pub mod branch;
+pub mod column_stats;
 pub mod commit;
 pub mod content_type;
 pub mod data_frame_diff;
+pub mod diff_summary;
 pub mod entry;
+pub mod image_diff;
+pub mod image_meta_data;
+pub mod json_diff;
 pub mod merge_conflict;
 pub mod namespace;
 pub mod object_id;
@@ -23,7 +28,9 @@ pub mod user;
 // Repository
 pub use crate::model::repository::local_repository::{LocalRepository, RepositoryNew};
 pub use crate::model::repository::remote_repository::RemoteRepository;
-pub use crate::model::repository::repo_stats::{DataTypeStat, RepoStats};
+pub use crate::model::repository::repo_stats::{
+    DataTypeStat, DirStats, FileStats, RepoSizeLogEntry, RepoSizeStats, RepoStats,
+};
 
 // Commit
 pub use crate::model::base_head::BaseHead;
@@ -34,6 +41,7 @@ pub use crate::model::merge_conflict::MergeConflict;
 
 // Branch
 pub use crate::model::branch::Branch;
+pub use crate::model::column_stats::{ColumnStats, TabularStats};
 pub use crate::model::remote_branch::RemoteBranch;
 
 // Entry
@@ -58,7 +66,11 @@ pub use crate::model::summarized_staged_dir_stats::SummarizedStagedDirStats;
 pub use crate::model::remote::Remote;
 
 pub use crate::model::data_frame_diff::DataFrameDiff;
+pub use crate::model::diff_summary::{DiffSummary, DiffSummaryGroup};
+pub use crate::model::image_diff::{ImageDiff, ImageInfo};
+pub use crate::model::image_meta_data::ImageMetaData;
+pub use crate::model::json_diff::{JsonDiff, JsonPathChange};
 
-pub use crate::model::schema::Schema;
+pub use crate::model::schema::{CsvDialect, CsvDialectOverride, Schema};
 
 pub use crate::model::namespace::Namespace;
@@ -0,0 +1,278 @@
+use polars::prelude::{ChunkAgg, DataFrame, DataType, PolarsResult, Series, SeriesMethods};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::model::Schema;
+
+/// How many of the most common values to keep per column
+pub const NUM_TOP_VALUES: usize = 10;
+/// How many equal width bins the histogram of a numeric column has
+pub const NUM_HISTOGRAM_BINS: usize = 10;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+/// Values in [start, end), the last bin also includes its end
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HistogramBin {
+    pub start: f64,
+    pub end: f64,
+    pub count: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ColumnStats {
+    pub name: String,
+    pub dtype: String,
+    pub null_count: usize,
+    /// Numbers are compared as numbers, everything else by its string value
+    pub min: Option<String>,
+    pub max: Option<String>,
+    /// None for columns polars can not count the distinct values of, like lists
+    pub num_distinct: Option<usize>,
+    pub top_values: Vec<ValueCount>,
+    /// Only computed for numeric columns
+    pub histogram: Option<Vec<HistogramBin>>,
+}
+
+/// The stats of every column of a tabular file
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TabularStats {
+    pub path: PathBuf,
+    pub num_rows: usize,
+    pub columns: Vec<ColumnStats>,
+}
+
+impl ColumnStats {
+    pub fn from_series(series: &Series, dtype: impl AsRef<str>) -> ColumnStats {
+        let mut stats = ColumnStats {
+            name: series.name().to_string(),
+            dtype: dtype.as_ref().to_string(),
+            null_count: series.null_count(),
+            min: None,
+            max: None,
+            num_distinct: None,
+            top_values: vec![],
+            histogram: None,
+        };
+
+        if series.dtype().is_numeric() {
+            if let Ok(values) = series.cast(&DataType::Float64) {
+                let values: Vec<f64> = values
+                    .f64()
+                    .expect("cast to f64")
+                    .into_iter()
+                    .flatten()
+                    .filter(|v| !v.is_nan())
+                    .collect();
+                let min = values.iter().cloned().reduce(f64::min);
+                let max = values.iter().cloned().reduce(f64::max);
+                if let (Some(min), Some(max)) = (min, max) {
+                    stats.histogram = Some(histogram(&values, min, max));
+                }
+                stats.min = min.map(|v| v.to_string());
+                stats.max = max.map(|v| v.to_string());
+            }
+            // f64 can not hold every integer above 2^53, keep the native type for the range
+            if series.dtype().is_integer() {
+                if let Some((min, max)) = integer_range(series) {
+                    stats.min = Some(min);
+                    stats.max = Some(max);
+                }
+            }
+        }
+
+        // Nulls are counted as a distinct value by polars
+        stats.num_distinct =
+            series
+                .n_unique()
+                .ok()
+                .map(|n| if stats.null_count > 0 { n - 1 } else { n });
+
+        if let Ok(mut counts) = value_counts(series) {
+            if stats.histogram.is_none() {
+                stats.min = counts.iter().map(|(v, _)| v).min().cloned();
+                stats.max = counts.iter().map(|(v, _)| v).max().cloned();
+            }
+
+            counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            stats.top_values = counts
+                .into_iter()
+                .take(NUM_TOP_VALUES)
+                .map(|(value, count)| ValueCount { value, count })
+                .collect();
+        }
+
+        stats
+    }
+}
+
+fn integer_range(series: &Series) -> Option<(String, String)> {
+    if series.dtype().is_unsigned() {
+        let values = series.cast(&DataType::UInt64).ok()?;
+        let values = values.u64().expect("cast to u64");
+        Some((values.min()?.to_string(), values.max()?.to_string()))
+    } else {
+        let values = series.cast(&DataType::Int64).ok()?;
+        let values = values.i64().expect("cast to i64");
+        Some((values.min()?.to_string(), values.max()?.to_string()))
+    }
+}
+
+/// How many times each value occurs, grouped by polars so only the distinct values are turned into strings
+fn value_counts(series: &Series) -> PolarsResult<Vec<(String, usize)>> {
+    let counts = series.value_counts(true, false)?;
+    let values = counts.get_columns()[0].cast(&DataType::Utf8)?;
+    let counts = counts.get_columns()[1].idx()?;
+    Ok(values
+        .utf8()?
+        .into_iter()
+        .zip(counts.into_iter())
+        .filter_map(|(value, count)| Some((value?.to_string(), count? as usize)))
+        .collect())
+}
+
+fn histogram(values: &[f64], min: f64, max: f64) -> Vec<HistogramBin> {
+    let num_bins = if min == max { 1 } else { NUM_HISTOGRAM_BINS };
+    let width = (max - min) / num_bins as f64;
+    let mut bins: Vec<HistogramBin> = (0..num_bins)
+        .map(|i| HistogramBin {
+            start: min + width * i as f64,
+            end: if i + 1 == num_bins {
+                max
+            } else {
+                min + width * (i + 1) as f64
+            },
+            count: 0,
+        })
+        .collect();
+    for value in values {
+        let i = if width == 0.0 {
+            0
+        } else {
+            (((value - min) / width) as usize).min(num_bins - 1)
+        };
+        bins[i].count += 1;
+    }
+    bins
+}
+
+impl TabularStats {
+    pub fn from_df(path: impl AsRef<Path>, df: &DataFrame) -> TabularStats {
+        let schema = Schema::from_polars(&df.schema());
+        TabularStats {
+            path: path.as_ref().to_path_buf(),
+            num_rows: df.height(),
+            columns: df
+                .get_columns()
+                .iter()
+                .zip(schema.fields.iter())
+                .map(|(series, field)| ColumnStats::from_series(series, &field.dtype))
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for TabularStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = comfy_table::Table::new();
+        table.set_header(vec![
+            "column",
+            "dtype",
+            "nulls",
+            "min",
+            "max",
+            "distinct",
+            "top values",
+        ]);
+        for column in self.columns.iter() {
+            let top_values = column
+                .top_values
+                .iter()
+                .take(3)
+                .map(|v| format!("{} ({})", v.value, v.count))
+                .collect::<Vec<String>>()
+                .join(", ");
+            table.add_row(vec![
+                column.name.to_owned(),
+                column.dtype.to_owned(),
+                column.null_count.to_string(),
+                column.min.to_owned().unwrap_or_default(),
+                column.max.to_owned().unwrap_or_default(),
+                column
+                    .num_distinct
+                    .map(|n| n.to_string())
+                    .unwrap_or_default(),
+                top_values,
+            ]);
+        }
+        write!(f, "{} rows\n{}", self.num_rows, table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+
+    use crate::model::column_stats::{TabularStats, ValueCount};
+
+    #[test]
+    fn test_tabular_stats_from_df() {
+        let df = df!(
+            "label" => &[Some("cat"), Some("dog"), Some("cat"), None],
+            "width" => &[Some(10), Some(20), Some(110), None]
+        )
+        .unwrap();
+        let stats = TabularStats::from_df("annotations/labels.csv", &df);
+        assert_eq!(stats.num_rows, 4);
+
+        let label = &stats.columns[0];
+        assert_eq!(label.dtype, "str");
+        assert_eq!(label.null_count, 1);
+        assert_eq!(label.num_distinct, Some(2));
+        assert_eq!(label.min.as_deref(), Some("cat"));
+        assert_eq!(
+            label.top_values[0],
+            ValueCount {
+                value: String::from("cat"),
+                count: 2
+            }
+        );
+        assert!(label.histogram.is_none());
+
+        let width = &stats.columns[1];
+        assert_eq!(width.min.as_deref(), Some("10"));
+        assert_eq!(width.max.as_deref(), Some("110"));
+        let histogram = width.histogram.as_ref().unwrap();
+        assert_eq!(histogram.len(), 10);
+        assert_eq!(histogram[0].count, 1);
+        assert_eq!(histogram[1].count, 1);
+        assert_eq!(histogram[9].count, 1);
+        assert_eq!(histogram[9].end, 110.0);
+    }
+
+    #[test]
+    fn test_tabular_stats_keep_large_integers() {
+        let df = df!(
+            "id" => &[Some(9_007_199_254_740_993_i64), Some(1), None, Some(1)]
+        )
+        .unwrap();
+        let stats = TabularStats::from_df("ids.csv", &df);
+
+        let id = &stats.columns[0];
+        assert_eq!(id.min.as_deref(), Some("1"));
+        assert_eq!(id.max.as_deref(), Some("9007199254740993"));
+        assert_eq!(id.num_distinct, Some(2));
+        assert_eq!(
+            id.top_values[0],
+            ValueCount {
+                value: String::from("1"),
+                count: 2
+            }
+        );
+    }
+}
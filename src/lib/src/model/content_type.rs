@@ -7,6 +7,8 @@ pub enum ContentType {
     Json,
     Csv,
     Text,
+    ArrowStream,
+    Parquet,
 }
 
 impl std::str::FromStr for ContentType {
@@ -17,6 +19,8 @@ impl std::str::FromStr for ContentType {
             "json" => Ok(ContentType::Json),
             "csv" => Ok(ContentType::Csv),
             "text" => Ok(ContentType::Text),
+            "arrow" => Ok(ContentType::ArrowStream),
+            "parquet" => Ok(ContentType::Parquet),
             _ => Err(format!("Invalid data type: {}", s)),
         }
     }
@@ -28,6 +32,8 @@ impl ContentType {
             ContentType::Json => "application/json".to_string(),
             ContentType::Csv => "text/csv".to_string(),
             ContentType::Text => "text/plain".to_string(),
+            ContentType::ArrowStream => "application/vnd.apache.arrow.stream".to_string(),
+            ContentType::Parquet => "application/vnd.apache.parquet".to_string(),
         }
     }
 
@@ -36,9 +42,40 @@ impl ContentType {
             "application/json" => Ok(ContentType::Json),
             "text/csv" => Ok(ContentType::Csv),
             "text/plain" => Ok(ContentType::Text),
+            "application/vnd.apache.arrow.stream" => Ok(ContentType::ArrowStream),
+            "application/vnd.apache.parquet" | "application/x-parquet" => Ok(ContentType::Parquet),
             _ => Err(OxenError::basic_str(format!(
                 "Unsupported content-type: {s}"
             ))),
         }
     }
+
+    /// The first type in an Accept header that is supported, ignoring quality values.
+    /// "application/vnd.apache.arrow.stream, application/json;q=0.9" -> ArrowStream
+    pub fn from_accept_header(accept: &str) -> Option<ContentType> {
+        accept
+            .split(',')
+            .filter_map(|media| media.split(';').next())
+            .find_map(|media| ContentType::from_http_content_type(media.trim()).ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::ContentType;
+
+    #[test]
+    fn test_content_type_from_accept_header() {
+        assert_eq!(
+            ContentType::from_accept_header(
+                "application/vnd.apache.arrow.stream, application/json;q=0.9"
+            ),
+            Some(ContentType::ArrowStream)
+        );
+        assert_eq!(
+            ContentType::from_accept_header("text/html, application/x-parquet"),
+            Some(ContentType::Parquet)
+        );
+        assert_eq!(ContentType::from_accept_header("*/*"), None);
+    }
 }
@@ -0,0 +1,136 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::model::entry::diff_entry::DiffEntryStatus;
+
+/// Counts of changes for all the files in a directory that share a data type
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DiffSummaryGroup {
+    pub directory: String,
+    pub data_type: String,
+    pub num_added: usize,
+    pub num_modified: usize,
+    pub num_removed: usize,
+    pub bytes_delta: i64,
+    pub rows_delta: Option<i64>,
+}
+
+/// Summary of all the changes between two commits, grouped by directory and data type
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct DiffSummary {
+    pub num_added: usize,
+    pub num_modified: usize,
+    pub num_removed: usize,
+    pub bytes_delta: i64,
+    pub rows_delta: Option<i64>,
+    pub groups: Vec<DiffSummaryGroup>,
+}
+
+impl DiffSummaryGroup {
+    pub fn new(directory: impl AsRef<str>, data_type: impl AsRef<str>) -> DiffSummaryGroup {
+        DiffSummaryGroup {
+            directory: directory.as_ref().to_string(),
+            data_type: data_type.as_ref().to_string(),
+            num_added: 0,
+            num_modified: 0,
+            num_removed: 0,
+            bytes_delta: 0,
+            rows_delta: None,
+        }
+    }
+
+    pub fn add(&mut self, status: &DiffEntryStatus, bytes_delta: i64, rows_delta: Option<i64>) {
+        match status {
+            DiffEntryStatus::Added => self.num_added += 1,
+            DiffEntryStatus::Modified => self.num_modified += 1,
+            DiffEntryStatus::Removed => self.num_removed += 1,
+        }
+        self.bytes_delta += bytes_delta;
+        if let Some(rows_delta) = rows_delta {
+            self.rows_delta = Some(self.rows_delta.unwrap_or(0) + rows_delta);
+        }
+    }
+
+    pub fn num_changed(&self) -> usize {
+        self.num_added + self.num_modified + self.num_removed
+    }
+}
+
+impl DiffSummary {
+    pub fn from_groups(groups: Vec<DiffSummaryGroup>) -> DiffSummary {
+        let mut rows_delta: Option<i64> = None;
+        for group in groups.iter() {
+            if let Some(delta) = group.rows_delta {
+                rows_delta = Some(rows_delta.unwrap_or(0) + delta);
+            }
+        }
+
+        DiffSummary {
+            num_added: groups.iter().map(|g| g.num_added).sum(),
+            num_modified: groups.iter().map(|g| g.num_modified).sum(),
+            num_removed: groups.iter().map(|g| g.num_removed).sum(),
+            bytes_delta: groups.iter().map(|g| g.bytes_delta).sum(),
+            rows_delta,
+            groups,
+        }
+    }
+
+    pub fn num_changed(&self) -> usize {
+        self.num_added + self.num_modified + self.num_removed
+    }
+}
+
+fn signed_bytes_to_string(delta: i64) -> String {
+    let size = bytesize::ByteSize::b(delta.unsigned_abs());
+    if delta < 0 {
+        format!("-{size}")
+    } else {
+        format!("+{size}")
+    }
+}
+
+fn signed_rows_to_string(delta: Option<i64>) -> String {
+    match delta {
+        Some(delta) if delta < 0 => format!("{delta}"),
+        Some(delta) => format!("+{delta}"),
+        None => String::from("-"),
+    }
+}
+
+impl fmt::Display for DiffSummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut table = comfy_table::Table::new();
+        table.set_header(vec![
+            "directory",
+            "data type",
+            "added",
+            "modified",
+            "removed",
+            "bytes",
+            "rows",
+        ]);
+
+        for group in self.groups.iter() {
+            table.add_row(vec![
+                group.directory.to_string(),
+                group.data_type.to_string(),
+                group.num_added.to_string(),
+                group.num_modified.to_string(),
+                group.num_removed.to_string(),
+                signed_bytes_to_string(group.bytes_delta),
+                signed_rows_to_string(group.rows_delta),
+            ]);
+        }
+
+        write!(
+            f,
+            "{table}\n\n{} files changed, {} added, {} modified, {} removed ({} bytes, {} rows)",
+            self.num_changed(),
+            self.num_added,
+            self.num_modified,
+            self.num_removed,
+            signed_bytes_to_string(self.bytes_delta),
+            signed_rows_to_string(self.rows_delta),
+        )
+    }
+}
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::model::Commit;
+use crate::model::{Commit, ImageMetaData};
 use crate::view::entry::ResourceVersion;
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -11,4 +11,7 @@ pub struct DirEntry {
     pub latest_commit: Option<Commit>,
     pub datatype: String,
     pub resource: Option<ResourceVersion>,
+    /// Set for images once their commit is processed
+    #[serde(default)]
+    pub image_meta_data: Option<ImageMetaData>,
 }
@@ -0,0 +1,100 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+/// Basic properties of one version of an image
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ImageInfo {
+    pub format: String,
+    pub color_mode: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Summary of the differences between two versions of an image
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ImageDiff {
+    pub base: ImageInfo,
+    pub head: ImageInfo,
+    /// If the dimensions differ, head is resized to the base dimensions before comparing pixels
+    pub resized_for_comparison: bool,
+    /// Fraction of pixels that are not identical, between 0.0 and 1.0
+    pub pixel_diff_ratio: f64,
+    /// Mean absolute difference over all channels, normalized between 0.0 and 1.0
+    pub mean_abs_diff: f64,
+    /// Hamming distance between the 64 bit perceptual hashes, 0 means visually the same
+    pub phash_distance: u32,
+    /// Where the mask of the changed pixels was written, None if no pixels changed or no mask was asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask_path: Option<PathBuf>,
+}
+
+impl ImageDiff {
+    pub fn dimensions_changed(&self) -> bool {
+        self.base.width != self.head.width || self.base.height != self.head.height
+    }
+
+    pub fn format_changed(&self) -> bool {
+        self.base.format != self.head.format
+    }
+
+    pub fn color_mode_changed(&self) -> bool {
+        self.base.color_mode != self.head.color_mode
+    }
+
+    pub fn is_identical(&self) -> bool {
+        !self.dimensions_changed()
+            && !self.format_changed()
+            && !self.color_mode_changed()
+            && self.pixel_diff_ratio == 0.0
+    }
+}
+
+impl fmt::Display for ImageDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut results: Vec<String> = vec![];
+        if self.dimensions_changed() {
+            results.push(format!(
+                "dimensions: {}x{} -> {}x{}",
+                self.base.width, self.base.height, self.head.width, self.head.height
+            ));
+        } else {
+            results.push(format!(
+                "dimensions: {}x{}",
+                self.base.width, self.base.height
+            ));
+        }
+
+        if self.format_changed() {
+            results.push(format!(
+                "format: {} -> {}",
+                self.base.format, self.head.format
+            ));
+        } else {
+            results.push(format!("format: {}", self.base.format));
+        }
+
+        if self.color_mode_changed() {
+            results.push(format!(
+                "color mode: {} -> {}",
+                self.base.color_mode, self.head.color_mode
+            ));
+        } else {
+            results.push(format!("color mode: {}", self.base.color_mode));
+        }
+
+        let resized = if self.resized_for_comparison {
+            " (resized to compare)"
+        } else {
+            ""
+        };
+        results.push(format!(
+            "pixels changed: {:.2}%{resized}",
+            self.pixel_diff_ratio * 100.0
+        ));
+        results.push(format!("mean abs diff: {:.4}", self.mean_abs_diff));
+        results.push(format!("perceptual hash distance: {}", self.phash_distance));
+
+        write!(f, "{}", results.join("\n"))
+    }
+}
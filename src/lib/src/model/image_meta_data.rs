@@ -0,0 +1,13 @@
+use serde::{Deserialize, Serialize};
+
+/// Properties of an image that are cached when its commit is processed
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct ImageMetaData {
+    pub format: String,
+    /// Dimensions as stored in the file, before the EXIF orientation is applied
+    pub width: u32,
+    pub height: u32,
+    pub channels: u8,
+    /// EXIF orientation tag from 1 to 8, None if the image has no EXIF data
+    pub orientation: Option<u16>,
+}
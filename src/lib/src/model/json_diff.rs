@@ -0,0 +1,129 @@
+use colored::Colorize;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// A single path within a json document that differs between two versions.
+/// Paths are written `$.key[0].nested` starting from the document root `$`
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct JsonPathChange {
+    pub path: String,
+    pub old_value: Option<Value>,
+    pub new_value: Option<Value>,
+}
+
+/// Structural diff of two json documents, independent of key order and formatting
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct JsonDiff {
+    pub added: Vec<JsonPathChange>,
+    pub removed: Vec<JsonPathChange>,
+    pub changed: Vec<JsonPathChange>,
+}
+
+impl JsonDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Recursively compare two json values, objects are compared by key and arrays by index
+    pub fn from_values(original: &Value, compare: &Value) -> JsonDiff {
+        let mut diff = JsonDiff::default();
+        diff.compare_values("$", original, compare);
+        diff
+    }
+
+    fn compare_values(&mut self, path: &str, original: &Value, compare: &Value) {
+        match (original, compare) {
+            (Value::Object(original_map), Value::Object(compare_map)) => {
+                for (key, original_val) in original_map.iter() {
+                    let key_path = format!("{path}.{key}");
+                    match compare_map.get(key) {
+                        Some(compare_val) => {
+                            self.compare_values(&key_path, original_val, compare_val)
+                        }
+                        None => self.removed.push(JsonPathChange {
+                            path: key_path,
+                            old_value: Some(original_val.to_owned()),
+                            new_value: None,
+                        }),
+                    }
+                }
+
+                for (key, compare_val) in compare_map.iter() {
+                    if !original_map.contains_key(key) {
+                        self.added.push(JsonPathChange {
+                            path: format!("{path}.{key}"),
+                            old_value: None,
+                            new_value: Some(compare_val.to_owned()),
+                        });
+                    }
+                }
+            }
+            (Value::Array(original_arr), Value::Array(compare_arr)) => {
+                for (i, original_val) in original_arr.iter().enumerate() {
+                    let idx_path = format!("{path}[{i}]");
+                    match compare_arr.get(i) {
+                        Some(compare_val) => {
+                            self.compare_values(&idx_path, original_val, compare_val)
+                        }
+                        None => self.removed.push(JsonPathChange {
+                            path: idx_path,
+                            old_value: Some(original_val.to_owned()),
+                            new_value: None,
+                        }),
+                    }
+                }
+
+                for (i, compare_val) in compare_arr.iter().enumerate().skip(original_arr.len()) {
+                    self.added.push(JsonPathChange {
+                        path: format!("{path}[{i}]"),
+                        old_value: None,
+                        new_value: Some(compare_val.to_owned()),
+                    });
+                }
+            }
+            (original, compare) => {
+                if original != compare {
+                    self.changed.push(JsonPathChange {
+                        path: path.to_string(),
+                        old_value: Some(original.to_owned()),
+                        new_value: Some(compare.to_owned()),
+                    });
+                }
+            }
+        }
+    }
+}
+
+fn value_to_string(value: &Option<Value>) -> String {
+    match value {
+        Some(value) => value.to_string(),
+        None => String::from("null"),
+    }
+}
+
+impl fmt::Display for JsonDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut results: Vec<String> = vec![];
+        for change in self.added.iter() {
+            let line = format!("+ {}: {}", change.path, value_to_string(&change.new_value));
+            results.push(line.green().to_string());
+        }
+
+        for change in self.removed.iter() {
+            let line = format!("- {}: {}", change.path, value_to_string(&change.old_value));
+            results.push(line.red().to_string());
+        }
+
+        for change in self.changed.iter() {
+            let line = format!(
+                "~ {}: {} -> {}",
+                change.path,
+                value_to_string(&change.old_value),
+                value_to_string(&change.new_value)
+            );
+            results.push(line.yellow().to_string());
+        }
+        write!(f, "{}", results.join("\n"))
+    }
+}
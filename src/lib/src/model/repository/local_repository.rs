@@ -4,7 +4,7 @@ use crate::constants::DEFAULT_REMOTE_NAME;
 use crate::constants::SHALLOW_FLAG;
 use crate::core::index::EntryIndexer;
 use crate::error::OxenError;
-use crate::model::{Commit, Remote, RemoteBranch, RemoteRepository};
+use crate::model::{Commit, CsvDialectOverride, Remote, RemoteBranch, RemoteRepository};
 use crate::opts::CloneOpts;
 use crate::util;
 use crate::view::RepositoryView;
@@ -62,6 +62,9 @@ pub struct LocalRepository {
     pub path: PathBuf,
     remote_name: Option<String>, // this is the current remote name
     pub remotes: Vec<Remote>,
+    /// Per path overrides for csv files whose dialect cannot be detected reliably
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub csv_dialects: Vec<CsvDialectOverride>,
 }
 
 impl LocalRepository {
@@ -71,6 +74,7 @@ impl LocalRepository {
             path: path.to_path_buf(),
             remotes: vec![],
             remote_name: None,
+            csv_dialects: vec![],
         })
     }
 
@@ -79,6 +83,7 @@ impl LocalRepository {
             path: std::env::current_dir()?.join(view.name),
             remotes: vec![],
             remote_name: None,
+            csv_dialects: vec![],
         })
     }
 
@@ -87,6 +92,7 @@ impl LocalRepository {
             path: path.to_owned(),
             remotes: vec![repo.remote],
             remote_name: Some(String::from(constants::DEFAULT_REMOTE_NAME)),
+            csv_dialects: vec![],
         })
     }
 
@@ -105,6 +111,12 @@ impl LocalRepository {
         Ok(repo)
     }
 
+    /// The configured csv dialect override for a path relative to the repo root
+    pub fn csv_dialect_override(&self, path: impl AsRef<Path>) -> Option<&CsvDialectOverride> {
+        let path = path.as_ref();
+        self.csv_dialects.iter().find(|o| o.path == path)
+    }
+
     pub fn dirname(&self) -> String {
         String::from(self.path.file_name().unwrap().to_str().unwrap())
     }
@@ -1,8 +1,14 @@
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+use crate::model::Commit;
+
+/// How many of the largest files are kept in the stats of a commit
+pub const NUM_LARGEST_FILES: usize = 100;
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct DataTypeStat {
     pub data_type: String,
     pub data_size: u64,
@@ -14,3 +20,179 @@ pub struct RepoStats {
     pub data_size: u64,
     pub data_types: HashMap<String, DataTypeStat>,
 }
+
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct FileStats {
+    pub path: PathBuf,
+    pub data_type: String,
+    pub data_size: u64,
+    /// Only known for tabular files whose column stats were computed
+    pub num_rows: Option<usize>,
+}
+
+/// Everything below a directory, including its subdirectories
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct DirStats {
+    pub path: PathBuf,
+    pub data_size: u64,
+    pub file_count: usize,
+}
+
+/// The size and composition of a repository at a commit
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct RepoSizeStats {
+    pub data_size: u64,
+    pub file_count: usize,
+    pub data_types: HashMap<String, DataTypeStat>,
+    /// Every directory, largest first
+    pub dirs: Vec<DirStats>,
+    /// The largest files, largest first
+    pub largest_files: Vec<FileStats>,
+    /// The files with a row count, sorted by path
+    pub tabular_files: Vec<FileStats>,
+}
+
+/// The stats of the repository after a commit
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RepoSizeLogEntry {
+    pub commit: Commit,
+    pub stats: RepoSizeStats,
+}
+
+impl RepoSizeStats {
+    pub fn from_files(mut files: Vec<FileStats>) -> RepoSizeStats {
+        let mut data_types: HashMap<String, DataTypeStat> = HashMap::new();
+        let mut dirs: HashMap<PathBuf, DirStats> = HashMap::new();
+        for file in files.iter() {
+            let stat = data_types
+                .entry(file.data_type.to_owned())
+                .or_insert(DataTypeStat {
+                    data_type: file.data_type.to_owned(),
+                    data_size: 0,
+                    file_count: 0,
+                });
+            stat.data_size += file.data_size;
+            stat.file_count += 1;
+
+            for dir in file.path.ancestors().skip(1) {
+                if dir == Path::new("") {
+                    break;
+                }
+                let stat = dirs.entry(dir.to_path_buf()).or_insert(DirStats {
+                    path: dir.to_path_buf(),
+                    data_size: 0,
+                    file_count: 0,
+                });
+                stat.data_size += file.data_size;
+                stat.file_count += 1;
+            }
+        }
+
+        let mut dirs: Vec<DirStats> = dirs.into_values().collect();
+        dirs.sort_by(|a, b| b.data_size.cmp(&a.data_size).then(a.path.cmp(&b.path)));
+
+        let mut tabular_files: Vec<FileStats> = files
+            .iter()
+            .filter(|f| f.num_rows.is_some())
+            .cloned()
+            .collect();
+        tabular_files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        files.sort_by(|a, b| b.data_size.cmp(&a.data_size).then(a.path.cmp(&b.path)));
+        let file_count = files.len();
+        let data_size = files.iter().map(|f| f.data_size).sum();
+        files.truncate(NUM_LARGEST_FILES);
+
+        RepoSizeStats {
+            data_size,
+            file_count,
+            data_types,
+            dirs,
+            largest_files: files,
+            tabular_files,
+        }
+    }
+
+    /// The stats of everything below `path`, the root of the repository if it is empty
+    pub fn dir(&self, path: impl AsRef<Path>) -> Option<DirStats> {
+        let path = path.as_ref();
+        if path == Path::new("") {
+            Some(DirStats {
+                path: path.to_path_buf(),
+                data_size: self.data_size,
+                file_count: self.file_count,
+            })
+        } else {
+            self.dirs.iter().find(|d| d.path == path).cloned()
+        }
+    }
+
+    /// The directories directly in `path`, largest first
+    pub fn subdirs(&self, path: impl AsRef<Path>) -> Vec<&DirStats> {
+        let path = path.as_ref();
+        self.dirs
+            .iter()
+            .filter(|d| d.path.parent() == Some(path))
+            .collect()
+    }
+
+    /// The largest files of the repository that are below `path`, largest first
+    pub fn largest_files_in(&self, path: impl AsRef<Path>) -> Vec<&FileStats> {
+        let path = path.as_ref();
+        self.largest_files
+            .iter()
+            .filter(|f| f.path.starts_with(path))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::model::repository::repo_stats::{FileStats, RepoSizeStats};
+
+    fn file(path: &str, data_type: &str, data_size: u64, num_rows: Option<usize>) -> FileStats {
+        FileStats {
+            path: PathBuf::from(path),
+            data_type: data_type.to_string(),
+            data_size,
+            num_rows,
+        }
+    }
+
+    #[test]
+    fn test_repo_size_stats_from_files() {
+        let stats = RepoSizeStats::from_files(vec![
+            file("README.md", "markdown", 10, None),
+            file("images/train/cat.jpg", "image", 300, None),
+            file("images/test/dog.jpg", "image", 200, None),
+            file("annotations/labels.csv", "tabular", 50, Some(4)),
+        ]);
+
+        assert_eq!(stats.data_size, 560);
+        assert_eq!(stats.file_count, 4);
+        assert_eq!(stats.data_types["image"].data_size, 500);
+        assert_eq!(stats.data_types["image"].file_count, 2);
+        assert_eq!(
+            stats.largest_files[0].path,
+            Path::new("images/train/cat.jpg")
+        );
+        assert_eq!(stats.tabular_files.len(), 1);
+        assert_eq!(stats.tabular_files[0].num_rows, Some(4));
+
+        let images = stats.dir("images").unwrap();
+        assert_eq!(images.data_size, 500);
+        assert_eq!(images.file_count, 2);
+        assert_eq!(stats.dir("").unwrap().data_size, 560);
+        assert!(stats.dir("videos").is_none());
+
+        let top_level: Vec<&Path> = stats.subdirs("").iter().map(|d| d.path.as_path()).collect();
+        assert_eq!(
+            top_level,
+            vec![Path::new("images"), Path::new("annotations")]
+        );
+        assert_eq!(stats.subdirs("images").len(), 2);
+        assert_eq!(stats.largest_files_in("images/test").len(), 1);
+    }
+}
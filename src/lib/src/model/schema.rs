@@ -1,10 +1,20 @@
+pub mod contract;
+pub mod csv_dialect;
 pub mod data_type;
 pub mod field;
+pub mod field_metadata;
+pub mod schema_diff;
 
-pub use data_type::DataType;
+pub use contract::{ContractViolation, FieldContract, SchemaContract, SchemaContracts};
+pub use csv_dialect::{CsvDialect, CsvDialectOverride};
+pub use data_type::{DataType, TimeUnit};
 pub use field::Field;
+use field_metadata::ConstraintCheck;
+pub use field_metadata::FieldMetadata;
 use itertools::Itertools;
+pub use schema_diff::{DialectChange, FieldChange, SchemaDiff, SchemaFileDiff, SchemaLogEntry};
 
+use crate::error::OxenError;
 use crate::util::hasher;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -14,6 +24,9 @@ pub struct Schema {
     pub name: Option<String>,
     pub hash: String,
     pub fields: Vec<Field>,
+    /// Only set for delimited text files that are not plain comma separated utf-8
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<CsvDialect>,
 }
 
 impl Schema {
@@ -22,17 +35,33 @@ impl Schema {
             name: None,
             hash: Schema::hash_fields(&fields),
             fields: fields.to_owned(),
+            dialect: None,
+        }
+    }
+
+    /// Attach the dialect the file was read with. Default dialects are not saved.
+    /// The hash only covers the fields, the dialect is saved per file next to it.
+    pub fn with_dialect(mut self, dialect: CsvDialect) -> Schema {
+        self.dialect = if dialect.is_default() {
+            None
+        } else {
+            Some(dialect)
+        };
+        self
+    }
+
+    /// The fields shared by every file with this hash, without the dialect of one of them
+    pub fn without_dialect(&self) -> Schema {
+        Schema {
+            dialect: None,
+            ..self.to_owned()
         }
     }
 
     pub fn to_polars(&self) -> polars::prelude::Schema {
         let mut schema = polars::prelude::Schema::new();
         for field in self.fields.iter() {
-            let data_type = DataType::from_string(&field.dtype);
-            schema.with_column(
-                field.name.to_owned().into(),
-                DataType::to_polars(&data_type),
-            );
+            schema.with_column(field.name.to_owned().into(), field.data_type().to_polars());
         }
 
         schema
@@ -41,17 +70,14 @@ impl Schema {
     pub fn from_polars(schema: &polars::prelude::Schema) -> Schema {
         let mut fields: Vec<Field> = vec![];
         for field in schema.iter_fields() {
-            let f = Field {
-                name: field.name().to_string(),
-                dtype: field.data_type().to_string(),
-            };
-            fields.push(f);
+            fields.push(Field::from_polars(&field));
         }
 
         Schema {
             name: None,
             hash: Schema::hash_fields(&fields),
             fields,
+            dialect: None,
         }
     }
 
@@ -102,10 +128,90 @@ impl Schema {
         self.fields.iter().find(|f| f.name == name)
     }
 
+    /// Copy the field metadata from `other` onto fields with the same name that have none.
+    /// Metadata is kept when the dtype changed, with a warning since its constraints may no longer fit.
+    pub fn copy_field_metadata(&mut self, other: &Schema) {
+        for field in self.fields.iter_mut() {
+            if field.metadata.is_some() {
+                continue;
+            }
+            if let Some(other_field) = other.get_field(&field.name) {
+                if other_field.metadata.is_some() && other_field.dtype != field.dtype {
+                    log::warn!(
+                        "Field '{}' changed from {} to {}, keeping its metadata: {}",
+                        field.name,
+                        other_field.dtype,
+                        field.dtype,
+                        other_field.metadata.as_ref().unwrap()
+                    );
+                }
+                field.metadata = other_field.metadata.to_owned();
+            }
+        }
+    }
+
+    /// Whether any field has metadata
+    pub fn has_field_metadata(&self) -> bool {
+        self.fields.iter().any(|f| f.metadata.is_some())
+    }
+
+    pub fn has_field_constraints(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|f| f.metadata.as_ref().map_or(false, |m| m.has_constraints()))
+    }
+
+    /// Describe the values in the columns of `df` that fall outside the constraints declared on the fields.
+    /// The values are counted in one streaming aggregation, `df` is not collected.
+    pub fn check_field_constraints(
+        &self,
+        df: polars::prelude::LazyFrame,
+    ) -> Result<Vec<String>, OxenError> {
+        let df_schema = df
+            .schema()
+            .map_err(|err| OxenError::basic_str(err.to_string()))?;
+        let mut checks: Vec<ConstraintCheck> = vec![];
+        for field in self.fields.iter() {
+            if let (Some(metadata), Some(dtype)) = (&field.metadata, df_schema.get(&field.name)) {
+                checks.extend(metadata.constraint_checks(&field.name, dtype));
+            }
+        }
+        field_metadata::run_constraint_checks(df, checks)
+    }
+
+    /// A table of the fields and their metadata, for `oxen schemas show`
+    pub fn verbose_str(&self) -> String {
+        let mut table = comfy_table::Table::new();
+        if self.has_field_metadata() {
+            table.set_header(vec!["column", "dtype", "metadata"]);
+        } else {
+            table.set_header(vec!["column", "dtype"]);
+        }
+
+        for field in self.fields.iter() {
+            let mut row = vec![field.name.to_owned(), field.dtype.to_owned()];
+            if self.has_field_metadata() {
+                match &field.metadata {
+                    Some(metadata) => row.push(metadata.to_string()),
+                    None => row.push(String::new()),
+                }
+            }
+            table.add_row(row);
+        }
+        table.to_string()
+    }
+
     fn hash_fields(fields: &Vec<Field>) -> String {
         let mut hash_buffers: Vec<String> = vec![];
         for f in fields {
-            hash_buffers.push(format!("{}{}", f.name, f.dtype));
+            // Struct columns hash the way polars printed them, so their files keep the hash they were committed with
+            let data_type = f.data_type();
+            let dtype = if data_type.contains_struct() {
+                data_type.to_hash_string()
+            } else {
+                f.dtype.to_owned()
+            };
+            hash_buffers.push(format!("{}{}", f.name, dtype));
         }
 
         let buffer_str = hash_buffers.join("");
@@ -149,6 +255,11 @@ impl Schema {
         fields
     }
 
+    /// Compare to an older version of the schema: added, removed, renamed and retyped fields
+    pub fn diff(&self, base: &Schema) -> SchemaDiff {
+        SchemaDiff::between(base, self)
+    }
+
     pub fn schemas_to_string<S: AsRef<Vec<Schema>>>(schemas: S) -> String {
         let schemas = schemas.as_ref();
         let mut table = comfy_table::Table::new();
@@ -182,18 +293,34 @@ impl std::error::Error for Schema {}
 
 #[cfg(test)]
 mod tests {
+    use crate::model::schema::CsvDialect;
     use crate::model::schema::Field;
     use crate::model::schema::Schema;
 
+    #[test]
+    fn test_schema_dialect_is_not_part_of_the_hash() {
+        let fields = vec![Field::new("file", "str"), Field::new("label", "str")];
+        let csv = Schema::from_fields(fields.to_owned());
+        let tsv = Schema::from_fields(fields).with_dialect(CsvDialect::with_delimiter('\t'));
+
+        assert_eq!(csv.hash, tsv.hash);
+        assert_eq!(tsv.dialect, Some(CsvDialect::with_delimiter('\t')));
+        let diff = tsv.diff(&csv);
+        assert!(!diff.is_empty());
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.dialect.unwrap().to.delimiter, '\t');
+
+        let default = Schema::from_fields(vec![]).with_dialect(CsvDialect::default());
+        assert!(default.dialect.is_none());
+    }
+
     #[test]
     fn test_schemas_to_string_one_field() {
         let schemas = vec![Schema {
             name: Some("bounding_box".to_string()),
             hash: "1234".to_string(),
-            fields: vec![Field {
-                name: "file".to_string(),
-                dtype: "".to_string(),
-            }],
+            dialect: None,
+            fields: vec![Field::new("file", "")],
         }];
         let table = Schema::schemas_to_string(schemas);
         assert_eq!(
@@ -213,27 +340,13 @@ mod tests {
         let schemas = vec![Schema {
             name: Some("bounding_box".to_string()),
             hash: "1234".to_string(),
+            dialect: None,
             fields: vec![
-                Field {
-                    name: "file".to_string(),
-                    dtype: "str".to_string(),
-                },
-                Field {
-                    name: "x".to_string(),
-                    dtype: "i64".to_string(),
-                },
-                Field {
-                    name: "y".to_string(),
-                    dtype: "i64".to_string(),
-                },
-                Field {
-                    name: "w".to_string(),
-                    dtype: "f64".to_string(),
-                },
-                Field {
-                    name: "h".to_string(),
-                    dtype: "f64".to_string(),
-                },
+                Field::new("file", "str"),
+                Field::new("x", "i64"),
+                Field::new("y", "i64"),
+                Field::new("w", "f64"),
+                Field::new("h", "f64"),
             ],
         }];
         let table = Schema::schemas_to_string(schemas);
@@ -255,45 +368,23 @@ mod tests {
             Schema {
                 name: Some("bounding_box".to_string()),
                 hash: "1234".to_string(),
+                dialect: None,
                 fields: vec![
-                    Field {
-                        name: "file".to_string(),
-                        dtype: "str".to_string(),
-                    },
-                    Field {
-                        name: "x".to_string(),
-                        dtype: "i64".to_string(),
-                    },
-                    Field {
-                        name: "y".to_string(),
-                        dtype: "i64".to_string(),
-                    },
-                    Field {
-                        name: "w".to_string(),
-                        dtype: "f64".to_string(),
-                    },
-                    Field {
-                        name: "h".to_string(),
-                        dtype: "f64".to_string(),
-                    },
+                    Field::new("file", "str"),
+                    Field::new("x", "i64"),
+                    Field::new("y", "i64"),
+                    Field::new("w", "f64"),
+                    Field::new("h", "f64"),
                 ],
             },
             Schema {
                 name: None,
                 hash: "5432".to_string(),
+                dialect: None,
                 fields: vec![
-                    Field {
-                        name: "file".to_string(),
-                        dtype: "str".to_string(),
-                    },
-                    Field {
-                        name: "x".to_string(),
-                        dtype: "i64".to_string(),
-                    },
-                    Field {
-                        name: "y".to_string(),
-                        dtype: "i64".to_string(),
-                    },
+                    Field::new("file", "str"),
+                    Field::new("x", "i64"),
+                    Field::new("y", "i64"),
                 ],
             },
         ];
@@ -0,0 +1,283 @@
+use ignore::gitignore::GitignoreBuilder;
+use polars::prelude::DataFrame;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::error::OxenError;
+use crate::model::schema::DataType;
+use crate::model::Schema;
+
+fn default_true() -> bool {
+    true
+}
+
+/// A field that files under a contract have to contain
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct FieldContract {
+    pub name: String,
+    /// Any dtype is allowed if not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dtype: Option<String>,
+    #[serde(default = "default_true")]
+    pub required: bool,
+    #[serde(default = "default_true")]
+    pub nullable: bool,
+}
+
+impl FieldContract {
+    pub fn new(name: impl AsRef<str>, dtype: impl AsRef<str>) -> FieldContract {
+        FieldContract {
+            name: name.as_ref().to_string(),
+            dtype: Some(dtype.as_ref().to_string()),
+            required: true,
+            nullable: true,
+        }
+    }
+
+    fn dtype_matches(&self, dtype: &str) -> bool {
+        match &self.dtype {
+            Some(expected) => normalize_dtype(expected) == normalize_dtype(dtype),
+            None => true,
+        }
+    }
+}
+
+// "uint8" and "u8" are the same type, fall back to the raw string for types we do not model
+fn normalize_dtype(dtype: &str) -> String {
+    match DataType::from_string(dtype) {
+        DataType::Unknown => dtype.to_string(),
+        dtype => dtype.to_string(),
+    }
+}
+
+/// The fields that every tabular file matching `path` has to have, pinned in the .oxencontracts file
+/// at the repository root so that the contracts are versioned and pushed with the data
+///
+/// ```toml
+/// [[schema_contracts]]
+/// path = "annotations/**/*.csv"
+///
+/// [[schema_contracts.fields]]
+/// name = "file"
+/// dtype = "str"
+/// nullable = false
+///
+/// [[schema_contracts.fields]]
+/// name = "label"
+/// dtype = "str"
+/// ```
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct SchemaContract {
+    /// A .gitignore style glob relative to the repo root, "annotations/**/*.csv"
+    pub path: String,
+    pub fields: Vec<FieldContract>,
+    /// Whether files may have columns that are not in `fields`
+    #[serde(default)]
+    pub allow_extra_fields: bool,
+}
+
+/// All the contracts in a .oxencontracts file
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct SchemaContracts {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub schema_contracts: Vec<SchemaContract>,
+}
+
+impl SchemaContracts {
+    pub fn from_toml(contents: &str) -> Result<SchemaContracts, OxenError> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String, OxenError> {
+        Ok(toml::to_string(self)?)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.schema_contracts.is_empty()
+    }
+
+    /// The contracts that apply to a path relative to the repo root
+    pub fn for_path(&self, path: impl AsRef<Path>) -> Vec<&SchemaContract> {
+        let path = path.as_ref();
+        self.schema_contracts
+            .iter()
+            .filter(|c| c.matches(path))
+            .collect()
+    }
+}
+
+/// One column of one file that does not follow its contract
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ContractViolation {
+    pub path: PathBuf,
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for ContractViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: column '{}' {}",
+            self.path.display(),
+            self.field,
+            self.message
+        )
+    }
+}
+
+impl SchemaContract {
+    /// Pin the fields and dtypes of an existing schema, all of them required and nullable
+    pub fn from_schema(path: impl AsRef<str>, schema: &Schema) -> SchemaContract {
+        SchemaContract {
+            path: path.as_ref().to_string(),
+            fields: schema
+                .fields
+                .iter()
+                .map(|field| FieldContract::new(&field.name, &field.dtype))
+                .collect(),
+            allow_extra_fields: false,
+        }
+    }
+
+    pub fn matches(&self, path: impl AsRef<Path>) -> bool {
+        let mut builder = GitignoreBuilder::new("");
+        if let Err(err) = builder.add_line(None, &self.path) {
+            log::warn!("Invalid schema contract path {:?}: {err}", self.path);
+            return false;
+        }
+        match builder.build() {
+            Ok(glob) => glob
+                .matched_path_or_any_parents(path.as_ref(), false)
+                .is_ignore(),
+            Err(_) => false,
+        }
+    }
+
+    /// Check the fields and dtypes of a schema, nullability can only be checked on the data
+    pub fn check_schema(&self, path: &Path, schema: &Schema) -> Vec<ContractViolation> {
+        let violation = |field: &str, message: String| ContractViolation {
+            path: path.to_path_buf(),
+            field: field.to_string(),
+            message,
+        };
+
+        let mut violations: Vec<ContractViolation> = vec![];
+        for expected in self.fields.iter() {
+            match schema.get_field(&expected.name) {
+                Some(field) => {
+                    if !expected.dtype_matches(&field.dtype) {
+                        violations.push(violation(
+                            &expected.name,
+                            format!(
+                                "has dtype {} but the contract requires {}",
+                                field.dtype,
+                                expected.dtype.as_deref().unwrap_or_default()
+                            ),
+                        ));
+                    }
+                }
+                None if expected.required => {
+                    // Point out renames that only changed the case
+                    let similar = schema
+                        .fields
+                        .iter()
+                        .find(|f| f.name.to_lowercase() == expected.name.to_lowercase());
+                    let message = match similar {
+                        Some(similar) => format!("is missing, found '{}'", similar.name),
+                        None => String::from("is missing"),
+                    };
+                    violations.push(violation(&expected.name, message));
+                }
+                None => {}
+            }
+        }
+
+        if !self.allow_extra_fields {
+            for field in schema.fields.iter() {
+                if !self.fields.iter().any(|f| f.name == field.name) {
+                    violations.push(violation(
+                        &field.name,
+                        String::from("is not in the contract"),
+                    ));
+                }
+            }
+        }
+        violations
+    }
+
+    /// Check the schema of the data frame and that non nullable fields have no nulls
+    pub fn check_df(&self, path: &Path, df: &DataFrame) -> Vec<ContractViolation> {
+        let schema = Schema::from_polars(&df.schema());
+        let mut violations = self.check_schema(path, &schema);
+        for expected in self.fields.iter().filter(|f| !f.nullable) {
+            if let Ok(column) = df.column(&expected.name) {
+                let null_count = column.null_count();
+                if null_count > 0 {
+                    violations.push(ContractViolation {
+                        path: path.to_path_buf(),
+                        field: expected.name.to_owned(),
+                        message: format!("has {null_count} null values but is not nullable"),
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+    use std::path::Path;
+
+    use crate::model::schema::{Field, FieldContract, SchemaContract};
+    use crate::model::Schema;
+
+    fn contract() -> SchemaContract {
+        let mut label = FieldContract::new("label", "str");
+        label.nullable = false;
+        SchemaContract {
+            path: String::from("annotations/**/*.csv"),
+            fields: vec![FieldContract::new("file", "str"), label],
+            allow_extra_fields: false,
+        }
+    }
+
+    #[test]
+    fn test_schema_contract_matches_glob() {
+        let contract = contract();
+        assert!(contract.matches("annotations/train/bounding_box.csv"));
+        assert!(!contract.matches("annotations/train/bounding_box.tsv"));
+        assert!(!contract.matches("labels.csv"));
+    }
+
+    #[test]
+    fn test_schema_contract_reports_each_column() {
+        let schema =
+            Schema::from_fields(vec![Field::new("file", "i64"), Field::new("Label", "str")]);
+        let violations = contract().check_schema(Path::new("annotations/a.csv"), &schema);
+        let messages: Vec<String> = violations.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "annotations/a.csv: column 'file' has dtype i64 but the contract requires str",
+                "annotations/a.csv: column 'label' is missing, found 'Label'",
+                "annotations/a.csv: column 'Label' is not in the contract",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_schema_contract_checks_nulls() {
+        let df = df!(
+            "file" => &["a.jpg", "b.jpg"],
+            "label" => &[Some("cat"), None]
+        )
+        .unwrap();
+        let violations = contract().check_df(Path::new("annotations/a.csv"), &df);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].field, "label");
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::error::OxenError;
+
+pub const UTF8_ENCODING: &str = "UTF-8";
+
+/// How a delimited text file is laid out. Detected when the file is staged and saved on its schema
+/// so that reading, diffing and appending to the file all agree.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CsvDialect {
+    pub delimiter: char,
+    pub quote: Option<char>,
+    pub has_header: bool,
+    /// Label of the text encoding, ex) UTF-8, windows-1252 or UTF-16LE
+    pub encoding: String,
+}
+
+impl Default for CsvDialect {
+    fn default() -> Self {
+        CsvDialect {
+            delimiter: ',',
+            quote: Some('"'),
+            has_header: true,
+            encoding: String::from(UTF8_ENCODING),
+        }
+    }
+}
+
+impl CsvDialect {
+    pub fn with_delimiter(delimiter: char) -> CsvDialect {
+        CsvDialect {
+            delimiter,
+            ..CsvDialect::default()
+        }
+    }
+
+    /// Comma separated, double quoted, with a header and utf-8 encoded
+    pub fn is_default(&self) -> bool {
+        *self == CsvDialect::default()
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.encoding.eq_ignore_ascii_case(UTF8_ENCODING)
+            || self.encoding.eq_ignore_ascii_case("utf8")
+    }
+
+    /// The csv reader and writer only split on single byte characters
+    pub fn delimiter_byte(&self) -> Result<u8, OxenError> {
+        ascii_byte("delimiter", self.delimiter)
+    }
+
+    pub fn quote_byte(&self) -> Result<Option<u8>, OxenError> {
+        self.quote
+            .map(|quote| ascii_byte("quote", quote))
+            .transpose()
+    }
+}
+
+fn ascii_byte(name: &str, c: char) -> Result<u8, OxenError> {
+    if c.is_ascii() {
+        Ok(c as u8)
+    } else {
+        Err(OxenError::basic_str(format!(
+            "Csv {name} {c:?} is not supported, it must be a single byte ascii character"
+        )))
+    }
+}
+
+impl fmt::Display for CsvDialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let quote = match self.quote {
+            Some(quote) => format!("{quote:?}"),
+            None => String::from("none"),
+        };
+        write!(
+            f,
+            "delimiter: {:?}, quote: {}, header: {}, encoding: {}",
+            self.delimiter, quote, self.has_header, self.encoding
+        )
+    }
+}
+
+/// Per path override of the detected dialect, set in the repository config
+///
+/// ```toml
+/// [[csv_dialects]]
+/// path = "exports/sales.csv"
+/// delimiter = ";"
+/// encoding = "windows-1252"
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CsvDialectOverride {
+    pub path: PathBuf,
+    pub delimiter: Option<char>,
+    pub quote: Option<char>,
+    pub has_header: Option<bool>,
+    pub encoding: Option<String>,
+}
+
+impl CsvDialectOverride {
+    pub fn apply(&self, mut dialect: CsvDialect) -> CsvDialect {
+        if let Some(delimiter) = self.delimiter {
+            dialect.delimiter = delimiter;
+        }
+        if let Some(quote) = self.quote {
+            dialect.quote = Some(quote);
+        }
+        if let Some(has_header) = self.has_header {
+            dialect.has_header = has_header;
+        }
+        if let Some(encoding) = &self.encoding {
+            dialect.encoding = encoding.to_owned();
+        }
+        dialect
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::schema::CsvDialect;
+
+    #[test]
+    fn test_csv_dialect_rejects_multi_byte_delimiter() {
+        assert_eq!(
+            CsvDialect::with_delimiter(';').delimiter_byte().unwrap(),
+            b';'
+        );
+        assert!(CsvDialect::with_delimiter('§').delimiter_byte().is_err());
+
+        let mut dialect = CsvDialect::default();
+        dialect.quote = Some('«');
+        assert!(dialect.quote_byte().is_err());
+        dialect.quote = None;
+        assert_eq!(dialect.quote_byte().unwrap(), None);
+    }
+}
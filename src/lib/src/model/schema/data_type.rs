@@ -1,3 +1,52 @@
+use std::fmt;
+
+/// Precision polars uses for decimals that do not set one
+pub const DEFAULT_DECIMAL_PRECISION: usize = 38;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeUnit {
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+}
+
+impl TimeUnit {
+    pub fn from_string(s: &str) -> Option<TimeUnit> {
+        match s {
+            "ns" => Some(TimeUnit::Nanoseconds),
+            "us" | "μs" => Some(TimeUnit::Microseconds),
+            "ms" => Some(TimeUnit::Milliseconds),
+            _ => None,
+        }
+    }
+
+    /// Same units polars prints, so schemas hash the same as before these types were supported
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeUnit::Nanoseconds => "ns",
+            TimeUnit::Microseconds => "μs",
+            TimeUnit::Milliseconds => "ms",
+        }
+    }
+
+    pub fn to_polars(self) -> polars::prelude::TimeUnit {
+        match self {
+            TimeUnit::Nanoseconds => polars::prelude::TimeUnit::Nanoseconds,
+            TimeUnit::Microseconds => polars::prelude::TimeUnit::Microseconds,
+            TimeUnit::Milliseconds => polars::prelude::TimeUnit::Milliseconds,
+        }
+    }
+
+    pub fn from_polars(unit: &polars::prelude::TimeUnit) -> TimeUnit {
+        match unit {
+            polars::prelude::TimeUnit::Nanoseconds => TimeUnit::Nanoseconds,
+            polars::prelude::TimeUnit::Microseconds => TimeUnit::Microseconds,
+            polars::prelude::TimeUnit::Milliseconds => TimeUnit::Milliseconds,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum DataType {
     Boolean,
     UInt8,
@@ -10,23 +59,32 @@ pub enum DataType {
     Int64,
     Float32,
     Float64,
+    /// precision, scale
+    Decimal(usize, usize),
     String,
+    Binary,
     Date,
     Time,
+    /// time unit, time zone
+    Datetime(TimeUnit, Option<String>),
+    Duration(TimeUnit),
+    Categorical,
     List(Box<DataType>),
+    /// name and dtype of each field
+    Struct(Vec<(String, DataType)>),
     // TODO: implement these when needed...
     // Object(&'static str),
-    // Datetime,
-    // Duration,
     Null,
     Unknown,
 }
 
 impl DataType {
+    /// Parse a dtype as written by `to_string`, ex) "i64", "list[str]", "datetime[ms, UTC]" or "struct[x: f64, y: f64]"
     pub fn from_string(s: &str) -> DataType {
         match s {
             "bool" => DataType::Boolean,
             "uint8" => DataType::UInt8,
+            "u8" => DataType::UInt8,
             "u16" => DataType::UInt16,
             "u32" => DataType::UInt32,
             "u64" => DataType::UInt64,
@@ -39,14 +97,59 @@ impl DataType {
             "f32" => DataType::Float32,
             "double" => DataType::Float64,
             "f64" => DataType::Float64,
+            "decimal" => DataType::Decimal(DEFAULT_DECIMAL_PRECISION, 0),
             "str" => DataType::String,
+            "binary" => DataType::Binary,
             "date" => DataType::Date,
             "time" => DataType::Time,
+            "datetime" => DataType::Datetime(TimeUnit::Microseconds, None),
+            "duration" => DataType::Duration(TimeUnit::Microseconds),
+            "cat" => DataType::Categorical,
             "null" => DataType::Null,
-            _ => DataType::Unknown,
+            _ => DataType::from_parameterized_string(s).unwrap_or(DataType::Unknown),
         }
     }
 
+    // The types that carry parameters in brackets, "list[i64]"
+    fn from_parameterized_string(s: &str) -> Option<DataType> {
+        let (name, params) = s.strip_suffix(']')?.split_once('[')?;
+        match name {
+            "list" => match DataType::from_string(params) {
+                DataType::Unknown => None,
+                dtype => Some(DataType::List(Box::new(dtype))),
+            },
+            "datetime" => match params.split_once(", ") {
+                Some((unit, tz)) => Some(DataType::Datetime(
+                    TimeUnit::from_string(unit)?,
+                    Some(tz.to_string()),
+                )),
+                None => Some(DataType::Datetime(TimeUnit::from_string(params)?, None)),
+            },
+            "duration" => Some(DataType::Duration(TimeUnit::from_string(params)?)),
+            "decimal" => {
+                let (precision, scale) = params.split_once(',')?;
+                Some(DataType::Decimal(
+                    precision.trim().parse().ok()?,
+                    scale.trim().parse().ok()?,
+                ))
+            }
+            "struct" => {
+                let mut fields: Vec<(String, DataType)> = vec![];
+                for field in split_top_level(params) {
+                    // The dtype may contain ": " inside brackets, the name is everything before the last one outside
+                    let (name, dtype) = rsplit_top_level(field, ": ")?;
+                    match DataType::from_string(dtype) {
+                        DataType::Unknown => return None,
+                        dtype => fields.push((name.to_string(), dtype)),
+                    }
+                }
+                Some(DataType::Struct(fields))
+            }
+            _ => None,
+        }
+    }
+
+    /// The name of the type without its parameters, "list" for "list[i64]"
     pub fn as_str(&self) -> &'static str {
         match self {
             DataType::Boolean => "bool",
@@ -60,15 +163,39 @@ impl DataType {
             DataType::Int64 => "i64",
             DataType::Float32 => "f32",
             DataType::Float64 => "f64",
+            DataType::Decimal(_, _) => "decimal",
             DataType::String => "str",
+            DataType::Binary => "binary",
             DataType::Date => "date",
             DataType::Time => "time",
+            DataType::Datetime(_, _) => "datetime",
+            DataType::Duration(_) => "duration",
+            DataType::Categorical => "cat",
             DataType::List(_val) => "list",
+            DataType::Struct(_) => "struct",
             DataType::Null => "null",
             DataType::Unknown => "?",
         }
     }
 
+    /// The dtype as polars prints it, "struct[2]" instead of "struct[x: f64, y: f64]".
+    /// Schemas were hashed over these strings before struct fields were modeled.
+    pub fn to_hash_string(&self) -> String {
+        match self {
+            DataType::List(val) => format!("list[{}]", val.to_hash_string()),
+            DataType::Struct(fields) => format!("struct[{}]", fields.len()),
+            dtype => dtype.to_string(),
+        }
+    }
+
+    pub fn contains_struct(&self) -> bool {
+        match self {
+            DataType::List(val) => val.contains_struct(),
+            DataType::Struct(_) => true,
+            _ => false,
+        }
+    }
+
     pub fn to_polars(&self) -> polars::prelude::DataType {
         match self {
             DataType::Boolean => polars::prelude::DataType::Boolean,
@@ -82,10 +209,25 @@ impl DataType {
             DataType::Int64 => polars::prelude::DataType::Int64,
             DataType::Float32 => polars::prelude::DataType::Float32,
             DataType::Float64 => polars::prelude::DataType::Float64,
+            DataType::Decimal(precision, scale) => {
+                polars::prelude::DataType::Decimal(Some(*precision), Some(*scale))
+            }
             DataType::String => polars::prelude::DataType::Utf8,
+            DataType::Binary => polars::prelude::DataType::Binary,
             DataType::Date => polars::prelude::DataType::Date,
             DataType::Time => polars::prelude::DataType::Time,
+            DataType::Datetime(unit, tz) => {
+                polars::prelude::DataType::Datetime(unit.to_polars(), tz.to_owned())
+            }
+            DataType::Duration(unit) => polars::prelude::DataType::Duration(unit.to_polars()),
+            DataType::Categorical => polars::prelude::DataType::Categorical(None),
             DataType::List(val) => polars::prelude::DataType::List(Box::new(val.to_polars())),
+            DataType::Struct(fields) => polars::prelude::DataType::Struct(
+                fields
+                    .iter()
+                    .map(|(name, dtype)| polars::prelude::Field::new(name, dtype.to_polars()))
+                    .collect(),
+            ),
             DataType::Null => polars::prelude::DataType::Null,
             DataType::Unknown => polars::prelude::DataType::Unknown,
         }
@@ -104,9 +246,189 @@ impl DataType {
             polars::prelude::DataType::Int64 => DataType::Int64,
             polars::prelude::DataType::Float32 => DataType::Float32,
             polars::prelude::DataType::Float64 => DataType::Float64,
+            polars::prelude::DataType::Decimal(precision, scale) => DataType::Decimal(
+                precision.unwrap_or(DEFAULT_DECIMAL_PRECISION),
+                scale.unwrap_or(0),
+            ),
             polars::prelude::DataType::Utf8 => DataType::String,
+            polars::prelude::DataType::Binary => DataType::Binary,
+            polars::prelude::DataType::Datetime(unit, tz) => {
+                DataType::Datetime(TimeUnit::from_polars(unit), tz.to_owned())
+            }
+            polars::prelude::DataType::Duration(unit) => {
+                DataType::Duration(TimeUnit::from_polars(unit))
+            }
+            polars::prelude::DataType::Categorical(_) => DataType::Categorical,
+            polars::prelude::DataType::List(val) => {
+                DataType::List(Box::new(DataType::from_polars(val)))
+            }
+            polars::prelude::DataType::Struct(fields) => {
+                let mut struct_fields: Vec<(String, DataType)> = vec![];
+                for field in fields.iter() {
+                    // A struct we cannot fully model keeps the name polars gives it
+                    match DataType::from_polars(field.data_type()) {
+                        DataType::Unknown => return DataType::Unknown,
+                        dtype => struct_fields.push((field.name().to_string(), dtype)),
+                    }
+                }
+                DataType::Struct(struct_fields)
+            }
             polars::prelude::DataType::Null => DataType::Null,
             _ => DataType::Unknown,
         }
     }
 }
+
+/// The full dtype with its parameters, parsed back by `from_string`. Everything but structs prints
+/// the way polars prints it, polars only prints the number of fields of a struct, see `to_hash_string`.
+impl fmt::Display for DataType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DataType::UInt8 => write!(f, "u8"),
+            DataType::Decimal(precision, scale) => write!(f, "decimal[{precision},{scale}]"),
+            DataType::Datetime(unit, None) => write!(f, "datetime[{}]", unit.as_str()),
+            DataType::Datetime(unit, Some(tz)) => write!(f, "datetime[{}, {tz}]", unit.as_str()),
+            DataType::Duration(unit) => write!(f, "duration[{}]", unit.as_str()),
+            DataType::List(val) => write!(f, "list[{val}]"),
+            DataType::Struct(fields) => {
+                let fields: Vec<String> = fields
+                    .iter()
+                    .map(|(name, dtype)| format!("{name}: {dtype}"))
+                    .collect();
+                write!(f, "struct[{}]", fields.join(", "))
+            }
+            dtype => write!(f, "{}", dtype.as_str()),
+        }
+    }
+}
+
+/// Split "a: i64, b: struct[c: str, d: str]" on the commas that are not inside brackets
+pub(crate) fn split_top_level(s: &str) -> Vec<&str> {
+    let mut parts: Vec<&str> = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            ',' if depth == 0 => {
+                parts.push(s[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    let last = s[start..].trim();
+    if !last.is_empty() {
+        parts.push(last);
+    }
+    parts
+}
+
+/// Split on the last `delimiter` that is not inside brackets
+pub(crate) fn rsplit_top_level<'a>(s: &'a str, delimiter: &str) -> Option<(&'a str, &'a str)> {
+    let mut depth = 0;
+    let mut split_at = None;
+    for (i, c) in s.char_indices() {
+        match c {
+            '[' => depth += 1,
+            ']' => depth -= 1,
+            _ if depth == 0 && s[i..].starts_with(delimiter) => split_at = Some(i),
+            _ => {}
+        }
+    }
+    split_at.map(|i| (&s[..i], &s[i + delimiter.len()..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::schema::{DataType, Field, Schema, TimeUnit};
+
+    #[test]
+    fn test_data_type_round_trips_strings() {
+        let dtypes = vec![
+            DataType::UInt8,
+            DataType::Decimal(10, 2),
+            DataType::Binary,
+            DataType::Categorical,
+            DataType::Datetime(TimeUnit::Milliseconds, None),
+            DataType::Datetime(TimeUnit::Microseconds, Some(String::from("UTC"))),
+            DataType::Duration(TimeUnit::Nanoseconds),
+            DataType::List(Box::new(DataType::String)),
+            DataType::Struct(vec![
+                (String::from("label"), DataType::String),
+                (
+                    String::from("box"),
+                    DataType::from_string("struct[x: f64, y: f64]"),
+                ),
+                (
+                    String::from("tags"),
+                    DataType::List(Box::new(DataType::Categorical)),
+                ),
+            ]),
+        ];
+        for dtype in dtypes {
+            assert_eq!(DataType::from_string(&dtype.to_string()), dtype);
+        }
+    }
+
+    #[test]
+    fn test_data_type_matches_polars_strings() {
+        let dtypes = vec![
+            polars::prelude::DataType::UInt8,
+            polars::prelude::DataType::Utf8,
+            polars::prelude::DataType::Binary,
+            polars::prelude::DataType::Datetime(
+                polars::prelude::TimeUnit::Microseconds,
+                Some(String::from("UTC")),
+            ),
+            polars::prelude::DataType::Duration(polars::prelude::TimeUnit::Milliseconds),
+            polars::prelude::DataType::List(Box::new(polars::prelude::DataType::Int64)),
+        ];
+        for dtype in dtypes {
+            assert_eq!(DataType::from_polars(&dtype).to_string(), dtype.to_string());
+        }
+    }
+
+    #[test]
+    fn test_data_type_round_trips_polars_struct() {
+        let dtype = polars::prelude::DataType::Struct(vec![
+            polars::prelude::Field::new("x", polars::prelude::DataType::Float64),
+            polars::prelude::Field::new(
+                "ts",
+                polars::prelude::DataType::Datetime(polars::prelude::TimeUnit::Nanoseconds, None),
+            ),
+        ]);
+        assert_eq!(DataType::from_polars(&dtype).to_polars(), dtype);
+    }
+
+    #[test]
+    fn test_data_type_unknown_parameters() {
+        assert_eq!(DataType::from_string("list[nope]"), DataType::Unknown);
+        assert_eq!(DataType::from_string("datetime[hours]"), DataType::Unknown);
+        assert_eq!(DataType::from_string("struct[x]"), DataType::Unknown);
+    }
+
+    #[test]
+    fn test_struct_fields_keep_the_polars_hash() {
+        let polars_schema = polars::prelude::Schema::from_iter(vec![
+            polars::prelude::Field::new("file", polars::prelude::DataType::Utf8),
+            polars::prelude::Field::new(
+                "box",
+                polars::prelude::DataType::Struct(vec![
+                    polars::prelude::Field::new("x", polars::prelude::DataType::Float64),
+                    polars::prelude::Field::new("y", polars::prelude::DataType::Float64),
+                ]),
+            ),
+        ]);
+        let schema = Schema::from_polars(&polars_schema);
+        assert_eq!(schema.fields[1].dtype, "struct[x: f64, y: f64]");
+
+        // The hash of the schema as it was committed when the dtype was polars' string
+        let committed = Schema::from_fields(vec![
+            Field::new("file", "str"),
+            Field::new("box", "struct[2]"),
+        ]);
+        assert_eq!(schema.hash, committed.hash);
+    }
+}
@@ -1,12 +1,41 @@
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+use crate::model::schema::{DataType, FieldMetadata};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Field {
     pub name: String,
     pub dtype: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<FieldMetadata>,
 }
 
 impl Field {
+    pub fn new(name: impl AsRef<str>, dtype: impl AsRef<str>) -> Field {
+        Field {
+            name: name.as_ref().to_string(),
+            dtype: dtype.as_ref().to_string(),
+            metadata: None,
+        }
+    }
+
+    pub fn data_type(&self) -> DataType {
+        DataType::from_string(&self.dtype)
+    }
+
+    pub fn to_polars(&self) -> polars::prelude::Field {
+        polars::prelude::Field::new(&self.name, self.data_type().to_polars())
+    }
+
+    /// Types we do not model keep the name polars gives them
+    pub fn from_polars(field: &polars::prelude::Field) -> Field {
+        let dtype = match DataType::from_polars(field.data_type()) {
+            DataType::Unknown => field.data_type().to_string(),
+            dtype => dtype.to_string(),
+        };
+        Field::new(field.name(), dtype)
+    }
+
     pub fn all_fields_to_string<V: AsRef<Vec<Field>>>(fields: V) -> String {
         let names: Vec<String> = fields.as_ref().iter().map(|f| f.name.to_owned()).collect();
 
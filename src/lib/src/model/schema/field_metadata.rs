@@ -0,0 +1,279 @@
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::error::OxenError;
+
+/// What a field means and the values it may take. Staged and committed with the schema,
+/// but not part of its hash.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FieldMetadata {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_values: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max: Option<f64>,
+    /// Whether the field holds personally identifiable information, None if never declared
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pii: Option<bool>,
+}
+
+impl FieldMetadata {
+    /// Names of the values that `clear` can remove, matching the `oxen schemas field` flags
+    pub const KEYS: [&'static str; 6] = ["description", "unit", "values", "min", "max", "pii"];
+
+    pub fn is_empty(&self) -> bool {
+        *self == FieldMetadata::default()
+    }
+
+    pub fn has_constraints(&self) -> bool {
+        self.allowed_values.is_some() || self.min.is_some() || self.max.is_some()
+    }
+
+    /// Overwrite the values that are set on `other`
+    pub fn update(&mut self, other: &FieldMetadata) {
+        if other.description.is_some() {
+            self.description = other.description.to_owned();
+        }
+        if other.unit.is_some() {
+            self.unit = other.unit.to_owned();
+        }
+        if other.allowed_values.is_some() {
+            self.allowed_values = other.allowed_values.to_owned();
+        }
+        if other.min.is_some() {
+            self.min = other.min;
+        }
+        if other.max.is_some() {
+            self.max = other.max;
+        }
+        if other.pii.is_some() {
+            self.pii = other.pii;
+        }
+    }
+
+    /// Unset a value by its name in `FieldMetadata::KEYS`
+    pub fn clear(&mut self, key: &str) -> Result<(), OxenError> {
+        match key {
+            "description" => self.description = None,
+            "unit" => self.unit = None,
+            "values" => self.allowed_values = None,
+            "min" => self.min = None,
+            "max" => self.max = None,
+            "pii" => self.pii = None,
+            _ => {
+                return Err(OxenError::basic_str(format!(
+                    "Unknown field metadata '{key}', expected one of: {}",
+                    FieldMetadata::KEYS.join(", ")
+                )))
+            }
+        }
+        Ok(())
+    }
+
+    /// The checks of the values of column `name`, with the dtype polars reads it as, against the declared constraints
+    pub fn constraint_checks(&self, name: &str, dtype: &DataType) -> Vec<ConstraintCheck> {
+        let mut checks: Vec<ConstraintCheck> = vec![];
+
+        if let Some(allowed) = &self.allowed_values {
+            let allowed_values = Series::new("", allowed.to_owned());
+            let not_allowed = col(name)
+                .cast(DataType::Utf8)
+                .is_in(lit(allowed_values))
+                .not()
+                .and(col(name).is_not_null());
+            checks.push(ConstraintCheck::Count(
+                count_true(not_allowed),
+                format!(
+                    "column '{name}' has {{n}} values not in [{}]",
+                    allowed.join(", ")
+                ),
+            ));
+        }
+
+        if self.min.is_some() || self.max.is_some() {
+            if !dtype.is_numeric() {
+                checks.push(ConstraintCheck::Warn(format!(
+                    "column '{name}' has a min or max but is not numeric"
+                )));
+                return checks;
+            }
+            let values = col(name).cast(DataType::Float64);
+            if let Some(min) = self.min {
+                checks.push(ConstraintCheck::Count(
+                    count_true(values.clone().lt(lit(min))),
+                    format!("column '{name}' has {{n}} values below the min {min}"),
+                ));
+            }
+            if let Some(max) = self.max {
+                checks.push(ConstraintCheck::Count(
+                    count_true(values.gt(lit(max))),
+                    format!("column '{name}' has {{n}} values above the max {max}"),
+                ));
+            }
+        }
+
+        checks
+    }
+
+    /// Describe the values of `series` that fall outside the declared constraints
+    pub fn check_series(&self, series: &Series) -> Result<Vec<String>, OxenError> {
+        let checks = self.constraint_checks(series.name(), series.dtype());
+        let df = DataFrame::new(vec![series.to_owned()])
+            .map_err(|err| OxenError::basic_str(err.to_string()))?;
+        run_constraint_checks(df.lazy(), checks)
+    }
+}
+
+/// A check of the values of a column against the constraints declared on its field
+pub enum ConstraintCheck {
+    /// Counts the values outside the constraint, warns with the message with `{n}` replaced by the count if any
+    Count(Expr, String),
+    /// Warns without looking at the values
+    Warn(String),
+}
+
+fn count_true(expr: Expr) -> Expr {
+    expr.cast(DataType::UInt64).sum()
+}
+
+/// Run the counting checks as one aggregation over `df`, so the values are streamed rather than kept in memory
+pub fn run_constraint_checks(
+    df: LazyFrame,
+    checks: Vec<ConstraintCheck>,
+) -> Result<Vec<String>, OxenError> {
+    let exprs: Vec<Expr> = checks
+        .iter()
+        .enumerate()
+        .filter_map(|(i, check)| match check {
+            ConstraintCheck::Count(expr, _) => Some(expr.clone().alias(&format!("check_{i}"))),
+            ConstraintCheck::Warn(_) => None,
+        })
+        .collect();
+    let counts = if exprs.is_empty() {
+        DataFrame::default()
+    } else {
+        df.select(exprs)
+            .with_streaming(true)
+            .collect()
+            .map_err(|err| OxenError::basic_str(format!("Could not check constraints: {err}")))?
+    };
+
+    let mut warnings: Vec<String> = vec![];
+    for (i, check) in checks.into_iter().enumerate() {
+        match check {
+            ConstraintCheck::Count(_, message) => {
+                let n = counts
+                    .column(&format!("check_{i}"))
+                    .ok()
+                    .and_then(|series| series.get(0).ok())
+                    .and_then(|value| value.extract::<u64>())
+                    .unwrap_or(0);
+                if n > 0 {
+                    warnings.push(message.replace("{n}", &n.to_string()));
+                }
+            }
+            ConstraintCheck::Warn(message) => warnings.push(message),
+        }
+    }
+    Ok(warnings)
+}
+
+impl fmt::Display for FieldMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts: Vec<String> = vec![];
+        if let Some(description) = &self.description {
+            parts.push(description.to_owned());
+        }
+        if let Some(unit) = &self.unit {
+            parts.push(format!("unit: {unit}"));
+        }
+        if let Some(allowed) = &self.allowed_values {
+            parts.push(format!("values: [{}]", allowed.join(", ")));
+        }
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => parts.push(format!("range: [{min}, {max}]")),
+            (Some(min), None) => parts.push(format!("min: {min}")),
+            (None, Some(max)) => parts.push(format!("max: {max}")),
+            (None, None) => {}
+        }
+        match self.pii {
+            Some(true) => parts.push(String::from("pii")),
+            Some(false) => parts.push(String::from("not pii")),
+            None => {}
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use polars::prelude::*;
+
+    use crate::model::schema::FieldMetadata;
+
+    #[test]
+    fn test_field_metadata_checks_allowed_values_and_range() {
+        let metadata = FieldMetadata {
+            allowed_values: Some(vec![String::from("cat"), String::from("dog")]),
+            ..FieldMetadata::default()
+        };
+        let labels = Series::new("label", &[Some("cat"), Some("bird"), None]);
+        assert_eq!(
+            metadata.check_series(&labels).unwrap(),
+            vec!["column 'label' has 1 values not in [cat, dog]"]
+        );
+
+        let metadata = FieldMetadata {
+            min: Some(0.0),
+            max: Some(100.0),
+            ..FieldMetadata::default()
+        };
+        let widths = Series::new("width", &[-1, 50, 101, 200]);
+        assert_eq!(
+            metadata.check_series(&widths).unwrap(),
+            vec![
+                "column 'width' has 1 values below the min 0",
+                "column 'width' has 2 values above the max 100",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_field_metadata_update_keeps_unset_values() {
+        let mut metadata = FieldMetadata {
+            description: Some(String::from("Left edge of the box")),
+            unit: Some(String::from("px")),
+            ..FieldMetadata::default()
+        };
+        metadata.update(&FieldMetadata {
+            unit: Some(String::from("mm")),
+            ..FieldMetadata::default()
+        });
+        assert_eq!(metadata.to_string(), "Left edge of the box, unit: mm");
+    }
+
+    #[test]
+    fn test_field_metadata_pii_can_be_unset_and_values_cleared() {
+        let mut metadata = FieldMetadata {
+            description: Some(String::from("Email of the annotator")),
+            pii: Some(true),
+            ..FieldMetadata::default()
+        };
+        metadata.update(&FieldMetadata {
+            pii: Some(false),
+            ..FieldMetadata::default()
+        });
+        assert_eq!(metadata.pii, Some(false));
+
+        metadata.clear("pii").unwrap();
+        metadata.clear("description").unwrap();
+        assert!(metadata.is_empty());
+        assert!(metadata.clear("color").is_err());
+    }
+}
@@ -0,0 +1,226 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::model::schema::{CsvDialect, Field};
+use crate::model::{Commit, Schema};
+
+/// A field that kept its place in the schema but changed its name or its dtype
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub from: Field,
+    pub to: Field,
+}
+
+/// The csv dialect a file was read with before and after a change
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DialectChange {
+    pub from: CsvDialect,
+    pub to: CsvDialect,
+}
+
+/// How the fields of a schema changed between two versions
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SchemaDiff {
+    pub added: Vec<Field>,
+    pub removed: Vec<Field>,
+    pub renamed: Vec<FieldChange>,
+    pub retyped: Vec<FieldChange>,
+    /// Set if the file is laid out differently, ex) it went from comma to semicolon separated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialect: Option<DialectChange>,
+}
+
+impl SchemaDiff {
+    /// Compare `head` to `base`. A removed and an added field with the same dtype are a rename
+    /// if they are at the same position, or if they are the only pair with that dtype.
+    pub fn between(base: &Schema, head: &Schema) -> SchemaDiff {
+        let mut added = head.added_fields(base);
+        let mut removed = head.removed_fields(base);
+
+        let retyped: Vec<FieldChange> = head
+            .fields
+            .iter()
+            .filter_map(|field| match base.get_field(&field.name) {
+                Some(base_field) if base_field.dtype != field.dtype => Some(FieldChange {
+                    from: base_field.to_owned(),
+                    to: field.to_owned(),
+                }),
+                _ => None,
+            })
+            .collect();
+
+        let position = |schema: &Schema, field: &Field| {
+            schema.fields.iter().position(|f| f.name == field.name)
+        };
+        let mut renamed: Vec<FieldChange> = vec![];
+        let mut i = 0;
+        while i < removed.len() {
+            let from = &removed[i];
+            let same_dtype: Vec<usize> = (0..added.len())
+                .filter(|j| added[*j].dtype == from.dtype)
+                .collect();
+            let same_position = same_dtype
+                .iter()
+                .find(|j| position(head, &added[**j]) == position(base, from));
+            let matched = match (same_position, same_dtype.len()) {
+                (Some(j), _) => Some(*j),
+                (None, 1) => {
+                    let num_removed_same_dtype =
+                        removed.iter().filter(|f| f.dtype == from.dtype).count();
+                    if num_removed_same_dtype == 1 {
+                        Some(same_dtype[0])
+                    } else {
+                        None
+                    }
+                }
+                _ => None,
+            };
+
+            match matched {
+                Some(j) => {
+                    let to = added.remove(j);
+                    let from = removed.remove(i);
+                    renamed.push(FieldChange { from, to });
+                }
+                None => i += 1,
+            }
+        }
+
+        // Default dialects are not saved, so None and the default are the same
+        let base_dialect = base.dialect.to_owned().unwrap_or_default();
+        let head_dialect = head.dialect.to_owned().unwrap_or_default();
+        let dialect = if base_dialect != head_dialect {
+            Some(DialectChange {
+                from: base_dialect,
+                to: head_dialect,
+            })
+        } else {
+            None
+        };
+
+        SchemaDiff {
+            added,
+            removed,
+            renamed,
+            retyped,
+            dialect,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.renamed.is_empty()
+            && self.retyped.is_empty()
+            && self.dialect.is_none()
+    }
+}
+
+impl fmt::Display for SchemaDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut lines: Vec<String> = vec![];
+        for field in self.added.iter() {
+            lines.push(format!("  + {}: {}", field.name, field.dtype));
+        }
+        for field in self.removed.iter() {
+            lines.push(format!("  - {}: {}", field.name, field.dtype));
+        }
+        for change in self.renamed.iter() {
+            lines.push(format!(
+                "  ~ {} -> {}: {}",
+                change.from.name, change.to.name, change.to.dtype
+            ));
+        }
+        for change in self.retyped.iter() {
+            lines.push(format!(
+                "  ! {}: {} -> {}",
+                change.to.name, change.from.dtype, change.to.dtype
+            ));
+        }
+        if let Some(change) = &self.dialect {
+            lines.push(format!("  ~ dialect: {} -> {}", change.from, change.to));
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+/// The schema changes of one file between two revisions
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SchemaFileDiff {
+    pub path: PathBuf,
+    /// None if the file did not exist at the base revision
+    pub base: Option<Schema>,
+    /// None if the file does not exist at the head revision
+    pub head: Option<Schema>,
+    pub diff: SchemaDiff,
+}
+
+/// A commit that changed the schema of a file
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SchemaLogEntry {
+    pub commit: Commit,
+    pub schema: Schema,
+    pub diff: SchemaDiff,
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::model::schema::{Field, SchemaDiff};
+    use crate::model::Schema;
+
+    fn schema(fields: &[(&str, &str)]) -> Schema {
+        Schema::from_fields(fields.iter().map(|(n, d)| Field::new(n, d)).collect())
+    }
+
+    #[test]
+    fn test_schema_diff_added_removed_retyped() {
+        let base = schema(&[("file", "str"), ("label", "str"), ("width", "i64")]);
+        let head = schema(&[("file", "str"), ("width", "f64"), ("score", "f32")]);
+        let diff = SchemaDiff::between(&base, &head);
+
+        assert_eq!(diff.added, vec![Field::new("score", "f32")]);
+        assert_eq!(diff.removed, vec![Field::new("label", "str")]);
+        assert!(diff.renamed.is_empty());
+        assert_eq!(diff.retyped.len(), 1);
+        assert_eq!(diff.retyped[0].from.dtype, "i64");
+        assert_eq!(diff.retyped[0].to.dtype, "f64");
+    }
+
+    #[test]
+    fn test_schema_diff_renamed_by_position_and_type() {
+        let base = schema(&[("file", "str"), ("label", "str"), ("min_x", "f64")]);
+        let head = schema(&[("path", "str"), ("label", "str"), ("x", "f64")]);
+        let diff = SchemaDiff::between(&base, &head);
+
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        let renames: Vec<(String, String)> = diff
+            .renamed
+            .iter()
+            .map(|c| (c.from.name.to_owned(), c.to.name.to_owned()))
+            .collect();
+        assert_eq!(
+            renames,
+            vec![
+                (String::from("file"), String::from("path")),
+                (String::from("min_x"), String::from("x")),
+            ]
+        );
+        assert_eq!(
+            diff.to_string(),
+            "  ~ file -> path: str\n  ~ min_x -> x: f64"
+        );
+    }
+
+    #[test]
+    fn test_schema_diff_ambiguous_is_not_a_rename() {
+        let base = schema(&[("a", "str"), ("b", "str"), ("id", "i64")]);
+        let head = schema(&[("id", "i64"), ("c", "str"), ("d", "str"), ("e", "str")]);
+        let diff = SchemaDiff::between(&base, &head);
+
+        assert!(diff.renamed.is_empty());
+        assert_eq!(diff.added.len(), 3);
+        assert_eq!(diff.removed.len(), 2);
+    }
+}
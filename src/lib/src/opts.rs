@@ -8,11 +8,13 @@ pub mod log_opts;
 pub mod paginate_opts;
 pub mod restore_opts;
 pub mod rm_opts;
+pub mod split_opts;
 
 pub use crate::opts::add_opts::AddOpts;
 pub use crate::opts::clone_opts::CloneOpts;
-pub use crate::opts::df_opts::DFOpts;
+pub use crate::opts::df_opts::{DFOpts, PivotColVals};
 pub use crate::opts::log_opts::LogOpts;
 pub use crate::opts::paginate_opts::PaginateOpts;
 pub use crate::opts::restore_opts::RestoreOpts;
 pub use crate::opts::rm_opts::RmOpts;
+pub use crate::opts::split_opts::SplitOpts;
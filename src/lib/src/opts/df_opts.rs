@@ -1,11 +1,13 @@
+use polars::prelude::JoinType;
 use std::path::PathBuf;
 
 use crate::constants::{FILE_ROW_NUM_COL_NAME, ROW_HASH_COL_NAME, ROW_NUM_COL_NAME};
 use crate::core::df::agg::{self, DFAggregation};
 use crate::error::OxenError;
-use crate::model::schema::Field;
-use crate::model::{ContentType, Schema};
+use crate::model::schema::{data_type, DataType, Field};
+use crate::model::{ContentType, CsvDialect, Schema};
 
+use crate::core::df::expr::{self, DFWithColumn};
 use crate::core::df::filter::{self, DFFilterExp};
 
 #[derive(Debug)]
@@ -15,6 +17,19 @@ pub struct AddColVals {
     pub dtype: String,
 }
 
+#[derive(Debug)]
+pub struct CastColVals {
+    pub name: String,
+    pub dtype: DataType,
+}
+
+#[derive(Debug)]
+pub struct PivotColVals {
+    pub index: Vec<String>,
+    pub columns: String,
+    pub values: String,
+}
+
 #[derive(Clone, Debug)]
 pub struct IndexedItem {
     pub col: String,
@@ -25,6 +40,8 @@ pub struct IndexedItem {
 pub struct DFOpts {
     pub output: Option<PathBuf>,
     pub delimiter: Option<String>,
+    /// Dialect to read csv files with, sniffed from the file when not set
+    pub dialect: Option<CsvDialect>,
     pub slice: Option<String>,
     pub take: Option<String>,
     pub columns: Option<String>,
@@ -34,9 +51,20 @@ pub struct DFOpts {
     pub vstack: Option<Vec<PathBuf>>,
     pub add_col: Option<String>,
     pub add_row: Option<String>,
+    pub cast: Option<String>,
+    pub with_columns: Option<Vec<String>>,
+    pub pivot: Option<String>,
+    pub melt: Option<String>,
+    pub explode: Option<String>,
     pub delete_row: Option<String>,
     pub sort_by: Option<String>,
     pub unique: Option<String>,
+    pub sql: Option<String>,
+    pub sql_tables: Option<Vec<String>>,
+    pub join: Option<PathBuf>,
+    pub join_on: Option<String>,
+    pub join_how: Option<String>,
+    pub join_revision: Option<String>,
     pub should_randomize: bool,
     pub should_reverse: bool,
     pub content_type: ContentType,
@@ -51,6 +79,7 @@ impl DFOpts {
         DFOpts {
             output: None,
             delimiter: None,
+            dialect: None,
             slice: None,
             take: None,
             columns: None,
@@ -60,9 +89,20 @@ impl DFOpts {
             vstack: None,
             add_col: None,
             add_row: None,
+            cast: None,
+            with_columns: None,
+            pivot: None,
+            melt: None,
+            explode: None,
             delete_row: None,
             sort_by: None,
             unique: None,
+            sql: None,
+            sql_tables: None,
+            join: None,
+            join_on: None,
+            join_how: None,
+            join_revision: None,
             should_randomize: false,
             should_reverse: false,
             content_type: ContentType::Json,
@@ -131,15 +171,38 @@ impl DFOpts {
             || self.vstack.is_some()
             || self.add_col.is_some()
             || self.add_row.is_some()
+            || self.cast.is_some()
+            || self.with_columns.is_some()
+            || self.pivot.is_some()
+            || self.melt.is_some()
+            || self.explode.is_some()
             || self.filter.is_some()
             || self.aggregate.is_some()
             || self.col_at.is_some()
             || self.sort_by.is_some()
             || self.unique.is_some()
+            || self.sql.is_some()
+            || self.join.is_some()
             || self.should_randomize
             || self.should_reverse
     }
 
+    /// The filter runs on the rows and columns of the file as it was read, with nothing added, joined or reshaped before it,
+    /// so an index on the file can answer it
+    pub fn filter_reads_source_rows(&self) -> bool {
+        self.filter.is_some()
+            && self.vstack.is_none()
+            && self.add_col.is_none()
+            && self.add_row.is_none()
+            && self.join.is_none()
+            && self.sql.is_none()
+            && self.cast.is_none()
+            && self.with_columns.is_none()
+            && self.explode.is_none()
+            && self.melt.is_none()
+            && self.pivot.is_none()
+    }
+
     pub fn slice_indices(&self) -> Option<(i64, i64)> {
         if let Some(slice) = self.slice.clone() {
             let split = slice.split("..").collect::<Vec<&str>>();
@@ -191,6 +254,60 @@ impl DFOpts {
         None
     }
 
+    /// Parse the extra tables that can be referenced from the sql query
+    /// 'name=path' -> ("name", "path")
+    pub fn sql_table_paths(&self) -> Result<Vec<(String, PathBuf)>, OxenError> {
+        let mut tables: Vec<(String, PathBuf)> = vec![];
+        if let Some(values) = &self.sql_tables {
+            for value in values.iter() {
+                match value.split_once('=') {
+                    Some((name, path)) if !name.is_empty() && !path.is_empty() => {
+                        tables.push((name.trim().to_string(), PathBuf::from(path.trim())));
+                    }
+                    _ => {
+                        return Err(OxenError::parse_error(format!(
+                            "Invalid sql table '{value}'. Format: 'name=path'"
+                        )));
+                    }
+                }
+            }
+        }
+        Ok(tables)
+    }
+
+    /// Columns to join on, must exist in both data frames
+    /// 'file,label' -> ["file", "label"]
+    pub fn join_columns(&self) -> Result<Vec<String>, OxenError> {
+        match &self.join_on {
+            Some(on) => {
+                let columns: Vec<String> = on
+                    .split(',')
+                    .map(|c| c.trim().to_string())
+                    .filter(|c| !c.is_empty())
+                    .collect();
+                if columns.is_empty() {
+                    return Err(OxenError::parse_error(on));
+                }
+                Ok(columns)
+            }
+            None => Err(OxenError::basic_str(
+                "Must supply the columns to join on with --on",
+            )),
+        }
+    }
+
+    /// How to join, defaults to inner. Valid values are 'inner', 'left', 'outer'
+    pub fn join_type(&self) -> Result<JoinType, OxenError> {
+        match self.join_how.as_deref() {
+            None | Some("inner") => Ok(JoinType::Inner),
+            Some("left") => Ok(JoinType::Left),
+            Some("outer") => Ok(JoinType::Outer),
+            Some(how) => Err(OxenError::parse_error(format!(
+                "Invalid join type '{how}'. Valid types are 'inner', 'left', 'outer'"
+            ))),
+        }
+    }
+
     pub fn get_filter(&self) -> Result<Option<DFFilterExp>, OxenError> {
         filter::parse(self.filter.clone())
     }
@@ -245,6 +362,79 @@ impl DFOpts {
         None
     }
 
+    /// Parse the columns to convert to another type
+    /// 'width:i64,score:f32' -> [("width", Int64), ("score", Float32)]
+    pub fn cast_col_vals(&self) -> Result<Vec<CastColVals>, OxenError> {
+        let mut casts: Vec<CastColVals> = vec![];
+        if let Some(cast) = &self.cast {
+            // Dtypes can have commas and colons of their own, "ts:datetime[ms, UTC]"
+            for value in data_type::split_top_level(cast) {
+                let (name, dtype) = match data_type::rsplit_top_level(value, ":") {
+                    Some(split) => split,
+                    None => {
+                        return Err(OxenError::parse_error(format!(
+                            "Invalid cast '{value}'. Format: 'name:dtype'"
+                        )));
+                    }
+                };
+                let dtype = DataType::from_string(dtype.trim());
+                if dtype == DataType::Unknown {
+                    return Err(OxenError::parse_error(format!(
+                        "Invalid cast '{value}', unknown dtype"
+                    )));
+                }
+                casts.push(CastColVals {
+                    name: name.trim().to_string(),
+                    dtype,
+                });
+            }
+        }
+        Ok(casts)
+    }
+
+    /// Parse the computed columns, in the order they were given so later ones can use earlier ones
+    /// 'area = width * height' -> DFWithColumn { name: "area", exp: width * height }
+    pub fn get_with_columns(&self) -> Result<Vec<DFWithColumn>, OxenError> {
+        match &self.with_columns {
+            Some(queries) => queries
+                .iter()
+                .map(|query| expr::parse_with_column(query))
+                .collect(),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Parse the pivot, the index can be more than one column
+    /// 'file,split:label:score' -> index ["file", "split"], columns "label", values "score"
+    pub fn pivot_col_vals(&self) -> Result<Option<PivotColVals>, OxenError> {
+        if let Some(pivot) = &self.pivot {
+            let split: Vec<&str> = pivot.split(':').map(|s| s.trim()).collect();
+            if split.len() != 3 || split.iter().any(|s| s.is_empty()) {
+                return Err(OxenError::parse_error(format!(
+                    "Invalid pivot '{pivot}'. Format: 'index:columns:values'"
+                )));
+            }
+            return Ok(Some(PivotColVals {
+                index: split[0].split(',').map(|c| c.trim().to_string()).collect(),
+                columns: split[1].to_string(),
+                values: split[2].to_string(),
+            }));
+        }
+        Ok(None)
+    }
+
+    pub fn melt_id_columns(&self) -> Option<Vec<String>> {
+        self.melt
+            .as_ref()
+            .map(|columns| columns.split(',').map(|c| c.trim().to_string()).collect())
+    }
+
+    pub fn explode_columns(&self) -> Option<Vec<String>> {
+        self.explode
+            .as_ref()
+            .map(|columns| columns.split(',').map(|c| c.trim().to_string()).collect())
+    }
+
     pub fn to_http_query_params(&self) -> String {
         let randomize = if self.should_randomize {
             Some(String::from("true"))
@@ -275,6 +465,23 @@ impl DFOpts {
             ("col-at", self.col_at.clone()),
             ("sort-by", self.sort_by.clone()),
             ("unique", self.unique.clone()),
+            ("cast", self.cast.clone()),
+            (
+                "with_column",
+                self.with_columns.as_ref().map(|w| w.join(";")),
+            ),
+            ("pivot", self.pivot.clone()),
+            ("melt", self.melt.clone()),
+            ("explode", self.explode.clone()),
+            ("sql", self.sql.clone()),
+            ("tables", self.sql_tables.as_ref().map(|t| t.join(","))),
+            (
+                "join",
+                self.join.as_ref().map(|p| p.to_string_lossy().to_string()),
+            ),
+            ("on", self.join_on.clone()),
+            ("how", self.join_how.clone()),
+            ("join_revision", self.join_revision.clone()),
             ("randomize", randomize),
             ("reverse", should_reverse),
             ("page", page),
@@ -296,6 +503,7 @@ impl DFOpts {
 
 #[cfg(test)]
 mod tests {
+    use crate::core::df::expr::DFColumnExp;
     use crate::{error::OxenError, opts::DFOpts};
 
     #[test]
@@ -313,7 +521,10 @@ mod tests {
         assert_eq!(agg_opt.agg.len(), 1);
         assert_eq!(agg_opt.agg[0].name, "list");
         assert_eq!(agg_opt.agg[0].args.len(), 1);
-        assert_eq!(agg_opt.agg[0].args[0], "col_1");
+        assert_eq!(
+            agg_opt.agg[0].args[0],
+            DFColumnExp::Col(String::from("col_1"))
+        );
         Ok(())
     }
 
@@ -332,7 +543,10 @@ mod tests {
         assert_eq!(agg_opt.agg.len(), 1);
         assert_eq!(agg_opt.agg[0].name, "count");
         assert_eq!(agg_opt.agg[0].args.len(), 1);
-        assert_eq!(agg_opt.agg[0].args[0], "col_1");
+        assert_eq!(
+            agg_opt.agg[0].args[0],
+            DFColumnExp::Col(String::from("col_1"))
+        );
         Ok(())
     }
 
@@ -352,7 +566,10 @@ mod tests {
         assert_eq!(agg_opt.agg.len(), 1);
         assert_eq!(agg_opt.agg[0].name, "list");
         assert_eq!(agg_opt.agg[0].args.len(), 1);
-        assert_eq!(agg_opt.agg[0].args[0], "col_1");
+        assert_eq!(
+            agg_opt.agg[0].args[0],
+            DFColumnExp::Col(String::from("col_1"))
+        );
         Ok(())
     }
 
@@ -372,15 +589,24 @@ mod tests {
         assert_eq!(agg_opt.agg.len(), 3);
         assert_eq!(agg_opt.agg[0].name, "list");
         assert_eq!(agg_opt.agg[0].args.len(), 1);
-        assert_eq!(agg_opt.agg[0].args[0], "col_3");
+        assert_eq!(
+            agg_opt.agg[0].args[0],
+            DFColumnExp::Col(String::from("col_3"))
+        );
 
         assert_eq!(agg_opt.agg[1].name, "max");
         assert_eq!(agg_opt.agg[1].args.len(), 1);
-        assert_eq!(agg_opt.agg[1].args[0], "col_2");
+        assert_eq!(
+            agg_opt.agg[1].args[0],
+            DFColumnExp::Col(String::from("col_2"))
+        );
 
         assert_eq!(agg_opt.agg[2].name, "n_unique");
         assert_eq!(agg_opt.agg[2].args.len(), 1);
-        assert_eq!(agg_opt.agg[2].args[0], "col_1");
+        assert_eq!(
+            agg_opt.agg[2].args[0],
+            DFColumnExp::Col(String::from("col_1"))
+        );
         Ok(())
     }
 
@@ -0,0 +1,163 @@
+use std::path::{Path, PathBuf};
+
+use crate::error::OxenError;
+use crate::util;
+
+#[derive(Clone, Debug)]
+pub struct SplitOpts {
+    /// Fraction of rows in each split, must add up to 1.0
+    pub ratios: Vec<f64>,
+    /// Defaults to train,test or train,val,test depending on the number of ratios
+    pub names: Option<Vec<String>>,
+    /// Column to keep the same distribution of values across every split
+    pub stratify: Option<String>,
+    /// Columns that identify a row, rows with the same key are always in the same split.
+    /// Defaults to every column.
+    pub key: Option<Vec<String>>,
+    pub seed: u64,
+    /// Directory to write the split files to, defaults to the directory of the input
+    pub output: Option<PathBuf>,
+    /// Stage the split files after writing them
+    pub add: bool,
+    /// Stage and commit the split files with this message
+    pub commit_message: Option<String>,
+}
+
+impl SplitOpts {
+    pub fn from_ratios(ratios: Vec<f64>) -> SplitOpts {
+        SplitOpts {
+            ratios,
+            names: None,
+            stratify: None,
+            key: None,
+            seed: 0,
+            output: None,
+            add: false,
+            commit_message: None,
+        }
+    }
+
+    /// Parse the ratios from a comma separated list
+    /// '0.8,0.1,0.1' -> [0.8, 0.1, 0.1]
+    pub fn parse_ratios(ratios: &str) -> Result<Vec<f64>, OxenError> {
+        ratios
+            .split(',')
+            .map(|r| {
+                r.trim().parse::<f64>().map_err(|_| {
+                    OxenError::parse_error(format!(
+                        "Invalid ratios '{ratios}'. Format: '0.8,0.1,0.1'"
+                    ))
+                })
+            })
+            .collect()
+    }
+
+    pub fn validate(&self) -> Result<(), OxenError> {
+        if self.ratios.len() < 2 {
+            return Err(OxenError::basic_str(
+                "Must supply at least two split ratios",
+            ));
+        }
+        if self.ratios.iter().any(|r| *r <= 0.0) {
+            return Err(OxenError::basic_str("Split ratios must be greater than 0"));
+        }
+        let total: f64 = self.ratios.iter().sum();
+        if (total - 1.0).abs() > 1e-6 {
+            return Err(OxenError::basic_str(format!(
+                "Split ratios must add up to 1.0, got {total}"
+            )));
+        }
+        let names = self.split_names();
+        if names.len() != self.ratios.len() {
+            return Err(OxenError::basic_str(format!(
+                "Got {} split names for {} ratios",
+                names.len(),
+                self.ratios.len()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn split_names(&self) -> Vec<String> {
+        if let Some(names) = &self.names {
+            return names.to_owned();
+        }
+        let names: Vec<&str> = match self.ratios.len() {
+            2 => vec!["train", "test"],
+            3 => vec!["train", "val", "test"],
+            n => return (0..n).map(|i| format!("split_{i}")).collect(),
+        };
+        names.into_iter().map(String::from).collect()
+    }
+
+    /// One file per split next to each other, in the same format as the input
+    /// 'data/labels.csv' -> ['data/labels_train.csv', 'data/labels_val.csv', 'data/labels_test.csv']
+    pub fn output_paths(&self, input: &Path) -> Result<Vec<PathBuf>, OxenError> {
+        let ext = util::fs::tabular_ext(input).ok_or_else(|| {
+            OxenError::basic_str(format!("Could not get the file type of {input:?}"))
+        })?;
+        // Strip only the compression and data extensions, labels.v2.csv.gz -> labels.v2
+        let uncompressed = if util::fs::is_compressed(input) {
+            input.file_stem().map(Path::new)
+        } else {
+            input.file_name().map(Path::new)
+        };
+        let stem = uncompressed
+            .and_then(|name| name.file_stem())
+            .and_then(|stem| stem.to_str())
+            .ok_or_else(|| OxenError::basic_str(format!("Invalid file name {input:?}")))?;
+        let dir = match &self.output {
+            Some(dir) => dir.to_owned(),
+            None => input.parent().map(Path::to_path_buf).unwrap_or_default(),
+        };
+        Ok(self
+            .split_names()
+            .iter()
+            .map(|name| dir.join(format!("{stem}_{name}.{ext}")))
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::error::OxenError;
+    use crate::opts::SplitOpts;
+
+    #[test]
+    fn test_split_opts_validate_ratios() -> Result<(), OxenError> {
+        let opts = SplitOpts::from_ratios(SplitOpts::parse_ratios("0.8, 0.1, 0.1")?);
+        opts.validate()?;
+        assert_eq!(opts.split_names(), vec!["train", "val", "test"]);
+
+        assert!(SplitOpts::from_ratios(vec![0.8, 0.1]).validate().is_err());
+        assert!(SplitOpts::from_ratios(vec![1.0]).validate().is_err());
+        assert!(SplitOpts::from_ratios(vec![1.2, -0.2]).validate().is_err());
+        assert!(SplitOpts::parse_ratios("0.8,x").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_split_opts_output_paths() -> Result<(), OxenError> {
+        let opts = SplitOpts::from_ratios(vec![0.9, 0.1]);
+        let paths = opts.output_paths(Path::new("data/labels.csv.gz"))?;
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("data/labels_train.csv"),
+                PathBuf::from("data/labels_test.csv")
+            ]
+        );
+
+        let paths = opts.output_paths(Path::new("exports/labels.2023.09.parquet"))?;
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::from("exports/labels.2023.09_train.parquet"),
+                PathBuf::from("exports/labels.2023.09_test.parquet")
+            ]
+        );
+        Ok(())
+    }
+}
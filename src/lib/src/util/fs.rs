@@ -502,10 +502,65 @@ pub fn file_create(path: impl AsRef<Path>) -> Result<std::fs::File, OxenError> {
 }
 
 pub fn is_tabular(path: &Path) -> bool {
-    let exts: HashSet<String> = vec!["csv", "tsv", "parquet", "arrow", "ndjson", "jsonl"]
-        .into_iter()
-        .map(String::from)
-        .collect();
+    let exts: HashSet<String> = vec![
+        "csv", "tsv", "parquet", "arrow", "arrows", "ndjson", "jsonl", "avro",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect();
+    match tabular_ext(path) {
+        Some(ext) => exts.contains(&ext),
+        None => false,
+    }
+}
+
+/// Uncompressed csv or tsv files, the only files that have a dialect to detect
+pub fn is_delimited_text(path: &Path) -> bool {
+    let exts: HashSet<String> = vec!["csv", "tsv"].into_iter().map(String::from).collect();
+    contains_ext(path, &exts)
+}
+
+/// Files compressed with gzip or zstd, ex) `data.csv.gz` or `data.jsonl.zst`
+pub fn is_compressed(path: &Path) -> bool {
+    let exts: HashSet<String> = vec!["gz", "zst"].into_iter().map(String::from).collect();
+    contains_ext(path, &exts)
+}
+
+/// The lowercase extension of the data itself, skipping over any compression extension.
+/// `data.csv.gz` -> `csv`
+pub fn tabular_ext(path: &Path) -> Option<String> {
+    let path = if is_compressed(path) {
+        Path::new(path.file_stem()?)
+    } else {
+        path
+    };
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_lowercase())
+}
+
+/// Read the full contents of a gzip or zstd compressed file
+pub fn read_decompressed(path: &Path) -> Result<Vec<u8>, OxenError> {
+    let file = File::open(path)?;
+    let mut buffer: Vec<u8> = vec![];
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("gz") => {
+            flate2::read::MultiGzDecoder::new(file).read_to_end(&mut buffer)?;
+        }
+        Some("zst") => {
+            zstd::stream::read::Decoder::new(file)?.read_to_end(&mut buffer)?;
+        }
+        _ => {
+            return Err(OxenError::basic_str(format!(
+                "Unknown compression for file {path:?}"
+            )))
+        }
+    }
+    Ok(buffer)
+}
+
+pub fn is_json(path: &Path) -> bool {
+    let exts: HashSet<String> = vec!["json"].into_iter().map(String::from).collect();
     contains_ext(path, &exts)
 }
 
@@ -938,4 +993,16 @@ mod tests {
             Ok(())
         })
     }
+
+    #[test]
+    fn compressed_tabular_ext() {
+        assert!(util::fs::is_tabular(Path::new("data/train.csv.gz")));
+        assert!(util::fs::is_tabular(Path::new("data/train.jsonl.zst")));
+        assert!(util::fs::is_tabular(Path::new("data/train.avro")));
+        assert!(!util::fs::is_tabular(Path::new("data/archive.tar.gz")));
+        assert_eq!(
+            util::fs::tabular_ext(Path::new("data/train.CSV.gz")),
+            Some(String::from("csv"))
+        );
+    }
 }
@@ -2,6 +2,7 @@
 //!
 
 pub mod branch;
+pub mod column_stats;
 pub mod commit;
 pub mod compare;
 pub mod entry;
@@ -18,7 +19,8 @@ pub mod repository;
 pub mod schema;
 pub mod status_message;
 
-pub use crate::view::compare::CompareResponse;
+pub use crate::view::column_stats::ColumnStatsResponse;
+pub use crate::view::compare::{CompareImageResponse, CompareResponse, CompareSummaryResponse};
 pub use crate::view::file_meta_data::{FileMetaData, FileMetaDataResponse, FilePathsResponse};
 pub use crate::view::status_message::{
     IsValidStatusMessage, StatusMessage, StatusMessageDescription,
@@ -26,10 +28,13 @@ pub use crate::view::status_message::{
 
 pub use crate::view::json_data_frame::{JsonDataFrame, JsonDataFrameSliceResponse};
 pub use crate::view::namespace::{ListNamespacesResponse, NamespaceResponse, NamespaceView};
-pub use crate::view::schema::{ListSchemaResponse, SchemaResponse};
+pub use crate::view::schema::{
+    ListSchemaResponse, SchemaDiffResponse, SchemaLogResponse, SchemaResponse,
+};
 
 pub use crate::view::repository::{
-    ListRepositoryResponse, RepositoryResolveResponse, RepositoryResponse, RepositoryView,
+    ListRepositoryResponse, RepositoryResolveResponse, RepositoryResponse,
+    RepositorySizeHistoryResponse, RepositorySizeResponse, RepositoryView,
 };
 
 pub use crate::view::entry::{
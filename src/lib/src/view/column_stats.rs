@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+
+use crate::model::{Commit, TabularStats};
+
+use super::StatusMessage;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ColumnStatsResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub commit: Commit,
+    /// The stats of the commit are still being computed, poll again to get all of them
+    pub is_processing: bool,
+    pub stats: Vec<TabularStats>,
+}
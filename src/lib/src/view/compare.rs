@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
-use crate::model::{Commit, DiffEntry};
+use crate::model::{Commit, DiffEntry, DiffSummary, ImageDiff};
 
 use super::StatusMessage;
 
@@ -16,3 +17,22 @@ pub struct CompareResponse {
     pub total_pages: usize,
     pub total_entries: usize,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CompareSummaryResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub base_commit: Commit,
+    pub head_commit: Commit,
+    pub summary: DiffSummary,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CompareImageResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub base_commit: Commit,
+    pub head_commit: Commit,
+    pub path: PathBuf,
+    pub image_diff: ImageDiff,
+}
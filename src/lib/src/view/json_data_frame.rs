@@ -6,7 +6,8 @@ use serde::{Deserialize, Serialize};
 use std::io::Cursor;
 
 use crate::core::df::tabular;
-use crate::{model::Schema, opts::DFOpts};
+use crate::model::{self, Schema};
+use crate::opts::DFOpts;
 
 use super::StatusMessage;
 
@@ -92,9 +93,17 @@ impl JsonDataFrame {
                         let opts = DFOpts::from_column_names(columns);
                         tabular::transform(df, opts).unwrap()
                     } else {
-                        let cols = columns
+                        // Keep the dtypes from the schema, types we do not model stay strings
+                        let cols = self
+                            .schema
+                            .fields
                             .iter()
-                            .map(|name| Series::new(name, Vec::<&str>::new()))
+                            .map(|field| match field.data_type() {
+                                model::schema::DataType::Unknown => {
+                                    Series::new(&field.name, Vec::<&str>::new())
+                                }
+                                dtype => Series::new_empty(&field.name, &dtype.to_polars()),
+                            })
                             .collect::<Vec<Series>>();
                         DataFrame::new(cols).unwrap()
                     }
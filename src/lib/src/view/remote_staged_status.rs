@@ -4,7 +4,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     model::{
-        DirEntry, LocalRepository, ModEntry, StagedData, StagedEntry, SummarizedStagedDirStats,
+        DirEntry, JsonDiff, LocalRepository, ModEntry, StagedData, StagedEntry,
+        SummarizedStagedDirStats,
     },
     util,
 };
@@ -58,6 +59,14 @@ pub struct ListStagedFileModResponseDF {
     pub modifications: StagedDFModifications,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ListStagedFileModResponseJson {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub data_type: String,
+    pub modifications: JsonDiff,
+}
+
 impl RemoteStagedStatus {
     pub fn from_staged(
         repo: &LocalRepository,
@@ -113,6 +122,7 @@ impl RemoteStagedStatus {
                     latest_commit: None,
                     datatype: util::fs::file_datatype(&full_path),
                     resource: None, // not committed so does not have a resource
+                    image_meta_data: None,
                 }
             })
             .collect()
@@ -1,4 +1,4 @@
-use crate::model::RemoteRepository;
+use crate::model::{Commit, RemoteRepository, RepoSizeLogEntry, RepoSizeStats};
 use serde::{Deserialize, Serialize};
 
 use super::StatusMessage;
@@ -59,3 +59,19 @@ impl RepositoryView {
         }
     }
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RepositorySizeResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub commit: Commit,
+    pub stats: RepoSizeStats,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct RepositorySizeHistoryResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    /// Oldest commit first
+    pub entries: Vec<RepoSizeLogEntry>,
+}
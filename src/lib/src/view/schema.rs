@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::model::schema::{SchemaFileDiff, SchemaLogEntry};
 use crate::model::Schema;
 
 use super::StatusMessage;
@@ -17,3 +18,17 @@ pub struct ListSchemaResponse {
     pub status: StatusMessage,
     pub schemas: Vec<Schema>,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SchemaDiffResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub diffs: Vec<SchemaFileDiff>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SchemaLogResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub entries: Vec<SchemaLogEntry>,
+}
@@ -14,11 +14,15 @@ use liboxen::view::http::MSG_INTERNAL_SERVER_ERROR;
 use liboxen::view::http::MSG_RESOURCE_IS_PROCESSING;
 use liboxen::view::http::STATUS_ERROR;
 use liboxen::view::http::{MSG_RESOURCE_FOUND, STATUS_SUCCESS};
-use liboxen::view::{CommitResponse, IsValidStatusMessage, ListCommitResponse, StatusMessage};
+use liboxen::view::{
+    CommitResponse, IsValidStatusMessage, ListCommitResponse, StatusMessage,
+    StatusMessageDescription,
+};
 
 use crate::app_data::OxenAppData;
 use crate::errors::OxenHttpError;
 use crate::helpers::get_repo;
+use crate::jobs::{self, JobQueue};
 use crate::params::{app_data, path_param};
 
 use actix_web::{web, Error, HttpRequest, HttpResponse};
@@ -308,6 +312,7 @@ pub async fn create(
         };
 
     // Create Commit from uri params
+    // Its files are not synced yet, so the schema contracts are checked when the push completes
     match api::local::commits::create_commit_object(&repository.path, bn.branch_name, &commit) {
         Ok(_) => Ok(HttpResponse::Ok().json(CommitResponse {
             status: StatusMessage::resource_created(),
@@ -558,27 +563,41 @@ pub async fn complete(req: HttpRequest) -> Result<HttpResponse, Error> {
         Ok(Some(repo)) => {
             match api::local::commits::get_by_id(&repo, commit_id) {
                 Ok(Some(commit)) => {
-                    // Kick off processing in background thread because could take awhile
-                    std::thread::spawn(move || {
-                        log::debug!("Processing commit {:?} on repo {:?}", commit, repo.path);
-                        match commit_cacher::run_all(&repo, &commit) {
-                            Ok(_) => {
-                                log::debug!(
-                                    "Success processing commit {:?} on repo {:?}",
-                                    commit,
-                                    repo.path
-                                );
-                            }
-                            Err(err) => {
+                    // The data is synced now, so the commit can be checked against the schema
+                    // contracts versioned with it before it is accepted
+                    match api::local::schemas::check_commit_contracts(&repo, &commit) {
+                        Ok(()) => {}
+                        Err(OxenError::SchemaContractViolation(report)) => {
+                            log::debug!("Rejecting commit {}: {}", commit.id, report);
+                            if let Err(err) = api::local::commits::remove_rejected(&repo, &commit) {
                                 log::error!(
-                                    "Could not process commit {:?} on repo {:?}: {}",
-                                    commit,
-                                    repo.path,
+                                    "Could not remove rejected commit {}: {}",
+                                    commit.id,
                                     err
                                 );
                             }
+                            return Ok(HttpResponse::BadRequest()
+                                .json(StatusMessageDescription::bad_request(format!("{report}"))));
                         }
-                    });
+                        Err(err) => {
+                            log::error!(
+                                "Could not check schema contracts of {}: {}",
+                                commit.id,
+                                err
+                            );
+                            return Ok(HttpResponse::InternalServerError()
+                                .json(StatusMessage::internal_server_error()));
+                        }
+                    }
+
+                    // Process in the background because it could take awhile
+                    jobs::process_commit_in_background(
+                        req.app_data::<JobQueue>(),
+                        repo,
+                        namespace,
+                        repo_name,
+                        commit,
+                    );
 
                     Ok(HttpResponse::Ok().json(StatusMessage::resource_created()))
                 }
@@ -650,7 +669,9 @@ mod tests {
     use liboxen::api;
     use liboxen::command;
     use liboxen::constants::OXEN_HIDDEN_DIR;
+    use liboxen::core::index::CommitEntryReader;
     use liboxen::error::OxenError;
+    use liboxen::model::schema::{FieldContract, SchemaContract, SchemaContracts};
     use liboxen::util;
     use liboxen::view::{CommitResponse, ListCommitResponse};
 
@@ -889,4 +910,76 @@ mod tests {
 
         Ok(())
     }
+
+    #[actix_web::test]
+    async fn test_controllers_commits_complete_rejects_schema_contract_violation(
+    ) -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let repo_name = "Testing-Name";
+        let repo = test::create_local_repo(&sync_dir, namespace, repo_name)?;
+
+        // Version the contract with the data it applies to
+        let contracts = SchemaContracts {
+            schema_contracts: vec![SchemaContract {
+                path: String::from("*.csv"),
+                fields: vec![
+                    FieldContract::new("file", "str"),
+                    FieldContract::new("label", "str"),
+                ],
+                allow_extra_fields: false,
+            }],
+        };
+        let contracts_path = api::local::schemas::write_contracts(&repo, &contracts)?;
+        let labels_path = repo.path.join("labels.csv");
+        util::fs::write_to_path(&labels_path, "file,label\na.jpg,cat\n")?;
+        command::add(&repo, &contracts_path)?;
+        command::add(&repo, &labels_path)?;
+        let first_commit = command::commit(&repo, "Adding labels")?;
+
+        util::fs::write_to_path(&labels_path, "file,label\na.jpg,cat\nb.jpg,dog\n")?;
+        command::add(&repo, &labels_path)?;
+        let commit = command::commit(&repo, "Adding a dog")?;
+
+        // As if a client that does not check the contracts pushed a renamed column
+        let entry = CommitEntryReader::new(&repo, &commit)?
+            .get_entry(Path::new("labels.csv"))?
+            .unwrap();
+        let version_path = util::fs::version_path(&repo, &entry);
+        util::fs::write_to_path(&version_path, "file,Label\na.jpg,cat\nb.jpg,dog\n")?;
+
+        let uri = format!(
+            "/oxen/{}/{}/commits/{}/complete",
+            namespace, repo_name, commit.id
+        );
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(OxenAppData {
+                    path: sync_dir.clone(),
+                })
+                .route(
+                    "/oxen/{namespace}/{repo_name}/commits/{commit_id}/complete",
+                    web::post().to(controllers::commits::complete),
+                ),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::post().uri(&uri).to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::BAD_REQUEST);
+        let bytes = actix_http::body::to_bytes(resp.into_body()).await.unwrap();
+        let body = std::str::from_utf8(&bytes).unwrap();
+        assert!(body.contains("column 'label' is missing, found 'Label'"));
+
+        // The commit and the version files it added are gone, the branch is back on its parent
+        assert!(api::local::commits::get_by_id(&repo, &commit.id)?.is_none());
+        assert!(!version_path.exists());
+        assert_eq!(api::local::commits::head_commit(&repo)?.id, first_commit.id);
+
+        // cleanup
+        util::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
 }
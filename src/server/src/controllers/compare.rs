@@ -2,13 +2,20 @@ use crate::errors::OxenHttpError;
 
 use actix_web::{web, HttpRequest, HttpResponse};
 use liboxen::error::OxenError;
-use liboxen::view::{CompareResponse, StatusMessage};
+use liboxen::view::{CompareImageResponse, CompareResponse, CompareSummaryResponse, StatusMessage};
 use liboxen::{api, constants, util};
+use serde::Deserialize;
+use std::path::PathBuf;
 
 use super::entries::PageNumQuery;
 use crate::helpers::get_repo;
 use crate::params::{app_data, parse_base_head, path_param, resolve_base_head_branches};
 
+#[derive(Deserialize, Debug)]
+pub struct ImageDiffQuery {
+    pub path: String,
+}
+
 pub async fn show(
     req: HttpRequest,
     query: web::Query<PageNumQuery>,
@@ -52,3 +59,75 @@ pub async fn show(
     };
     Ok(HttpResponse::Ok().json(view))
 }
+
+pub async fn summary(req: HttpRequest) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let name = path_param(&req, "repo_name")?;
+    let base_head = path_param(&req, "base_head")?;
+
+    // Get the repository or return error
+    let repository = get_repo(&app_data.path, namespace, name)?;
+
+    // Parse the base and head from the base..head string
+    let (base, head) = parse_base_head(&base_head)?;
+    let base_commit = api::local::commits::get_by_id_or_branch(&repository, &base)?
+        .ok_or(OxenError::committish_not_found(base.into()))?;
+    let head_commit = api::local::commits::get_by_id_or_branch(&repository, &head)?
+        .ok_or(OxenError::committish_not_found(head.into()))?;
+
+    let summary = api::local::diff::summarize_diff(&repository, &base_commit, &head_commit)?;
+
+    let view = CompareSummaryResponse {
+        status: StatusMessage::resource_found(),
+        base_commit,
+        head_commit,
+        summary,
+    };
+    Ok(HttpResponse::Ok().json(view))
+}
+
+pub async fn image(
+    req: HttpRequest,
+    query: web::Query<ImageDiffQuery>,
+) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let name = path_param(&req, "repo_name")?;
+    let base_head = path_param(&req, "base_head")?;
+
+    // Get the repository or return error
+    let repository = get_repo(&app_data.path, namespace, name)?;
+
+    // Parse the base and head from the base..head string
+    let (base, head) = parse_base_head(&base_head)?;
+    let base_commit = api::local::commits::get_by_id_or_branch(&repository, &base)?
+        .ok_or(OxenError::committish_not_found(base.into()))?;
+    let head_commit = api::local::commits::get_by_id_or_branch(&repository, &head)?
+        .ok_or(OxenError::committish_not_found(head.into()))?;
+
+    let path = PathBuf::from(&query.path);
+    let base_entry =
+        api::local::entries::get_commit_entry(&repository, &base_commit, &path)?.ok_or(
+            OxenError::entry_does_not_exist_in_commit(&path, &base_commit.id),
+        )?;
+    let head_entry =
+        api::local::entries::get_commit_entry(&repository, &head_commit, &path)?.ok_or(
+            OxenError::entry_does_not_exist_in_commit(&path, &head_commit.id),
+        )?;
+
+    let image_diff = api::local::diff::diff_image(
+        util::fs::version_path(&repository, &base_entry),
+        util::fs::version_path(&repository, &head_entry),
+        None::<&std::path::Path>,
+    )?;
+
+    let view = CompareImageResponse {
+        status: StatusMessage::resource_found(),
+        base_commit,
+        head_commit,
+        path,
+        image_diff,
+    };
+    Ok(HttpResponse::Ok().json(view))
+}
@@ -3,15 +3,24 @@ use crate::helpers::get_repo;
 use crate::params::df_opts_query::{self, DFOptsQuery};
 use crate::params::{app_data, parse_resource, path_param};
 
-use liboxen::{constants, current_function};
+use liboxen::error::OxenError;
+use liboxen::{api, constants, current_function};
 
-use actix_web::{web, HttpRequest, HttpResponse};
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
+use liboxen::core::cache::cachers::column_stats;
+use liboxen::core::cache::commit_cacher::{self, CacherStatusType};
 use liboxen::core::df::tabular;
+use liboxen::core::index::schema_indexer;
+use liboxen::model::ContentType;
 use liboxen::opts::DFOpts;
+use liboxen::view::http::MSG_RESOURCE_IS_PROCESSING;
 use liboxen::view::json_data_frame::JsonDataSize;
-use liboxen::view::{JsonDataFrame, JsonDataFrameSliceResponse, StatusMessage};
+use liboxen::view::{
+    ColumnStatsResponse, JsonDataFrame, JsonDataFrameSliceResponse, StatusMessage,
+};
 
 use liboxen::util;
+use std::str::FromStr;
 
 pub async fn get(
     req: HttpRequest,
@@ -30,32 +39,242 @@ pub async fn get(
         resource
     );
 
+    let content_type = response_content_type(&req, &query)?;
+
     let mut opts = DFOpts::empty();
     log::debug!("Initial opts {:?}", opts);
     opts = df_opts_query::parse_opts(&query, &mut opts);
 
+    // Tables referenced from sql are paths in the repo, read them from the same commit
+    if opts.sql_tables.is_some() {
+        let mut tables: Vec<String> = vec![];
+        for (name, path) in opts.sql_table_paths()? {
+            let version_path =
+                util::fs::version_path_for_commit_id(&repo, &resource.commit.id, &path)?;
+            tables.push(format!("{name}={}", version_path.to_string_lossy()));
+        }
+        opts.sql_tables = Some(tables);
+    }
+
+    // The file to join is a path in the repo, at the join revision or the same commit
+    if let Some(join) = &opts.join {
+        let commit_id = match &opts.join_revision {
+            Some(revision) => {
+                api::local::commits::get_by_id_or_branch(&repo, revision)?
+                    .ok_or(OxenError::committish_not_found(revision.to_owned().into()))?
+                    .id
+            }
+            None => resource.commit.id.to_owned(),
+        };
+        opts.join = Some(util::fs::version_path_for_commit_id(
+            &repo, &commit_id, join,
+        )?);
+    }
+
+    // Reads the data.arrow cache for the version if it has been converted
     let version_path =
         util::fs::version_path_for_commit_id(&repo, &resource.commit.id, &resource.file_path)?;
-    log::debug!("Reading version file {:?}", version_path);
-    let mut df = tabular::read_df(&version_path, opts)?;
-    log::debug!("Read df {:?}", df);
+    log::debug!("Scanning version file {:?}", version_path);
+
+    // Count the full result lazily, then only collect the requested page
+    // Equality filters on indexed fields only read the rows the index points to
+    let df =
+        match schema_indexer::scan_indexed(&repo, &resource.commit, &resource.file_path, &opts)? {
+            Some(df) => df,
+            None => tabular::scan_df(&version_path, &opts)?,
+        };
+    let df = tabular::transform_lazy(df, &opts)?;
+    let full_height = tabular::count_rows(df.clone())?;
+    let full_width = df
+        .schema()
+        .map_err(|err| OxenError::basic_str(format!("{err}")))?
+        .len();
 
     let page_size = query.page_size.unwrap_or(constants::DEFAULT_PAGE_SIZE);
     let page = query.page.unwrap_or(constants::DEFAULT_PAGE_NUM);
+    opts.page = Some(page);
+    opts.page_size = Some(page_size);
+    let mut df = tabular::collect_df(tabular::slice_lazy(df, &opts)?)?;
+    log::debug!("Read df page {:?}", df);
+
+    let total_pages = (full_height as f64 / page_size as f64).ceil() as usize;
 
-    let total_pages = (df.height() as f64 / page_size as f64).ceil() as usize;
+    if content_type != ContentType::Json {
+        // Only the json body has room for the page info, so for every other format it goes in the headers
+        let bytes = tabular::write_df_to_bytes(&mut df, &content_type)?;
+        return Ok(HttpResponse::Ok()
+            .content_type(content_type.to_http_content_type())
+            .insert_header((constants::PAGE_NUMBER_HEADER, page.to_string()))
+            .insert_header((constants::PAGE_SIZE_HEADER, page_size.to_string()))
+            .insert_header((constants::TOTAL_PAGES_HEADER, total_pages.to_string()))
+            .insert_header((constants::TOTAL_ENTRIES_HEADER, full_height.to_string()))
+            .insert_header((constants::FULL_WIDTH_HEADER, full_width.to_string()))
+            .body(bytes));
+    }
 
     let response = JsonDataFrameSliceResponse {
         status: StatusMessage::resource_found(),
         full_size: JsonDataSize {
-            width: df.width(),
-            height: df.height(),
+            width: full_width,
+            height: full_height,
         },
-        df: JsonDataFrame::from_df(&mut df),
+        df: JsonDataFrame::from_slice(
+            &mut df,
+            JsonDataSize {
+                width: full_width,
+                height: full_height,
+            },
+        ),
         page_number: page,
         page_size,
         total_pages,
-        total_entries: df.height(),
+        total_entries: full_height,
     };
     Ok(HttpResponse::Ok().json(response))
 }
+
+/// Respond with the `format` query param if given, otherwise the first supported type in the Accept header, otherwise json
+fn response_content_type(
+    req: &HttpRequest,
+    query: &DFOptsQuery,
+) -> Result<ContentType, OxenHttpError> {
+    if let Some(format) = &query.format {
+        return match ContentType::from_str(format) {
+            Ok(content_type) if content_type == ContentType::Text => Err(
+                OxenHttpError::BadRequest(format!("Unsupported format {format}").into()),
+            ),
+            Ok(content_type) => Ok(content_type),
+            Err(err) => Err(OxenHttpError::BadRequest(err.into())),
+        };
+    }
+
+    let accept = req
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .unwrap_or_default();
+    match ContentType::from_accept_header(accept) {
+        Some(ContentType::Text) | None => Ok(ContentType::Json),
+        Some(content_type) => Ok(content_type),
+    }
+}
+
+/// The cached column stats of a tabular file, or of every tabular file in the commit if no path is given
+pub async fn stats(req: HttpRequest) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let repo_name = path_param(&req, "repo_name")?;
+    let repo = get_repo(&app_data.path, namespace, &repo_name)?;
+    let resource = parse_resource(&req, &repo)?;
+    let commit = resource.commit;
+
+    log::debug!(
+        "{} commit {} path {:?}",
+        current_function!(),
+        commit.id,
+        resource.file_path
+    );
+
+    let is_processing = matches!(
+        commit_cacher::get_status(&repo, &commit)?,
+        None | Some(CacherStatusType::Pending)
+    );
+    let stats = if resource.file_path.as_os_str().is_empty() {
+        column_stats::list_stats(&repo, &commit)?
+    } else {
+        match column_stats::get_stats(&repo, &commit, &resource.file_path)? {
+            Some(stats) => vec![stats],
+            None if is_processing => vec![],
+            None => return Err(OxenError::path_does_not_exist(resource.file_path).into()),
+        }
+    };
+
+    let status = if is_processing {
+        StatusMessage::success(MSG_RESOURCE_IS_PROCESSING)
+    } else {
+        StatusMessage::resource_found()
+    };
+    Ok(HttpResponse::Ok().json(ColumnStatsResponse {
+        status,
+        commit,
+        is_processing,
+        stats,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{web, App};
+
+    use liboxen::command;
+    use liboxen::core::df::tabular;
+    use liboxen::error::OxenError;
+    use liboxen::model::ContentType;
+    use liboxen::opts::DFOpts;
+    use liboxen::util;
+    use std::str::FromStr;
+
+    use crate::app_data::OxenAppData;
+    use crate::controllers;
+    use crate::test;
+
+    #[actix_web::test]
+    async fn test_controllers_df_get_formats() -> Result<(), OxenError> {
+        test::init_test_env();
+
+        let sync_dir = test::get_sync_dir()?;
+
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Name";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+
+        liboxen::test::populate_dir_with_training_data(&repo.path)?;
+        command::add(&repo, &repo.path)?;
+        let commit = command::commit(&repo, "adding training data")?;
+
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(OxenAppData {
+                    path: sync_dir.clone(),
+                })
+                .route(
+                    "/oxen/{namespace}/{repo_name}/df/{resource:.*}",
+                    web::get().to(controllers::df::get),
+                ),
+        )
+        .await;
+
+        for (format, extension) in [("csv", "csv"), ("parquet", "parquet"), ("arrow", "arrows")] {
+            let uri = format!(
+                "/oxen/{namespace}/{name}/df/{}/annotations/train/bounding_box.csv?format={format}",
+                commit.id
+            );
+            let req = actix_web::test::TestRequest::get().uri(&uri).to_request();
+            let resp = actix_web::test::call_service(&app, req).await;
+            assert_eq!(resp.status(), actix_web::http::StatusCode::OK);
+
+            let content_type = ContentType::from_str(format).unwrap();
+            let header = resp
+                .headers()
+                .get(actix_web::http::header::CONTENT_TYPE)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .to_string();
+            assert_eq!(header, content_type.to_http_content_type());
+
+            // The body has to read back as the same data frame
+            let bytes = actix_http::body::to_bytes(resp.into_body()).await.unwrap();
+            let path = sync_dir.join(format!("response.{extension}"));
+            std::fs::write(&path, &bytes)?;
+            let df = tabular::read_df(&path, DFOpts::empty())?;
+            assert_eq!(df.height(), 6);
+            assert_eq!(df.width(), 6);
+        }
+
+        // cleanup
+        util::fs::remove_dir_all(sync_dir)?;
+
+        Ok(())
+    }
+}
@@ -2,6 +2,7 @@ use crate::errors::OxenHttpError;
 use crate::helpers::get_repo;
 use crate::params::{app_data, parse_resource, path_param};
 
+use liboxen::core::cache::cachers::image_meta_data;
 use liboxen::error::OxenError;
 use liboxen::util;
 use liboxen::view::{EntryMetaDataResponse, StatusMessage};
@@ -66,3 +67,24 @@ pub async fn meta_data(req: HttpRequest) -> actix_web::Result<HttpResponse, Oxen
     };
     Ok(HttpResponse::Ok().json(meta))
 }
+
+/// Small JPEG version of an image, once its commit is processed
+pub async fn thumbnail(req: HttpRequest) -> actix_web::Result<NamedFile, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let repo_name = path_param(&req, "repo_name")?;
+    let repo = get_repo(&app_data.path, &namespace, &repo_name)?;
+    let resource = parse_resource(&req, &repo)?;
+
+    log::debug!(
+        "{} resource {namespace}/{repo_name}/{resource}",
+        current_function!()
+    );
+
+    let entry =
+        api::local::entries::get_commit_entry(&repo, &resource.commit, &resource.file_path)?
+            .ok_or(OxenHttpError::NotFound)?;
+    let thumbnail_path =
+        image_meta_data::get_thumbnail_path(&repo, &entry).ok_or(OxenHttpError::NotFound)?;
+    Ok(NamedFile::open(thumbnail_path)?)
+}
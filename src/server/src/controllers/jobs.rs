@@ -0,0 +1,45 @@
+use crate::errors::OxenHttpError;
+use crate::helpers::get_repo;
+use crate::params::{app_data, job_queue, path_param};
+use crate::view::{JobResponse, ListJobsResponse};
+
+use liboxen::api;
+use liboxen::error::OxenError;
+use liboxen::view::StatusMessage;
+
+use actix_web::{HttpRequest, HttpResponse};
+
+/// The processing job of a commit
+pub async fn show(req: HttpRequest) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let job_queue = job_queue(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let name = path_param(&req, "repo_name")?;
+    let commit_or_branch = path_param(&req, "commit_or_branch")?;
+    let repository = get_repo(&app_data.path, &namespace, &name)?;
+    let commit = api::local::commits::get_by_id_or_branch(&repository, &commit_or_branch)?
+        .ok_or(OxenError::committish_not_found(commit_or_branch.into()))?;
+
+    match job_queue.get(&namespace, &name, &commit.id)? {
+        Some(job) => Ok(HttpResponse::Ok().json(JobResponse {
+            status: StatusMessage::resource_found(),
+            job,
+        })),
+        None => Ok(HttpResponse::NotFound().json(StatusMessage::resource_not_found())),
+    }
+}
+
+/// The processing jobs of a repository, most recently updated first
+pub async fn list(req: HttpRequest) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let job_queue = job_queue(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let name = path_param(&req, "repo_name")?;
+    get_repo(&app_data.path, &namespace, &name)?;
+
+    let jobs = job_queue.list(&namespace, &name)?;
+    Ok(HttpResponse::Ok().json(ListJobsResponse {
+        status: StatusMessage::resource_found(),
+        jobs,
+    }))
+}
@@ -10,7 +10,10 @@ use liboxen::view::http::{MSG_RESOURCE_FOUND, STATUS_SUCCESS};
 use liboxen::view::repository::DataTypeView;
 use liboxen::view::repository::RepositoryStatsResponse;
 use liboxen::view::repository::RepositoryStatsView;
-use liboxen::view::{ListRepositoryResponse, RepositoryResponse, RepositoryView, StatusMessage};
+use liboxen::view::{
+    ListRepositoryResponse, RepositoryResponse, RepositorySizeHistoryResponse,
+    RepositorySizeResponse, RepositoryView, StatusMessage,
+};
 
 use liboxen::model::{LocalRepository, RepositoryNew};
 
@@ -57,6 +60,41 @@ pub async fn show(req: HttpRequest) -> actix_web::Result<HttpResponse, OxenHttpE
     }))
 }
 
+/// Total size, file count, size per directory and rows per tabular file at a commit
+pub async fn size(req: HttpRequest) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let name = path_param(&req, "repo_name")?;
+    let commit_or_branch = path_param(&req, "commit_or_branch")?;
+    let repository = get_repo(&app_data.path, namespace, name)?;
+    let commit = api::local::commits::get_by_id_or_branch(&repository, &commit_or_branch)?
+        .ok_or(OxenError::committish_not_found(commit_or_branch.into()))?;
+
+    let stats = api::local::repositories::get_size_stats(&repository, &commit)?;
+    Ok(HttpResponse::Ok().json(RepositorySizeResponse {
+        status: StatusMessage::resource_found(),
+        commit,
+        stats,
+    }))
+}
+
+/// The size of the repository after every commit in the history of a commit, to chart its growth
+pub async fn size_history(req: HttpRequest) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let name = path_param(&req, "repo_name")?;
+    let commit_or_branch = path_param(&req, "commit_or_branch")?;
+    let repository = get_repo(&app_data.path, namespace, name)?;
+    let commit = api::local::commits::get_by_id_or_branch(&repository, &commit_or_branch)?
+        .ok_or(OxenError::committish_not_found(commit_or_branch.into()))?;
+
+    let entries = api::local::repositories::list_size_stats(&repository, &commit)?;
+    Ok(HttpResponse::Ok().json(RepositorySizeHistoryResponse {
+        status: StatusMessage::resource_found(),
+        entries,
+    }))
+}
+
 pub async fn stats(req: HttpRequest) -> HttpResponse {
     let app_data = req.app_data::<OxenAppData>().unwrap();
 
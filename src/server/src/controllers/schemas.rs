@@ -1,11 +1,22 @@
 use crate::app_data::OxenAppData;
+use crate::errors::OxenHttpError;
+use crate::helpers::get_repo;
+use crate::params::{app_data, parse_base_head, parse_resource, path_param, resolve_base_head};
 
 use liboxen::api;
+use liboxen::error::OxenError;
 
-use actix_web::{HttpRequest, HttpResponse};
-use liboxen::view::{ListSchemaResponse, StatusMessage};
+use actix_web::{web, HttpRequest, HttpResponse};
+use liboxen::view::{ListSchemaResponse, SchemaDiffResponse, SchemaLogResponse, StatusMessage};
+use serde::Deserialize;
 use std::path::PathBuf;
 
+#[derive(Deserialize, Debug)]
+pub struct SchemaDiffQuery {
+    /// Only compare the files with this schema name or hash
+    pub schema: Option<String>,
+}
+
 pub async fn get(req: HttpRequest) -> HttpResponse {
     let app_data = req.app_data::<OxenAppData>().unwrap();
 
@@ -57,3 +68,49 @@ pub async fn get(req: HttpRequest) -> HttpResponse {
         }
     }
 }
+
+pub async fn diff(
+    req: HttpRequest,
+    query: web::Query<SchemaDiffQuery>,
+) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let name = path_param(&req, "repo_name")?;
+    let base_head = path_param(&req, "base_head")?;
+    let repository = get_repo(&app_data.path, namespace, name)?;
+
+    let (base, head) = parse_base_head(&base_head)?;
+    let (base_commit, head_commit) = resolve_base_head(&repository, &base, &head)?;
+    let base_commit = base_commit.ok_or(OxenError::committish_not_found(base.into()))?;
+    let head_commit = head_commit.ok_or(OxenError::committish_not_found(head.into()))?;
+
+    let diffs = api::local::schemas::diff(
+        &repository,
+        &base_commit,
+        &head_commit,
+        query.schema.as_deref(),
+    )?;
+    Ok(HttpResponse::Ok().json(SchemaDiffResponse {
+        status: StatusMessage::resource_found(),
+        diffs,
+    }))
+}
+
+pub async fn log(req: HttpRequest) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    let app_data = app_data(&req)?;
+    let namespace = path_param(&req, "namespace")?;
+    let name = path_param(&req, "repo_name")?;
+    let repository = get_repo(&app_data.path, namespace, name)?;
+    let resource = parse_resource(&req, &repository)?;
+
+    log::debug!(
+        "schemas::log {:?} from commit {}",
+        resource.file_path,
+        resource.commit.id
+    );
+    let entries = api::local::schemas::log(&repository, &resource.commit, &resource.file_path)?;
+    Ok(HttpResponse::Ok().json(SchemaLogResponse {
+        status: StatusMessage::resource_found(),
+        entries,
+    }))
+}
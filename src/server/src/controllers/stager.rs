@@ -1,31 +1,32 @@
 use crate::app_data::OxenAppData;
 use crate::errors::OxenHttpError;
 use crate::helpers::get_repo;
+use crate::jobs::{self, JobQueue};
 use crate::params::{
     app_data,
     df_opts_query::{self, DFOptsQuery},
     parse_resource, path_param,
 };
 
-use liboxen::core::cache::commit_cacher;
 use liboxen::core::df::tabular;
 use liboxen::core::index::mod_stager;
 use liboxen::error::OxenError;
 use liboxen::model::entry::mod_entry::NewMod;
 use liboxen::model::{
-    entry::mod_entry::ModType, Branch, CommitBody, CommitEntry, ContentType, LocalRepository,
-    ObjectID, Schema,
+    entry::mod_entry::ModType, Branch, CommitBody, CommitEntry, ContentType, JsonDiff,
+    LocalRepository, ObjectID, Schema,
 };
 use liboxen::opts::DFOpts;
 
 use liboxen::view::json_data_frame::JsonDataSize;
 use liboxen::view::remote_staged_status::{
-    ListStagedFileModResponseDF, RemoteStagedStatus, StagedDFModifications, StagedFileModResponse,
+    ListStagedFileModResponseDF, ListStagedFileModResponseJson, RemoteStagedStatus,
+    StagedDFModifications, StagedFileModResponse,
 };
 use liboxen::view::{
     CommitResponse, FilePathsResponse, JsonDataFrame, RemoteStagedStatusResponse, StatusMessage,
 };
-use liboxen::{api, constants, core::index};
+use liboxen::{api, constants, core::index, util};
 
 use actix_web::{web, web::Bytes, HttpRequest, HttpResponse};
 use std::io::Write;
@@ -87,16 +88,16 @@ pub async fn diff_file(
         api::local::entries::get_commit_entry(&repo, &resource.commit, &resource.file_path)?
             .ok_or(OxenHttpError::NotFound)?;
 
-    Ok(df_mods_response(
-        &repo,
-        &resource
-            .branch
-            .to_owned()
-            .ok_or(OxenError::parsed_resource_not_found(resource))?,
-        &identifier,
-        &entry,
-        query,
-    ))
+    let branch = resource
+        .branch
+        .to_owned()
+        .ok_or(OxenError::parsed_resource_not_found(resource))?;
+
+    if util::fs::is_json(&entry.path) {
+        return json_mods_response(&repo, &branch, &identifier, &entry);
+    }
+
+    Ok(df_mods_response(&repo, &branch, &identifier, &entry, query))
 }
 
 async fn save_parts(
@@ -364,70 +365,54 @@ pub async fn commit(req: HttpRequest, body: String) -> Result<HttpResponse, Erro
     log::debug!("stager::commit repo name {repo_name} -> {branch_name}");
     match api::local::repositories::get_by_namespace_and_name(&app_data.path, namespace, repo_name)
     {
-        Ok(Some(repo)) => match api::local::branches::get_by_name(&repo, branch_name) {
-            Ok(Some(branch)) => {
-                let branch_repo =
-                    index::remote_dir_stager::init_or_get(&repo, &branch, user_id).unwrap();
-                match index::remote_dir_stager::commit_staged(
-                    &repo,
-                    &branch_repo,
-                    &branch,
-                    &data.user,
-                    user_id,
-                    &data.message,
-                ) {
-                    Ok(commit) => {
-                        log::debug!("stager::commit ✅ success! commit {:?}", commit);
-
-                        // Clone the commit so we can move it into the thread
-                        let ret_commit = commit.clone();
-
-                        // Start computing data about the commit in the background thread
-                        std::thread::spawn(move || {
-                            log::debug!("Processing commit {:?} on repo {:?}", commit, repo.path);
-                            match commit_cacher::run_all(&repo, &commit) {
-                                Ok(_) => {
-                                    log::debug!(
-                                        "Success processing commit {:?} on repo {:?}",
-                                        commit,
-                                        repo.path
-                                    );
-                                }
-                                Err(err) => {
-                                    log::error!(
-                                        "Could not process commit {:?} on repo {:?}: {}",
-                                        commit,
-                                        repo.path,
-                                        err
-                                    );
-                                }
-                            }
-                        });
-
-                        Ok(HttpResponse::Ok().json(CommitResponse {
-                            status: StatusMessage::resource_created(),
-                            commit: ret_commit,
-                        }))
-                    }
-                    Err(err) => {
-                        log::error!("unable to commit branch {:?}. Err: {}", branch_name, err);
-                        Ok(HttpResponse::UnprocessableEntity()
-                            .json(StatusMessage::error(format!("{err:?}"))))
+        Ok(Some(repo)) => {
+            match api::local::branches::get_by_name(&repo, branch_name) {
+                Ok(Some(branch)) => {
+                    let branch_repo =
+                        index::remote_dir_stager::init_or_get(&repo, &branch, user_id).unwrap();
+                    match index::remote_dir_stager::commit_staged(
+                        &repo,
+                        &branch_repo,
+                        &branch,
+                        &data.user,
+                        user_id,
+                        &data.message,
+                    ) {
+                        Ok(commit) => {
+                            log::debug!("stager::commit ✅ success! commit {:?}", commit);
+
+                            // Start computing data about the commit in the background
+                            jobs::process_commit_in_background(
+                                req.app_data::<JobQueue>(),
+                                repo.to_owned(),
+                                namespace,
+                                repo_name,
+                                commit.to_owned(),
+                            );
+
+                            Ok(HttpResponse::Ok().json(CommitResponse {
+                                status: StatusMessage::resource_created(),
+                                commit,
+                            }))
+                        }
+                        Err(err) => {
+                            log::error!("unable to commit branch {:?}. Err: {}", branch_name, err);
+                            Ok(HttpResponse::UnprocessableEntity()
+                                .json(StatusMessage::error(format!("{err:?}"))))
+                        }
                     }
                 }
+                Ok(None) => {
+                    log::debug!("unable to find branch {}", branch_name);
+                    Ok(HttpResponse::NotFound().json(StatusMessage::resource_not_found()))
+                }
+                Err(err) => {
+                    log::error!("Could not commit staged: {:?}", err);
+                    Ok(HttpResponse::InternalServerError()
+                        .json(StatusMessage::internal_server_error()))
+                }
             }
-            Ok(None) => {
-                log::debug!("unable to find branch {}", branch_name);
-                Ok(HttpResponse::NotFound().json(StatusMessage::resource_not_found()))
-            }
-            Err(err) => {
-                log::error!("Could not commit staged: {:?}", err);
-                Ok(
-                    HttpResponse::InternalServerError()
-                        .json(StatusMessage::internal_server_error()),
-                )
-            }
-        },
+        }
         Ok(None) => {
             log::debug!("unable to find repo {}", repo_name);
             Ok(HttpResponse::NotFound().json(StatusMessage::resource_not_found()))
@@ -587,6 +572,30 @@ fn delete_staged_file_on_branch(
     }
 }
 
+fn json_mods_response(
+    repo: &LocalRepository,
+    branch: &Branch,
+    user_id: &str,
+    entry: &CommitEntry,
+) -> actix_web::Result<HttpResponse, OxenHttpError> {
+    // Compare the committed version to the file staged on the branch, if there is one
+    let staged_path =
+        index::remote_dir_stager::branch_staging_dir(repo, branch, user_id).join(&entry.path);
+    let modifications = if staged_path.exists() {
+        let version_path = util::fs::version_path(repo, entry);
+        api::local::diff::diff_json(version_path, staged_path)?
+    } else {
+        JsonDiff::default()
+    };
+
+    let response = ListStagedFileModResponseJson {
+        status: StatusMessage::resource_found(),
+        data_type: String::from("json"),
+        modifications,
+    };
+    Ok(HttpResponse::Ok().json(response))
+}
+
 fn df_mods_response(
     repo: &LocalRepository,
     branch: &Branch,
@@ -128,6 +128,12 @@ impl error::ResponseError for OxenHttpError {
                             format!("Schema is invalid: '{}'", schema),
                         ))
                     }
+                    OxenError::SchemaContractViolation(report) => {
+                        log::debug!("Schema contract violation: {}", report);
+
+                        HttpResponse::BadRequest()
+                            .json(StatusMessageDescription::bad_request(format!("{report}")))
+                    }
                     OxenError::ParsingError(error) => {
                         log::error!("Parsing error: {}", error);
 
@@ -164,6 +170,7 @@ impl error::ResponseError for OxenHttpError {
                 OxenError::RepoNotFound(_) => StatusCode::NOT_FOUND,
                 OxenError::CommittishNotFound(_) => StatusCode::NOT_FOUND,
                 OxenError::InvalidSchema(_) => StatusCode::BAD_REQUEST,
+                OxenError::SchemaContractViolation(_) => StatusCode::BAD_REQUEST,
                 OxenError::ParsingError(_) => StatusCode::BAD_REQUEST,
                 _ => StatusCode::INTERNAL_SERVER_ERROR,
             },
@@ -0,0 +1,339 @@
+//! Jobs that process commits after they are pushed, persisted in rocksdb so that they are picked up
+//! again if the server stops before they finish. The db lives in the server dir, outside of the sync dir
+//! so that the sync dir only holds repositories.
+
+use liboxen::api;
+use liboxen::core::cache::commit_cacher;
+use liboxen::core::db::{self, str_json_db};
+use liboxen::error::OxenError;
+use liboxen::model::{Commit, LocalRepository, RepositoryNew};
+
+use rocksdb::{DBWithThreadMode, MultiThreaded};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+pub const JOBS_DIR: &str = "jobs";
+/// How many times a job runs before it is marked as failed
+pub const MAX_ATTEMPTS: u32 = 3;
+/// A failed job is queued again after this delay times the number of attempts
+const RETRY_DELAY: Duration = Duration::from_secs(30);
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+/// Run the commit cachers on a pushed commit
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Job {
+    pub namespace: String,
+    pub repo_name: String,
+    pub commit_id: String,
+    pub status: JobStatus,
+    pub attempts: u32,
+    /// The error of the last attempt
+    pub error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl Job {
+    fn key(&self) -> String {
+        job_key(&self.namespace, &self.repo_name, &self.commit_id)
+    }
+
+    fn is_active(&self) -> bool {
+        self.status == JobStatus::Queued || self.status == JobStatus::Running
+    }
+}
+
+fn job_key(namespace: &str, repo_name: &str, commit_id: &str) -> String {
+    format!("{namespace}/{repo_name}/{commit_id}")
+}
+
+/// Jobs are saved by repo and commit, the in memory queue only holds their keys
+#[derive(Clone)]
+pub struct JobQueue {
+    sync_dir: PathBuf,
+    db: Arc<DBWithThreadMode<MultiThreaded>>,
+    queue: Arc<deadqueue::unlimited::Queue<String>>,
+}
+
+impl fmt::Debug for JobQueue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "JobQueue({:?}, {} waiting)",
+            self.sync_dir,
+            self.queue.len()
+        )
+    }
+}
+
+impl JobQueue {
+    /// Open the jobs saved in `server_dir`, that run on the repositories in `sync_dir`.
+    /// Jobs that were queued or running when the server stopped are queued again.
+    pub fn open(server_dir: &Path, sync_dir: &Path) -> Result<JobQueue, OxenError> {
+        if !server_dir.exists() {
+            std::fs::create_dir_all(server_dir)?;
+        }
+
+        let db_path = server_dir.join(JOBS_DIR);
+        let opts = db::opts::default();
+        let db = DBWithThreadMode::open(&opts, dunce::simplified(&db_path))?;
+        let queue = JobQueue {
+            sync_dir: sync_dir.to_path_buf(),
+            db: Arc::new(db),
+            queue: Arc::new(deadqueue::unlimited::Queue::new()),
+        };
+
+        for mut job in str_json_db::list_vals::<MultiThreaded, Job>(&queue.db)? {
+            if job.is_active() {
+                log::debug!("JobQueue::open resuming job {}", job.key());
+                job.status = JobStatus::Queued;
+                queue.save(&mut job)?;
+                queue.queue.push(job.key());
+            }
+        }
+        Ok(queue)
+    }
+
+    /// Queue processing the commit, unless it is already queued or running
+    pub fn push(
+        &self,
+        namespace: &str,
+        repo_name: &str,
+        commit_id: &str,
+    ) -> Result<Job, OxenError> {
+        if let Some(job) = self.get(namespace, repo_name, commit_id)? {
+            if job.is_active() {
+                return Ok(job);
+            }
+        }
+
+        let mut job = Job {
+            namespace: namespace.to_string(),
+            repo_name: repo_name.to_string(),
+            commit_id: commit_id.to_string(),
+            status: JobStatus::Queued,
+            attempts: 0,
+            error: None,
+            updated_at: OffsetDateTime::now_utc(),
+        };
+        self.save(&mut job)?;
+        self.queue.push(job.key());
+        Ok(job)
+    }
+
+    pub fn get(
+        &self,
+        namespace: &str,
+        repo_name: &str,
+        commit_id: &str,
+    ) -> Result<Option<Job>, OxenError> {
+        let key = job_key(namespace, repo_name, commit_id);
+        str_json_db::get(&self.db, key)
+    }
+
+    /// The jobs of a repository, most recently updated first
+    pub fn list(&self, namespace: &str, repo_name: &str) -> Result<Vec<Job>, OxenError> {
+        let mut jobs: Vec<Job> = str_json_db::list_vals::<MultiThreaded, Job>(&self.db)?
+            .into_iter()
+            .filter(|job| job.namespace == namespace && job.repo_name == repo_name)
+            .collect();
+        jobs.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        Ok(jobs)
+    }
+
+    /// Number of jobs waiting for a worker
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Start `num_workers` threads that run the queued jobs
+    pub fn start_workers(&self, num_workers: usize) {
+        for worker in 0..num_workers {
+            let queue = self.clone();
+            std::thread::spawn(move || loop {
+                let key = futures::executor::block_on(queue.queue.pop());
+                log::debug!("JobQueue worker[{worker}] running {key}");
+                if let Err(err) = queue.run(&key) {
+                    log::error!("JobQueue worker[{worker}] could not run {key}: {err}");
+                }
+            });
+        }
+    }
+
+    /// Run the job, and queue it again after a delay if it fails and has attempts left
+    pub fn run(&self, key: &str) -> Result<Job, OxenError> {
+        let mut job: Job = match str_json_db::get(&self.db, key)? {
+            Some(job) => job,
+            None => return Err(OxenError::basic_str(format!("Job {key} does not exist"))),
+        };
+        job.status = JobStatus::Running;
+        job.attempts += 1;
+        self.save(&mut job)?;
+
+        match process_commit(&self.sync_dir, &job) {
+            Ok(_) => {
+                job.status = JobStatus::Succeeded;
+                job.error = None;
+                self.save(&mut job)?;
+            }
+            Err(err) => {
+                log::error!("Job {key} attempt {} failed: {err}", job.attempts);
+                job.error = Some(err.to_string());
+                if job.attempts < MAX_ATTEMPTS {
+                    job.status = JobStatus::Queued;
+                    self.save(&mut job)?;
+
+                    let queue = self.queue.clone();
+                    let key = key.to_string();
+                    let delay = RETRY_DELAY * job.attempts;
+                    std::thread::spawn(move || {
+                        std::thread::sleep(delay);
+                        queue.push(key);
+                    });
+                } else {
+                    job.status = JobStatus::Failed;
+                    self.save(&mut job)?;
+                }
+            }
+        }
+        Ok(job)
+    }
+
+    fn save(&self, job: &mut Job) -> Result<(), OxenError> {
+        job.updated_at = OffsetDateTime::now_utc();
+        str_json_db::put(&self.db, job.key(), &*job)
+    }
+}
+
+/// Process a commit on the job workers. Without a job queue, or if the job can not be saved, the commit
+/// is processed on a background thread instead, and is not retried or resumed after a restart.
+pub fn process_commit_in_background(
+    job_queue: Option<&JobQueue>,
+    repo: LocalRepository,
+    namespace: &str,
+    repo_name: &str,
+    commit: Commit,
+) {
+    match job_queue.map(|queue| queue.push(namespace, repo_name, &commit.id)) {
+        Some(Ok(_)) => return,
+        Some(Err(err)) => log::error!("Could not queue processing commit {}: {err}", commit.id),
+        None => log::warn!(
+            "Job queue is not configured, processing commit {}",
+            commit.id
+        ),
+    }
+
+    std::thread::spawn(move || {
+        log::debug!("Processing commit {:?} on repo {:?}", commit, repo.path);
+        if let Err(err) = commit_cacher::run_all(&repo, &commit) {
+            log::error!(
+                "Could not process commit {:?} on repo {:?}: {}",
+                commit,
+                repo.path,
+                err
+            );
+        }
+    });
+}
+
+/// Cachers that already succeeded on the commit are skipped, so retries only run the ones that failed
+fn process_commit(sync_dir: &Path, job: &Job) -> Result<(), OxenError> {
+    let repo = api::local::repositories::get_by_namespace_and_name(
+        sync_dir,
+        &job.namespace,
+        &job.repo_name,
+    )?
+    .ok_or_else(|| OxenError::repo_not_found(RepositoryNew::new(&job.namespace, &job.repo_name)))?;
+    let commit = api::local::commits::get_by_id(&repo, &job.commit_id)?
+        .ok_or_else(|| OxenError::committish_not_found(job.commit_id.to_owned().into()))?;
+
+    log::debug!("Processing commit {:?} on repo {:?}", commit, repo.path);
+    commit_cacher::run_all(&repo, &commit)?;
+
+    let failures = commit_cacher::get_failures(&repo, &commit)?;
+    if failures.is_empty() {
+        Ok(())
+    } else {
+        let errors: Vec<String> = failures
+            .into_iter()
+            .map(|f| format!("{}: {}", f.name, f.status_message))
+            .collect();
+        Err(OxenError::basic_str(errors.join(", ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use liboxen::command;
+    use liboxen::error::OxenError;
+    use liboxen::util;
+
+    use crate::jobs::{JobQueue, JobStatus, MAX_ATTEMPTS};
+    use crate::test;
+
+    #[test]
+    fn test_job_queue_runs_commit_and_dedupes() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+        let namespace = "Testing-Namespace";
+        let name = "Testing-Name";
+        let repo = test::create_local_repo(&sync_dir, namespace, name)?;
+        util::fs::write_to_path(&repo.path.join("labels.csv"), "file,label\na.jpg,cat\n")?;
+        command::add(&repo, &repo.path)?;
+        let commit = command::commit(&repo, "Adding labels")?;
+
+        let server_dir = test::get_sync_dir()?;
+        let queue = JobQueue::open(&server_dir, &sync_dir)?;
+        queue.push(namespace, name, &commit.id)?;
+        queue.push(namespace, name, &commit.id)?;
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.list(namespace, name)?.len(), 1);
+
+        let job = queue.run(&format!("{namespace}/{name}/{}", commit.id))?;
+        assert_eq!(job.status, JobStatus::Succeeded);
+        assert_eq!(job.attempts, 1);
+        // Nothing but the repositories is written to the sync dir
+        assert!(!util::fs::oxen_hidden_dir(&sync_dir).exists());
+
+        util::fs::remove_dir_all(sync_dir)?;
+        util::fs::remove_dir_all(server_dir)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_job_queue_resumes_jobs_after_restart() -> Result<(), OxenError> {
+        let sync_dir = test::get_sync_dir()?;
+        let server_dir = test::get_sync_dir()?;
+        {
+            let queue = JobQueue::open(&server_dir, &sync_dir)?;
+            queue.push("Testing-Namespace", "does-not-exist", "abc")?;
+        }
+
+        // The job was never run, so it is queued again when the queue is opened
+        let queue = JobQueue::open(&server_dir, &sync_dir)?;
+        assert_eq!(queue.len(), 1);
+
+        let job = queue.run("Testing-Namespace/does-not-exist/abc")?;
+        assert_eq!(job.status, JobStatus::Queued);
+        assert!(job.error.is_some());
+        assert!(job.attempts < MAX_ATTEMPTS);
+
+        util::fs::remove_dir_all(sync_dir)?;
+        util::fs::remove_dir_all(server_dir)?;
+        Ok(())
+    }
+}
@@ -6,6 +6,7 @@ pub mod auth;
 pub mod controllers;
 pub mod errors;
 pub mod helpers;
+pub mod jobs;
 pub mod params;
 pub mod routes;
 pub mod test;
@@ -29,6 +30,8 @@ const START_SERVER_USAGE: &str = "Usage: `oxen-server start -i 0.0.0.0 -p 3000`"
 
 const INVALID_PORT_MSG: &str = "Port must a valid number between 0-65535";
 
+const INVALID_WORKERS_MSG: &str = "Workers must be a valid number greater than 0";
+
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     env_logger::init_from_env(Env::default().default_filter_or("info,debug"));
@@ -38,6 +41,12 @@ async fn main() -> std::io::Result<()> {
         Err(_) => String::from("/tmp/oxen_sync"),
     };
 
+    // State of the server itself, like its jobs, kept out of the sync dir which only holds repositories
+    let server_dir = match std::env::var("SERVER_DIR") {
+        Ok(dir) => dir,
+        Err(_) => String::from("/tmp/oxen_server"),
+    };
+
     let command = Command::new("oxen-server")
         .version(VERSION)
         .about("Oxen Server")
@@ -65,6 +74,14 @@ async fn main() -> std::io::Result<()> {
                         .help("What port to bind the server to")
                         .action(clap::ArgAction::Set),
                 )
+                .arg(
+                    Arg::new("workers")
+                        .long("workers")
+                        .short('w')
+                        .default_value("2")
+                        .help("How many threads process pushed commits in the background")
+                        .action(clap::ArgAction::Set),
+                )
                 .arg(
                     Arg::new("auth")
                         .long("auth")
@@ -115,12 +132,37 @@ async fn main() -> std::io::Result<()> {
                     println!("🐂 v{VERSION}");
                     println!("Running on {host}:{port}");
                     println!("Syncing to directory: {sync_dir}");
+                    println!("Server state in directory: {server_dir}");
                     let enable_auth = sub_matches.get_flag("auth");
 
+                    let num_workers: usize = sub_matches
+                        .get_one::<String>("workers")
+                        .map(|n| n.parse::<usize>().expect(INVALID_WORKERS_MSG))
+                        .unwrap_or(2);
+                    // Without workers, pushed commits would be queued but never processed
+                    if num_workers == 0 {
+                        return Err(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            INVALID_WORKERS_MSG,
+                        ));
+                    }
+
                     let data = app_data::OxenAppData::from(&sync_dir);
+                    let job_queue =
+                        match jobs::JobQueue::open(Path::new(&server_dir), Path::new(&sync_dir)) {
+                            Ok(job_queue) => job_queue,
+                            Err(err) => {
+                                return Err(std::io::Error::new(
+                                    std::io::ErrorKind::Other,
+                                    format!("Could not open job queue: {err}"),
+                                ));
+                            }
+                        };
+                    job_queue.start_workers(num_workers);
                     HttpServer::new(move || {
                         App::new()
                             .app_data(data.clone())
+                            .app_data(job_queue.clone())
                             .route("/api/version", web::get().to(controllers::version::index))
                             .route("/api/health", web::get().to(controllers::health::index))
                             .route(
@@ -9,6 +9,7 @@ use actix_web::HttpRequest;
 
 use crate::app_data::OxenAppData;
 use crate::errors::OxenHttpError;
+use crate::jobs::JobQueue;
 
 pub mod df_opts_query;
 
@@ -17,6 +18,11 @@ pub fn app_data(req: &HttpRequest) -> Result<&OxenAppData, OxenHttpError> {
         .ok_or(OxenHttpError::AppDataDoesNotExist)
 }
 
+pub fn job_queue(req: &HttpRequest) -> Result<&JobQueue, OxenHttpError> {
+    req.app_data::<JobQueue>()
+        .ok_or(OxenHttpError::AppDataDoesNotExist)
+}
+
 pub fn path_param(req: &HttpRequest, param: &str) -> Result<String, OxenHttpError> {
     Ok(req
         .match_info()
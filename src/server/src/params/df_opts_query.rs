@@ -1,6 +1,7 @@
 use actix_web::web;
 use liboxen::opts::DFOpts;
 use serde::Deserialize;
+use std::path::PathBuf;
 
 #[derive(Deserialize, Debug)]
 pub struct DFOptsQuery {
@@ -11,10 +12,23 @@ pub struct DFOptsQuery {
     pub filter: Option<String>,
     pub aggregate: Option<String>,
     pub sort_by: Option<String>,
+    pub cast: Option<String>,
+    pub with_column: Option<String>,
+    pub pivot: Option<String>,
+    pub melt: Option<String>,
+    pub explode: Option<String>,
+    pub sql: Option<String>,
+    pub tables: Option<String>,
+    pub join: Option<String>,
+    pub on: Option<String>,
+    pub how: Option<String>,
+    pub join_revision: Option<String>,
     pub randomize: Option<bool>,
     pub reverse: Option<bool>,
     pub page: Option<usize>,
     pub page_size: Option<usize>,
+    /// Response type, 'json', 'arrow' or 'parquet'. Otherwise negotiated with the Accept header
+    pub format: Option<String>,
 }
 
 /// Provide some default vals for opts
@@ -45,6 +59,24 @@ pub fn parse_opts(query: &web::Query<DFOptsQuery>, filter_ops: &mut DFOpts) -> D
     filter_ops.filter = query.filter.clone();
     filter_ops.aggregate = query.aggregate.clone();
     filter_ops.sort_by = query.sort_by.clone();
+    filter_ops.cast = query.cast.clone();
+    // Computed columns can contain commas, so they are separated with ';'
+    filter_ops.with_columns = query
+        .with_column
+        .as_ref()
+        .map(|exprs| exprs.split(';').map(String::from).collect());
+    filter_ops.pivot = query.pivot.clone();
+    filter_ops.melt = query.melt.clone();
+    filter_ops.explode = query.explode.clone();
+    filter_ops.sql = query.sql.clone();
+    filter_ops.sql_tables = query
+        .tables
+        .as_ref()
+        .map(|tables| tables.split(',').map(String::from).collect());
+    filter_ops.join = query.join.as_ref().map(PathBuf::from);
+    filter_ops.join_on = query.on.clone();
+    filter_ops.join_how = query.how.clone();
+    filter_ops.join_revision = query.join_revision.clone();
     filter_ops.should_randomize = query.randomize.unwrap_or(false);
     filter_ops.should_reverse = query.reverse.unwrap_or(false);
 
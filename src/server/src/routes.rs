@@ -56,6 +56,14 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             "/{namespace}/{repo_name}/commits/{commit_or_branch:.*}/commit_db",
             web::get().to(controllers::commits::download_commit_db),
         )
+        .route(
+            "/{namespace}/{repo_name}/commits/{commit_or_branch:.*}/job",
+            web::get().to(controllers::jobs::show),
+        )
+        .route(
+            "/{namespace}/{repo_name}/jobs",
+            web::get().to(controllers::jobs::list),
+        )
         // ----- Branches ----- //
         .route(
             "/{namespace}/{repo_name}/branches",
@@ -78,6 +86,18 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             web::put().to(controllers::branches::update),
         )
         // ----- Compare ----- //
+        .route(
+            "/{namespace}/{repo_name}/compare/schemas/{base_head:.*}",
+            web::get().to(controllers::schemas::diff),
+        )
+        .route(
+            "/{namespace}/{repo_name}/compare/summary/{base_head:.*}",
+            web::get().to(controllers::compare::summary),
+        )
+        .route(
+            "/{namespace}/{repo_name}/compare/image/{base_head:.*}",
+            web::get().to(controllers::compare::image),
+        )
         .route(
             "/{namespace}/{repo_name}/compare/{base_head:.*}",
             web::get().to(controllers::compare::show),
@@ -134,6 +154,18 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             "/{namespace}/{repo_name}/stats",
             web::get().to(controllers::repositories::stats),
         )
+        .route(
+            "/{namespace}/{repo_name}/size/history/{commit_or_branch:.*}",
+            web::get().to(controllers::repositories::size_history),
+        )
+        .route(
+            "/{namespace}/{repo_name}/size/{commit_or_branch:.*}",
+            web::get().to(controllers::repositories::size),
+        )
+        .route(
+            "/{namespace}/{repo_name}/column_stats/{resource:.*}",
+            web::get().to(controllers::df::stats),
+        )
         // ----- Dir ----- //
         .route(
             "/{namespace}/{repo_name}/dir/{resource:.*}",
@@ -149,6 +181,11 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             "/{namespace}/{repo_name}/entry/{resource:.*}",
             web::get().to(controllers::file::meta_data),
         )
+        // ----- Thumbnail (returns a small version of an image) ----- //
+        .route(
+            "/{namespace}/{repo_name}/thumbnail/{resource:.*}",
+            web::get().to(controllers::file::thumbnail),
+        )
         .route(
             "/{namespace}/{repo_name}/chunk/{resource:.*}", // Download a chunk of a larger versioned file
             web::get().to(controllers::entries::download_chunk),
@@ -169,6 +206,10 @@ pub fn config(cfg: &mut web::ServiceConfig) {
             web::get().to(controllers::entries::download_data_from_version_paths),
         )
         // ----- Schemas ----- //
+        .route(
+            "/{namespace}/{repo_name}/schemas/log/{resource:.*}",
+            web::get().to(controllers::schemas::log),
+        )
         .route(
             "/{namespace}/{repo_name}/schemas/{resource:.*}",
             web::get().to(controllers::schemas::get),
@@ -1,5 +1,8 @@
+use liboxen::view::StatusMessage;
 use serde::{Deserialize, Serialize};
 
+use crate::jobs::Job;
+
 #[derive(Deserialize, Serialize, Debug)]
 pub struct PaginatedLinesResponse {
     pub status: String,
@@ -10,3 +13,17 @@ pub struct PaginatedLinesResponse {
     pub total_pages: usize,
     pub total_entries: usize,
 }
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct JobResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub job: Job,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ListJobsResponse {
+    #[serde(flatten)]
+    pub status: StatusMessage,
+    pub jobs: Vec<Job>,
+}
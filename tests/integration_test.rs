@@ -2,9 +2,14 @@ use liboxen::api;
 use liboxen::command;
 use liboxen::config::UserConfig;
 use liboxen::constants;
+use liboxen::core::cache::cachers::column_stats;
+use liboxen::core::cache::cachers::image_meta_data;
+use liboxen::core::cache::cachers::repo_stats;
+use liboxen::core::cache::commit_cacher::{self, CacherStatusType};
 use liboxen::core::df::tabular;
 use liboxen::core::index::CommitEntryReader;
 use liboxen::error::OxenError;
+use liboxen::model::schema::FieldMetadata;
 use liboxen::model::staged_data::StagedDataOpts;
 use liboxen::model::CommitBody;
 use liboxen::model::ContentType;
@@ -14,6 +19,7 @@ use liboxen::opts::DFOpts;
 use liboxen::opts::PaginateOpts;
 use liboxen::opts::RestoreOpts;
 use liboxen::opts::RmOpts;
+use liboxen::opts::SplitOpts;
 use liboxen::test;
 use liboxen::util;
 
@@ -2968,6 +2974,129 @@ shape: (1, 6)
     })
 }
 
+#[test]
+fn test_diff_tabular_between_revisions() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let bbox_filename = Path::new("annotations")
+            .join("train")
+            .join("bounding_box.csv");
+        let bbox_file = repo.path.join(&bbox_filename);
+        let base_commit = api::local::commits::head_commit(&repo)?;
+
+        // Remove a row and commit it
+        let bbox_file = test::modify_txt_file(
+            bbox_file,
+            r"file,label,min_x,min_y,width,height
+train/dog_1.jpg,dog,101.5,32.0,385,330
+train/dog_2.jpg,dog,7.0,29.5,246,247
+train/cat_2.jpg,cat,30.5,44.0,333,396
+",
+        )?;
+        command::add(&repo, &bbox_file)?;
+        let head_commit = command::commit(&repo, "Removing a row from train bbox data")?;
+
+        // Neither revision is checked out, both are read from the version store
+        let range = format!("{}..{}", base_commit.id, head_commit.id);
+        let diff = command::diff(&repo, Some(&range), &bbox_file)?;
+        assert!(diff.contains("Removed Rows"));
+
+        // The schema from the original revision is still readable
+        let schema = command::df::schema_revision(
+            &repo,
+            &bbox_file,
+            &base_commit.id,
+            true,
+            DFOpts::empty(),
+        )?;
+        assert!(schema.contains("min_x"));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_df_revision_reads_sql_tables_from_the_commit() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let bbox_file = repo
+            .path
+            .join("annotations")
+            .join("train")
+            .join("bounding_box.csv");
+        let animals_file = repo.path.join("animals.csv");
+        test::write_txt_file_to_path(&animals_file, "label,is_animal\ndog,true\ncat,true\n")?;
+        command::add(&repo, &animals_file)?;
+        let commit = command::commit(&repo, "Adding animals")?;
+
+        // The working tree no longer matches any row, the committed version matches all of them
+        test::modify_txt_file(&animals_file, "label,is_animal\ndog,false\ncat,false\n")?;
+
+        let output = repo.path.join("animal_boxes.csv");
+        let mut opts = DFOpts::empty();
+        opts.sql = Some(String::from(
+            "SELECT df.file FROM df JOIN animals ON df.label = animals.label WHERE animals.is_animal = true",
+        ));
+        opts.sql_tables = Some(vec![format!("animals={}", animals_file.to_string_lossy())]);
+        opts.output = Some(output.to_owned());
+        command::df::df_revision(&repo, &bbox_file, &commit.id, opts)?;
+
+        let boxes = tabular::read_df(&bbox_file, DFOpts::empty())?;
+        let animal_boxes = tabular::read_df(&output, DFOpts::empty())?;
+        assert_eq!(animal_boxes.height(), boxes.height());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_split_commit_and_resplit_keeps_rows() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let labels_file = repo.path.join("labels.csv");
+        let mut rows = String::from("file,label\n");
+        for i in 0..20 {
+            let label = if i % 2 == 0 { "cat" } else { "dog" };
+            rows.push_str(&format!("{i}.jpg,{label}\n"));
+        }
+        let labels_file = test::write_txt_file_to_path(labels_file, &rows)?;
+
+        let mut opts = SplitOpts::from_ratios(vec![0.5, 0.5]);
+        opts.key = Some(vec![String::from("file")]);
+        opts.stratify = Some(String::from("label"));
+        opts.commit_message = Some(String::from("Splitting labels"));
+        let commit = command::split::split_in_repo(&repo, &labels_file, &opts)?;
+        assert!(commit.is_some());
+
+        let train_file = repo.path.join("labels_train.csv");
+        let test_file = repo.path.join("labels_test.csv");
+        let train = tabular::read_df(&train_file, DFOpts::empty())?;
+        assert_eq!(train.height(), 10);
+        let status = command::status(&repo)?;
+        assert!(status.added_files.is_empty());
+
+        // Add more rows and split again without committing
+        rows.push_str("20.jpg,cat\n21.jpg,dog\n22.jpg,cat\n23.jpg,dog\n");
+        let labels_file = test::write_txt_file_to_path(labels_file, &rows)?;
+        opts.commit_message = None;
+        command::split::split_in_repo(&repo, &labels_file, &opts)?;
+
+        let resplit = tabular::read_df(&train_file, DFOpts::empty())?;
+        let test_split = tabular::read_df(&test_file, DFOpts::empty())?;
+        assert_eq!(resplit.height() + test_split.height(), 24);
+        // Every row that was in train before still is
+        let files: Vec<Option<&str>> = resplit
+            .column("file")
+            .unwrap()
+            .utf8()
+            .unwrap()
+            .into_iter()
+            .collect();
+        for file in train.column("file").unwrap().utf8().unwrap().into_iter() {
+            assert!(files.contains(&file), "{file:?} moved out of train");
+        }
+
+        Ok(())
+    })
+}
+
 #[test]
 fn test_diff_tabular_remove_row() -> Result<(), OxenError> {
     test::run_training_data_repo_test_fully_committed(|repo| {
@@ -3690,3 +3819,355 @@ async fn test_commit_remote_staging_behind_main() -> Result<(), OxenError> {
     })
     .await
 }
+
+#[test]
+fn test_field_metadata_is_committed_and_carried_to_new_versions() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let bbox_file = repo
+            .path
+            .join("annotations")
+            .join("train")
+            .join("bounding_box.csv");
+        test::append_line_txt_file(&bbox_file, "train/cat_3.jpg,cat,41.0,31.5,410,427")?;
+        command::add(&repo, &bbox_file)?;
+
+        let schema = command::schemas::list_staged(&repo)?.pop().unwrap();
+        let metadata = FieldMetadata {
+            description: Some(String::from("What is in the box")),
+            allowed_values: Some(vec![String::from("cat"), String::from("dog")]),
+            ..FieldMetadata::default()
+        };
+        command::schemas::set_field_metadata(&repo, &schema.hash, "label", &metadata, &[])?;
+        let commit = command::commit(&repo, "Describing the labels")?;
+
+        // Metadata is not part of the hash, and is read back from the commit
+        let committed =
+            api::local::schemas::get_schema_for_path(&repo, Some(&commit), &bbox_file)?.unwrap();
+        assert_eq!(committed.hash, schema.hash);
+        assert_eq!(
+            committed.get_field("label").unwrap().metadata,
+            Some(metadata.clone())
+        );
+
+        // Changing the file again keeps the metadata on the staged schema
+        test::append_line_txt_file(&bbox_file, "train/dog_4.jpg,bird,10.0,10.0,100,100")?;
+        command::add(&repo, &bbox_file)?;
+        let staged = api::local::schemas::get_schema_for_path(&repo, None, &bbox_file)?.unwrap();
+        assert_eq!(staged.get_field("label").unwrap().metadata, Some(metadata));
+        assert_eq!(
+            staged.check_field_constraints(tabular::scan_df(&bbox_file, &DFOpts::empty())?)?,
+            vec!["column 'label' has 1 values not in [cat, dog]"]
+        );
+
+        // Clearing every value removes the metadata from the field
+        let cleared = command::schemas::set_field_metadata(
+            &repo,
+            &schema.hash,
+            "label",
+            &FieldMetadata::default(),
+            &[String::from("description"), String::from("values")],
+        )?;
+        assert_eq!(cleared.get_field("label").unwrap().metadata, None);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_schemas_diff_and_log_report_renamed_and_retyped_fields() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let base = api::local::commits::head_commit(&repo)?;
+        let bbox_file = repo
+            .path
+            .join("annotations")
+            .join("train")
+            .join("bounding_box.csv");
+        test::modify_txt_file(
+            &bbox_file,
+            "file,category,min_x,min_y,width,height\ntrain/dog_1.jpg,dog,101.5,32.0,385,330.5\n",
+        )?;
+        command::add(&repo, &bbox_file)?;
+        let head = command::commit(&repo, "Rename label to category")?;
+
+        let diffs = command::schemas::diff(&repo, &base.id, &head.id, None)?;
+        assert_eq!(diffs.len(), 1);
+        let diff = &diffs[0].diff;
+        assert_eq!(
+            diffs[0].path,
+            Path::new("annotations/train/bounding_box.csv")
+        );
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.renamed.len(), 1);
+        assert_eq!(diff.renamed[0].from.name, "label");
+        assert_eq!(diff.renamed[0].to.name, "category");
+        assert_eq!(diff.retyped.len(), 1);
+        assert_eq!(diff.retyped[0].to.name, "height");
+
+        // Files that did not change schema are not reported
+        let diffs = command::schemas::diff(&repo, &base.id, &base.id, None)?;
+        assert!(diffs.is_empty());
+
+        // Newest first, the first version has all of its fields added
+        let log = command::schemas::log(&repo, &bbox_file)?;
+        assert_eq!(log.len(), 2);
+        assert_eq!(log[0].commit.id, head.id);
+        assert_eq!(log[0].diff, *diff);
+        assert_eq!(log[1].diff.added.len(), 6);
+
+        // Changing the rows without changing the fields does not add an entry
+        test::append_line_txt_file(&bbox_file, "train/cat_1.jpg,cat,57.0,35.5,304,427.5")?;
+        command::add(&repo, &bbox_file)?;
+        command::commit(&repo, "Adding a row")?;
+        assert_eq!(command::schemas::log(&repo, &bbox_file)?.len(), 2);
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_files_with_the_same_fields_keep_their_own_dialect() -> Result<(), OxenError> {
+    test::run_empty_local_repo_test(|repo| {
+        let commas = repo.path.join("commas.csv");
+        let semicolons = repo.path.join("semicolons.csv");
+        test::write_txt_file_to_path(&commas, "file,label\n0000.jpg,cat\n0001.jpg,dog\n")?;
+        test::write_txt_file_to_path(&semicolons, "file;label\n0000.jpg;cat\n0001.jpg;dog\n")?;
+        command::add(&repo, &repo.path)?;
+        let base = command::commit(&repo, "Adding the same data in two dialects")?;
+
+        let commas_schema =
+            api::local::schemas::get_schema_for_path(&repo, Some(&base), &commas)?.unwrap();
+        let semicolons_schema =
+            api::local::schemas::get_schema_for_path(&repo, Some(&base), &semicolons)?.unwrap();
+        assert_eq!(commas_schema.hash, semicolons_schema.hash);
+        assert!(commas_schema.dialect.is_none());
+        assert_eq!(semicolons_schema.dialect.unwrap().delimiter, ';');
+
+        // Only changing the delimiter is reported as a dialect change, not as changed fields
+        test::modify_txt_file(&commas, "file|label\n0000.jpg|cat\n0001.jpg|dog\n")?;
+        command::add(&repo, &commas)?;
+        let head = command::commit(&repo, "Pipe separated")?;
+
+        let diffs = command::schemas::diff(&repo, &base.id, &head.id, None)?;
+        assert_eq!(diffs.len(), 1);
+        let diff = &diffs[0].diff;
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.dialect.as_ref().unwrap().to.delimiter, '|');
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_diff_range_reads_each_version_with_its_own_dialect() -> Result<(), OxenError> {
+    test::run_empty_local_repo_test(|repo| {
+        let labels = repo.path.join("labels.csv");
+        test::write_txt_file_to_path(&labels, "file,label\n0000.jpg,cat\n0001.jpg,dog\n")?;
+        command::add(&repo, &labels)?;
+        let base = command::commit(&repo, "Comma separated")?;
+
+        test::modify_txt_file(
+            &labels,
+            "file|label\n0000.jpg|cat\n0001.jpg|dog\n0002.jpg|cat\n",
+        )?;
+        command::add(&repo, &labels)?;
+        let head = command::commit(&repo, "Pipe separated with a new row")?;
+
+        // Both sides have the same columns, only the new row is reported
+        let range = format!("{}..{}", base.id, head.id);
+        let diff = command::diff(&repo, Some(&range), &labels)?;
+        assert!(diff.contains("Added Rows"));
+        assert!(diff.contains("0002.jpg"));
+        assert!(!diff.contains("Columns"));
+        assert!(!diff.contains("Removed Rows"));
+
+        // The same against a commit id, with HEAD as the original
+        let diff = command::diff(&repo, Some(&base.id), &labels)?;
+        assert!(diff.contains("Removed Rows"));
+        assert!(!diff.contains("Columns"));
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_column_stats_cacher_computes_and_reuses_stats() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let commit = api::local::commits::head_commit(&repo)?;
+        commit_cacher::run_all(&repo, &commit)?;
+
+        let bbox_path = Path::new("annotations")
+            .join("train")
+            .join("bounding_box.csv");
+        let stats = column_stats::get_stats(&repo, &commit, &bbox_path)?.unwrap();
+        assert_eq!(stats.num_rows, 6);
+        // Cached with the rest of the cacher output, not next to the history dbs
+        let commit_dir = util::fs::oxen_hidden_dir(&repo.path)
+            .join(constants::HISTORY_DIR)
+            .join(&commit.id);
+        assert!(commit_dir
+            .join(constants::CACHE_DIR)
+            .join(constants::COLUMN_STATS_DIR)
+            .exists());
+        assert!(!commit_dir.join(constants::COLUMN_STATS_DIR).exists());
+        let label = &stats.columns[1];
+        assert_eq!(label.name, "label");
+        assert_eq!(label.num_distinct, Some(2));
+        assert_eq!(label.top_values[0].value, "dog");
+        assert_eq!(label.top_values[0].count, 4);
+        let width = &stats.columns[4];
+        assert_eq!(width.min.as_deref(), Some("246"));
+        assert_eq!(width.max.as_deref(), Some("386"));
+
+        // Unchanged files carry their stats over to the next commit
+        let readme = repo.path.join("README.md");
+        test::append_line_txt_file(&readme, "More about the data")?;
+        command::add(&repo, &readme)?;
+        let next = command::commit(&repo, "Update the readme")?;
+        commit_cacher::run_all(&repo, &next)?;
+        assert_eq!(
+            column_stats::get_stats(&repo, &next, &bbox_path)?,
+            Some(stats.clone())
+        );
+        let statuses = commit_cacher::get_all_statuses(&repo, &next)?;
+        let bbox_status = statuses
+            .iter()
+            .find(|s| s.name == "COLUMN_STATS" && s.path.as_deref() == Some(bbox_path.as_path()))
+            .unwrap();
+        assert_eq!(bbox_status.status, CacherStatusType::Success);
+        assert_eq!(
+            commit_cacher::get_status(&repo, &next)?,
+            Some(CacherStatusType::Success)
+        );
+        assert_eq!(
+            command::df::stats_revision(
+                &repo,
+                repo.path.join(&bbox_path),
+                &next.id,
+                DFOpts::empty()
+            )?,
+            stats
+        );
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_image_meta_data_cacher_saves_meta_data_and_thumbnails() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let commit = api::local::commits::head_commit(&repo)?;
+        commit_cacher::run_all(&repo, &commit)?;
+
+        let dog_path = Path::new("train").join("dog_1.jpg");
+        let entry = api::local::entries::get_commit_entry(&repo, &commit, &dog_path)?.unwrap();
+        let meta_data = image_meta_data::get_meta_data(&repo, &entry)?.unwrap();
+        assert_eq!(meta_data.format, "jpeg");
+        assert_eq!(meta_data.channels, 3);
+        assert_eq!(meta_data.width, 381);
+        assert_eq!(meta_data.height, 499);
+
+        let thumbnail_path = image_meta_data::get_thumbnail_path(&repo, &entry).unwrap();
+        let thumbnail = image::open(thumbnail_path).unwrap();
+        assert!(thumbnail.width() < image_meta_data::THUMBNAIL_SIZE);
+        assert_eq!(thumbnail.height(), image_meta_data::THUMBNAIL_SIZE);
+
+        // Directory listings include the meta data of images
+        let dir_entry = api::local::entries::get_dir_entry(&repo, &commit, &dog_path)?;
+        assert_eq!(dir_entry.image_meta_data, Some(meta_data));
+
+        // Files that are not images have none
+        let readme_entry =
+            api::local::entries::get_dir_entry(&repo, &commit, Path::new("README.md"))?;
+        assert!(readme_entry.image_meta_data.is_none());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_repo_stats_cacher_records_size_history() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let commit = api::local::commits::head_commit(&repo)?;
+        commit_cacher::run_all(&repo, &commit)?;
+
+        let stats = repo_stats::get_stats(&repo, &commit)?.unwrap();
+        assert_eq!(stats, repo_stats::compute_stats(&repo, &commit)?);
+        let commit_dir = util::fs::oxen_hidden_dir(&repo.path)
+            .join(constants::HISTORY_DIR)
+            .join(&commit.id);
+        assert!(commit_dir
+            .join(constants::CACHE_DIR)
+            .join(constants::REPO_STATS_FILE)
+            .exists());
+        assert!(stats.data_types.contains_key("image"));
+        let train = stats.dir("train").unwrap();
+        assert_eq!(train.file_count, 5);
+        assert!(stats
+            .subdirs("")
+            .iter()
+            .any(|d| d.path == Path::new("train")));
+
+        // Row counts come from the column stats
+        let bbox_path = Path::new("annotations")
+            .join("train")
+            .join("bounding_box.csv");
+        let bbox = stats
+            .tabular_files
+            .iter()
+            .find(|f| f.path == bbox_path)
+            .unwrap();
+        assert_eq!(bbox.num_rows, Some(6));
+
+        // Adding a file grows the repo in the next commit
+        let notes = repo.path.join("notes.txt");
+        test::write_txt_file_to_path(&notes, "More about the data")?;
+        command::add(&repo, &notes)?;
+        let next = command::commit(&repo, "Adding notes")?;
+        commit_cacher::run_all(&repo, &next)?;
+
+        let history = command::du::du_history(&repo)?;
+        let last = history.last().unwrap();
+        assert_eq!(last.commit.id, next.id);
+        assert!(last.stats.data_size > stats.data_size);
+        assert_eq!(last.stats.file_count, stats.file_count + 1);
+
+        let usage = command::du::format_usage(&last.stats, "train")?;
+        assert!(usage.contains("dog_1.jpg"));
+        assert!(command::du::format_usage(&last.stats, "does-not-exist").is_err());
+
+        Ok(())
+    })
+}
+
+#[test]
+fn test_du_caches_stats_with_row_counts_on_local_commits() -> Result<(), OxenError> {
+    test::run_training_data_repo_test_fully_committed(|repo| {
+        let commit = api::local::commits::head_commit(&repo)?;
+        assert!(repo_stats::get_stats(&repo, &commit)?.is_none());
+
+        // The cachers were never run on the commit, du runs and persists them
+        let stats = command::du::du(&repo)?;
+        let bbox_path = Path::new("annotations")
+            .join("train")
+            .join("bounding_box.csv");
+        let bbox = stats
+            .tabular_files
+            .iter()
+            .find(|f| f.path == bbox_path)
+            .unwrap();
+        assert_eq!(bbox.num_rows, Some(6));
+        assert_eq!(repo_stats::get_stats(&repo, &commit)?, Some(stats));
+
+        // Every commit in the history is cached once it has been listed
+        let history = command::du::du_history(&repo)?;
+        for entry in history.iter() {
+            assert_eq!(
+                repo_stats::get_stats(&repo, &entry.commit)?.as_ref(),
+                Some(&entry.stats)
+            );
+        }
+
+        Ok(())
+    })
+}